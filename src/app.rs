@@ -2,9 +2,36 @@ use crate::{cli, container, error::AppError};
 
 pub fn run() -> Result<(), AppError> {
     let args = std::env::args().skip(1).collect::<Vec<_>>();
-    let sources = crate::config::load_sources()?;
-    let parsed =
-        cli::parse_args_with_sources(args, &sources.defaults, &sources.file, &sources.env)?;
+    let sources =
+        match crate::config::load_sources_for_profile(cli::profile_from_args(&args).as_deref()) {
+            Ok(sources) => sources,
+            Err(err) => {
+                let fallback = crate::config::LoadedConfigSources::default();
+                return match cli::parse_args_with_loaded_sources(args, &fallback) {
+                    Ok(parsed) => match &parsed.action {
+                        cli::Action::ConfigValidate => validate_config(),
+                        cli::Action::ConfigEdit => edit_config(),
+                        cli::Action::ConfigSet(action) => set_config(action),
+                        cli::Action::ConfigInit(action) => init_config(action),
+                        cli::Action::ConfigSchema => print_config_schema(),
+                        cli::Action::ConfigMigrate(action) => migrate_config(action),
+                        _ => Err(err),
+                    },
+                    Err(_) => Err(err),
+                };
+            }
+        };
+    let parsed = cli::parse_args_with_loaded_sources(args, &sources)?;
+
+    let warnings = sources.warnings();
+    if !warnings.is_empty() && !matches!(parsed.action, cli::Action::ConfigMigrate(_)) {
+        for warning in &warnings {
+            eprintln!("WARNING: {}", warning);
+        }
+        eprintln!(
+            "WARNING: run `dungeon config migrate` (with --project for .dungeon.toml) to update the config format"
+        );
+    }
 
     if parsed.show_help {
         return Ok(());
@@ -32,6 +59,147 @@ pub fn run() -> Result<(), AppError> {
             container::engine::reset_cache_volume(&settings)
         }
         cli::Action::Run => run_container_session(parsed, &sources),
+        cli::Action::Ps(action) => {
+            let settings = crate::config::resolve_global_settings(&parsed.settings, &sources)?;
+            let containers = container::ps::list_containers(&settings, action.all)?;
+            if action.json {
+                println!("{}", container::ps::render_json(&containers)?);
+            } else {
+                println!("{}", container::ps::render_table(&containers));
+            }
+            Ok(())
+        }
+        cli::Action::Diff(action) => {
+            let workspaces = workspace_dirs(&action.paths)?;
+            for workspace in &workspaces {
+                let changes = container::workspace::changes(workspace)?;
+                if workspaces.len() > 1 {
+                    println!("{}:", workspace.display());
+                }
+                if changes.is_empty() {
+                    println!("no changes in {}", workspace.display());
+                }
+                for change in changes {
+                    println!("{}", change);
+                }
+            }
+            Ok(())
+        }
+        cli::Action::Apply(action) => {
+            for workspace in workspace_dirs(&action.paths)? {
+                if action.discard {
+                    container::workspace::discard(&workspace)?;
+                    println!("discarded changes for {}", workspace.display());
+                    continue;
+                }
+                let changes = container::workspace::apply(&workspace)?;
+                for change in &changes {
+                    println!("{}", change);
+                }
+                println!(
+                    "applied {} change(s) to {}",
+                    changes.len(),
+                    workspace.display()
+                );
+            }
+            Ok(())
+        }
+        cli::Action::ConfigShow(action) => {
+            let trace = crate::config::trace(&action.groups, &parsed.settings, &sources)?;
+            if action.json {
+                let rendered = serde_json::to_string_pretty(&trace)
+                    .map_err(|err| AppError::message(format!("render config: {}", err)))?;
+                println!("{}", rendered);
+            } else {
+                println!("{}", crate::config::render_trace(&trace));
+            }
+            Ok(())
+        }
+        cli::Action::ConfigValidate => validate_config(),
+        cli::Action::ConfigGet(action) => {
+            println!(
+                "{}",
+                crate::config::get_config_value(&crate::config::config_path()?, &action.key)?
+            );
+            Ok(())
+        }
+        cli::Action::ConfigSet(action) => set_config(action),
+        cli::Action::ConfigEdit => edit_config(),
+        cli::Action::ConfigInit(action) => init_config(action),
+        cli::Action::ConfigSchema => print_config_schema(),
+        cli::Action::ConfigMigrate(action) => migrate_config(action),
+        cli::Action::GroupsList => {
+            println!(
+                "{}",
+                crate::config::render_group_list(&sources.group_infos()?)
+            );
+            Ok(())
+        }
+        cli::Action::GroupsShow(action) => {
+            let info = sources
+                .group_infos()?
+                .into_iter()
+                .find(|info| info.name == action.name)
+                .ok_or_else(|| {
+                    AppError::message(format!("ERROR: unknown group \"{}\"", action.name))
+                })?;
+            println!(
+                "{}",
+                crate::config::render_group(&info, &sources.group_definitions()?)?
+            );
+            Ok(())
+        }
+        cli::Action::GroupsGraph(action) => {
+            println!(
+                "{}",
+                crate::config::render_group_graph(&sources.group_definitions()?, action.format)
+            );
+            Ok(())
+        }
+        cli::Action::Trust => {
+            let path = sources.project_path.as_ref().ok_or_else(|| {
+                AppError::message(
+                    "ERROR: no .dungeon.toml found in the current directory or its parents",
+                )
+            })?;
+            crate::config::trust_project_config(path)?;
+            println!("trusted {}", path.display());
+            Ok(())
+        }
+        cli::Action::Attach(action) => {
+            let settings = crate::config::resolve_global_settings(&parsed.settings, &sources)?;
+            container::ps::check_session_container(&settings, &action.session)?;
+            container::engine::run_container_command(container::engine::build_attach_command(
+                &settings,
+                &action.session,
+            ))
+        }
+        cli::Action::Exec(action) => {
+            let settings = crate::config::resolve_global_settings(&parsed.settings, &sources)?;
+            container::ps::check_session_container(&settings, &action.session)?;
+            container::engine::run_container_command(container::engine::build_exec_command(
+                &settings,
+                &action.session,
+                action.command.as_deref(),
+            ))
+        }
+        cli::Action::Stop(action) => {
+            let settings = crate::config::resolve_global_settings(&parsed.settings, &sources)?;
+            container::ps::check_session_container(&settings, &action.session)?;
+            container::engine::run_container_command(container::engine::build_stop_command(
+                &settings,
+                &action.session,
+            ))
+        }
+        cli::Action::Remove(action) => {
+            let settings = crate::config::resolve_global_settings(&parsed.settings, &sources)?;
+            container::ps::check_session_container(&settings, &action.session)?;
+            container::engine::run_container_command(container::engine::build_remove_command(
+                &settings,
+                &action.session,
+                action.force,
+            ))
+        }
     }
 }
 
@@ -41,24 +209,193 @@ fn run_container_session(
 ) -> Result<(), AppError> {
     let resolved = crate::config::resolve(&parsed, sources)?;
 
+    if parsed.explain_groups {
+        let explanations = crate::config::explain_groups(&parsed, sources)?;
+        if explanations.is_empty() {
+            println!("no groups enabled");
+        }
+        for explanation in explanations {
+            println!("{}", explanation);
+        }
+        return Ok(());
+    }
+
+    let worktree = match &resolved.worktree {
+        Some(branch) => Some(container::worktree::Worktree::plan(
+            &std::env::current_dir()?,
+            branch,
+            resolved.session_name.as_deref(),
+        )?),
+        None => None,
+    };
+    let worktree_path = worktree.as_ref().map(|worktree| worktree.path.as_path());
+
     if parsed.debug {
         let mut settings = resolved.settings.clone();
         let reservations = container::engine::reserve_dynamic_ports(&mut settings)?;
-        let spec = container::engine::build_container_command(
+        let spec = container::engine::build_resolved_container_command(
             &settings,
-            &resolved.paths,
-            resolved.skip_cwd,
+            &resolved,
+            worktree_path,
         )?;
         drop(reservations);
         println!("{} {}", spec.program, spec.args.join(" "));
         return Ok(());
     }
 
+    let Some(worktree) = worktree else {
+        return run_prepared_session(&resolved, None);
+    };
+    worktree.create(|| container::ps::list_containers(&resolved.settings, false))?;
+    let result = run_prepared_session(&resolved, Some(&worktree.path));
+    let finished = worktree.finish(resolved.detach);
+    result.and(finished)
+}
+
+// The command is built, and so validated, before any overlay or snapshot state
+// is created, so a rejected run leaves nothing behind for `dungeon diff`.
+fn run_prepared_session(
+    resolved: &crate::config::ResolvedConfig,
+    worktree: Option<&std::path::Path>,
+) -> Result<(), AppError> {
     let mut settings = resolved.settings.clone();
     let reservations = container::engine::reserve_dynamic_ports(&mut settings)?;
-    let spec =
-        container::engine::build_container_command(&settings, &resolved.paths, resolved.skip_cwd)?;
-    container::engine::run_reserved_container_command(spec, reservations)?;
+    let spec = container::engine::build_resolved_container_command(&settings, resolved, worktree)?;
+    container::engine::prepare_workspaces(&resolved.settings, resolved, worktree)?;
+    container::engine::run_reserved_container_command(spec, reservations)
+}
+
+fn workspace_dirs(paths: &[String]) -> Result<Vec<std::path::PathBuf>, AppError> {
+    let cwd = std::env::current_dir()?;
+    if paths.is_empty() {
+        return Ok(vec![cwd]);
+    }
+    Ok(paths.iter().map(|path| cwd.join(path)).collect())
+}
+
+fn set_config(action: &cli::ConfigSetAction) -> Result<(), AppError> {
+    crate::config::edit_config_file(
+        &crate::config::config_path()?,
+        &action.key,
+        action.edit.clone(),
+    )
+}
+
+fn print_config_schema() -> Result<(), AppError> {
+    let rendered = serde_json::to_string_pretty(&crate::config::config_schema())
+        .map_err(|err| AppError::message(format!("render config schema: {}", err)))?;
+    println!("{}", rendered);
+    Ok(())
+}
+
+fn migrate_config(action: &cli::ConfigMigrateAction) -> Result<(), AppError> {
+    let paths = if action.project {
+        let path =
+            crate::config::find_project_config(&std::env::current_dir()?).ok_or_else(|| {
+                AppError::message(
+                    "ERROR: no .dungeon.toml found in the current directory or its parents",
+                )
+            })?;
+        vec![path]
+    } else {
+        crate::config::user_config_files()?
+    };
+    if paths.is_empty() {
+        println!(
+            "{} does not exist; nothing to migrate",
+            crate::config::config_path()?.display()
+        );
+    }
+
+    for path in paths {
+        let trusted = action.project
+            && crate::config::load_from_project(&path)
+                .is_ok_and(|(_, state)| state == crate::config::TrustState::Trusted);
+        let changes = crate::config::migrate_config_file(&path)?;
+        if changes.is_empty() {
+            println!(
+                "{}: already version {}",
+                path.display(),
+                crate::config::CONFIG_VERSION
+            );
+            continue;
+        }
+        println!("migrated {}", path.display());
+        for change in changes {
+            println!("  {}", change);
+        }
+        if trusted {
+            crate::config::trust_project_config(&path)?;
+        }
+    }
+    Ok(())
+}
 
+fn init_config(action: &cli::ConfigInitAction) -> Result<(), AppError> {
+    let dir = std::env::current_dir()?;
+    let path = if action.project {
+        crate::config::project_config_path(&dir)
+    } else {
+        crate::config::config_path()?
+    };
+    crate::config::init_config(&path, &dir, action.project, action.force)?;
+    println!("wrote {}", path.display());
+    if action.project {
+        crate::config::trust_project_config(&path)?;
+        println!("trusted {}", path.display());
+    }
     Ok(())
 }
+
+fn edit_config() -> Result<(), AppError> {
+    let path = crate::config::config_path()?;
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| AppError::message("ERROR: $VISUAL or $EDITOR is empty"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|err| AppError::message(format!("ERROR: failed to start {}: {}", program, err)))?;
+    if !status.success() {
+        return Err(AppError::message(format!(
+            "ERROR: {} exited with {}",
+            program, status
+        )));
+    }
+    if !path.exists() {
+        return Ok(());
+    }
+    report_diagnostics(std::slice::from_ref(&path))
+}
+
+fn validate_config() -> Result<(), AppError> {
+    report_diagnostics(&crate::config::config_files()?)
+}
+
+fn report_diagnostics(paths: &[std::path::PathBuf]) -> Result<(), AppError> {
+    let diagnostics = crate::config::validate_files(paths)?;
+    if diagnostics.is_empty() {
+        for path in paths {
+            println!("{}: ok", path.display());
+        }
+        return Ok(());
+    }
+
+    let mut lines = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect::<Vec<_>>();
+    lines.push(format!(
+        "ERROR: found {} problem(s) in config",
+        diagnostics.len()
+    ));
+    Err(AppError::message(lines.join("\n")))
+}
@@ -5,61 +5,47 @@ use clap::{Arg, ArgAction, ArgMatches, Command};
 use crate::{config, error::AppError};
 
 use super::constants::{
-    ARG_PATHS, FLAG_CACHE, FLAG_COMMAND, FLAG_CONTEXT, FLAG_DEBUG, FLAG_DYNAMIC_PORT, FLAG_ENV,
-    FLAG_ENV_FILE, FLAG_EXPOSE_HOST_PORT, FLAG_HELP, FLAG_IMAGE, FLAG_MOUNT,
-    FLAG_MOUNT_GIT_METADATA, FLAG_NO_CACHE, FLAG_NO_MOUNT_GIT_METADATA, FLAG_PODMAN_ARG, FLAG_PORT,
-    FLAG_RUN_ARG, FLAG_SKIP_CWD, FLAG_TAG, FLAG_VERSION, SUBCOMMAND_CACHE, SUBCOMMAND_CACHE_RESET,
-    SUBCOMMAND_IMAGE, SUBCOMMAND_IMAGE_BUILD, SUBCOMMAND_RUN,
+    ARG_GROUP, ARG_KEY, ARG_PATHS, ARG_SESSION, ARG_VALUES, FLAG_ALL, FLAG_ALLOW_SENSITIVE_MOUNT,
+    FLAG_APPEND, FLAG_COMMAND, FLAG_CONTEXT, FLAG_DEBUG, FLAG_DETACH, FLAG_DISCARD,
+    FLAG_EXPLAIN_GROUPS, FLAG_FORCE, FLAG_FORMAT, FLAG_GROUPS, FLAG_HELP, FLAG_JSON, FLAG_NAME,
+    FLAG_NO_CACHE, FLAG_PODMAN_ARG, FLAG_PROFILE, FLAG_PROJECT, FLAG_SKIP_CWD, FLAG_TAG,
+    FLAG_VERSION, FLAG_WITHOUT, FLAG_WORKTREE, NEGATED_GROUP_PREFIX, SUBCOMMAND_APPLY,
+    SUBCOMMAND_ATTACH, SUBCOMMAND_CACHE, SUBCOMMAND_CACHE_RESET, SUBCOMMAND_CONFIG,
+    SUBCOMMAND_CONFIG_EDIT, SUBCOMMAND_CONFIG_GET, SUBCOMMAND_CONFIG_INIT,
+    SUBCOMMAND_CONFIG_MIGRATE, SUBCOMMAND_CONFIG_SCHEMA, SUBCOMMAND_CONFIG_SET,
+    SUBCOMMAND_CONFIG_SHOW, SUBCOMMAND_CONFIG_UNSET, SUBCOMMAND_CONFIG_VALIDATE, SUBCOMMAND_DIFF,
+    SUBCOMMAND_EXEC, SUBCOMMAND_GROUPS, SUBCOMMAND_GROUPS_GRAPH, SUBCOMMAND_GROUPS_LIST,
+    SUBCOMMAND_GROUPS_SHOW, SUBCOMMAND_IMAGE, SUBCOMMAND_IMAGE_BUILD, SUBCOMMAND_PS, SUBCOMMAND_RM,
+    SUBCOMMAND_RUN, SUBCOMMAND_STOP, SUBCOMMAND_TRUST,
 };
 
 pub(crate) fn print_targeted_help(
     matches: &ArgMatches,
     group_defs: &BTreeMap<String, config::GroupConfig>,
 ) -> Result<bool, AppError> {
-    if matches.get_flag(FLAG_HELP) {
-        print_help(base_command(group_defs))?;
-        return Ok(true);
-    }
-
-    if let Some((sub_name, sub_matches)) = matches.subcommand() {
-        match sub_name {
-            SUBCOMMAND_RUN => {
-                if sub_matches.get_flag(FLAG_HELP) {
-                    print_help(run_subcommand(group_defs))?;
-                    return Ok(true);
-                }
-            }
-            SUBCOMMAND_IMAGE => {
-                if sub_matches.get_flag(FLAG_HELP) {
-                    print_help(image_subcommand())?;
-                    return Ok(true);
-                }
-                if let Some((image_sub_name, image_sub_matches)) = sub_matches.subcommand()
-                    && image_sub_name == SUBCOMMAND_IMAGE_BUILD
-                    && image_sub_matches.get_flag(FLAG_HELP)
-                {
-                    print_help(image_build_subcommand())?;
-                    return Ok(true);
-                }
-            }
-            SUBCOMMAND_CACHE => {
-                if sub_matches.get_flag(FLAG_HELP) {
-                    print_help(cache_subcommand())?;
-                    return Ok(true);
-                }
-                if let Some((cache_sub_name, cache_sub_matches)) = sub_matches.subcommand()
-                    && cache_sub_name == SUBCOMMAND_CACHE_RESET
-                    && cache_sub_matches.get_flag(FLAG_HELP)
-                {
-                    print_help(cache_reset_subcommand())?;
-                    return Ok(true);
-                }
-            }
-            _ => {}
+    let mut help_path = matches.get_flag(FLAG_HELP).then(Vec::new);
+    let mut path = Vec::new();
+    let mut current = matches;
+    while let Some((sub_name, sub_matches)) = current.subcommand() {
+        path.push(sub_name.to_string());
+        if sub_matches.get_flag(FLAG_HELP) {
+            help_path = Some(path.clone());
         }
+        current = sub_matches;
     }
 
-    Ok(false)
+    let Some(help_path) = help_path else {
+        return Ok(false);
+    };
+    let mut cmd = base_command(group_defs);
+    for name in help_path {
+        cmd = cmd
+            .find_subcommand(&name)
+            .cloned()
+            .ok_or_else(|| AppError::message(format!("ERROR: unknown subcommand '{}'", name)))?;
+    }
+    print_help(cmd)?;
+    Ok(true)
 }
 
 pub(crate) fn base_command(group_defs: &BTreeMap<String, config::GroupConfig>) -> Command {
@@ -82,9 +68,28 @@ pub(crate) fn base_command(group_defs: &BTreeMap<String, config::GroupConfig>) -
                 .global(true)
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new(FLAG_PROFILE)
+                .long(FLAG_PROFILE)
+                .value_name("PROFILE")
+                .help("Use a profile from config.toml (or set DUNGEON_PROFILE)")
+                .help_heading("Options")
+                .global(true)
+                .num_args(1),
+        )
         .subcommand(run_subcommand(group_defs))
         .subcommand(image_subcommand())
         .subcommand(cache_subcommand())
+        .subcommand(attach_subcommand())
+        .subcommand(exec_subcommand())
+        .subcommand(stop_subcommand())
+        .subcommand(rm_subcommand())
+        .subcommand(ps_subcommand())
+        .subcommand(diff_subcommand())
+        .subcommand(apply_subcommand())
+        .subcommand(trust_subcommand())
+        .subcommand(config_subcommand())
+        .subcommand(groups_subcommand())
 }
 
 fn print_help(mut cmd: Command) -> Result<(), AppError> {
@@ -112,96 +117,26 @@ fn run_subcommand(group_defs: &BTreeMap<String, config::GroupConfig>) -> Command
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new(FLAG_COMMAND)
-                .long(FLAG_COMMAND)
-                .help("Run a command inside the container")
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Set),
+            Arg::new(FLAG_EXPLAIN_GROUPS)
+                .long(FLAG_EXPLAIN_GROUPS)
+                .help("Print why each group is enabled without running")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new(FLAG_IMAGE)
-                .long(FLAG_IMAGE)
-                .help("Select the container image")
-                .help_heading("Configurations")
+            Arg::new(FLAG_NAME)
+                .long(FLAG_NAME)
+                .help("Keep the container as a named session")
+                .help_heading("Sessions")
                 .num_args(1)
                 .action(ArgAction::Set),
         )
         .arg(
-            Arg::new(FLAG_PORT)
-                .long(FLAG_PORT)
-                .help("Publish a container port (repeatable)")
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_DYNAMIC_PORT)
-                .long(FLAG_DYNAMIC_PORT)
-                .help(
-                    "Publish a dynamic loopback port and set its environment variable (repeatable)",
-                )
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_EXPOSE_HOST_PORT)
-                .long(FLAG_EXPOSE_HOST_PORT)
-                .help("Expose a host loopback TCP port or range inside the container (repeatable)")
-                .help_heading("Network")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_CACHE)
-                .long(FLAG_CACHE)
-                .help("Mount a cache volume target (repeatable)")
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_MOUNT)
-                .long(FLAG_MOUNT)
-                .help("Bind-mount a host path (repeatable)")
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_ENV)
-                .long(FLAG_ENV)
-                .help("Add a container environment variable (repeatable)")
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_ENV_FILE)
-                .long(FLAG_ENV_FILE)
-                .help("Add a container env-file (repeatable)")
-                .help_heading("Configurations")
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_PODMAN_ARG)
-                .long(FLAG_PODMAN_ARG)
-                .help("Append an extra podman argument before the subcommand (repeatable)")
-                .help_heading("Configurations")
-                .allow_hyphen_values(true)
-                .num_args(1)
-                .action(ArgAction::Append),
-        )
-        .arg(
-            Arg::new(FLAG_RUN_ARG)
-                .long(FLAG_RUN_ARG)
-                .help("Append an extra podman run argument (repeatable)")
-                .help_heading("Configurations")
-                .allow_hyphen_values(true)
-                .num_args(1)
-                .action(ArgAction::Append),
+            Arg::new(FLAG_DETACH)
+                .long(FLAG_DETACH)
+                .help("Start the named session in the background")
+                .help_heading("Sessions")
+                .action(ArgAction::SetTrue),
         )
         .arg(
             Arg::new(FLAG_SKIP_CWD)
@@ -211,18 +146,31 @@ fn run_subcommand(group_defs: &BTreeMap<String, config::GroupConfig>) -> Command
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new(FLAG_MOUNT_GIT_METADATA)
-                .long(FLAG_MOUNT_GIT_METADATA)
-                .help("Mount external Git metadata for worktrees")
+            Arg::new(FLAG_ALLOW_SENSITIVE_MOUNT)
+                .long(FLAG_ALLOW_SENSITIVE_MOUNT)
+                .help("Allow mounts that overlap sensitive_paths for this run")
                 .help_heading("Configurations")
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new(FLAG_NO_MOUNT_GIT_METADATA)
-                .long(FLAG_NO_MOUNT_GIT_METADATA)
-                .help("Do not mount external Git metadata")
+            Arg::new(FLAG_WORKTREE)
+                .long(FLAG_WORKTREE)
+                .value_name("BRANCH")
+                .help("Run in a new git worktree on BRANCH (default: dungeon/<session or timestamp>)")
                 .help_heading("Configurations")
-                .action(ArgAction::SetTrue),
+                .num_args(0..=1)
+                .default_missing_value("")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new(FLAG_WITHOUT)
+                .long(FLAG_WITHOUT)
+                .value_name("GROUP")
+                .help("Disable a group enabled by configuration (comma-separated, repeatable; same as --no-<group>)")
+                .help_heading("Groups")
+                .num_args(1)
+                .value_delimiter(',')
+                .action(ArgAction::Append),
         )
         .arg(
             Arg::new(ARG_PATHS)
@@ -231,21 +179,64 @@ fn run_subcommand(group_defs: &BTreeMap<String, config::GroupConfig>) -> Command
                 .action(ArgAction::Append),
         );
 
-    let group_names: Vec<String> = group_defs.keys().cloned().collect();
-    for name in group_names {
+    for field in config::SETTING_FIELDS {
+        cmd = cmd.args(setting_args(field));
+    }
+
+    for (name, group) in group_defs {
         let leaked: &'static str = Box::leak(name.clone().into_boxed_str());
         cmd = cmd.arg(
             Arg::new(leaked)
                 .long(leaked)
-                .help(format!("Enable the {} group", name))
+                .help(
+                    group
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| format!("Enable the {} group", name)),
+                )
                 .help_heading("Groups")
                 .action(ArgAction::SetTrue),
         );
+        let negated: &'static str =
+            Box::leak(format!("{}{}", NEGATED_GROUP_PREFIX, name).into_boxed_str());
+        cmd = cmd.arg(
+            Arg::new(negated)
+                .long(negated)
+                .help(format!("Disable the {} group", name))
+                .help_heading("Groups")
+                .hide(true)
+                .action(ArgAction::SetTrue),
+        );
     }
 
     cmd
 }
 
+fn setting_args(field: &config::SettingField) -> Vec<Arg> {
+    let arg = Arg::new(field.flag)
+        .long(field.flag)
+        .help(field.help)
+        .help_heading(field.heading)
+        .allow_hyphen_values(field.hyphen_values);
+    match field.kind {
+        config::SettingKind::Scalar => vec![arg.num_args(1).action(ArgAction::Set)],
+        config::SettingKind::List => vec![arg.num_args(1).action(ArgAction::Append)],
+        config::SettingKind::Flag => {
+            let mut args = vec![arg.action(ArgAction::SetTrue)];
+            if let Some(negated_flag) = field.negated_flag {
+                args.push(
+                    Arg::new(negated_flag)
+                        .long(negated_flag)
+                        .help(field.negated_help)
+                        .help_heading(field.heading)
+                        .action(ArgAction::SetTrue),
+                );
+            }
+            args
+        }
+    }
+}
+
 fn image_subcommand() -> Command {
     Command::new(SUBCOMMAND_IMAGE)
         .disable_help_flag(true)
@@ -337,3 +328,400 @@ fn cache_reset_subcommand() -> Command {
                 .action(ArgAction::Append),
         )
 }
+
+fn attach_subcommand() -> Command {
+    session_subcommand(SUBCOMMAND_ATTACH, "Attach to a running named session")
+}
+
+fn exec_subcommand() -> Command {
+    session_subcommand(SUBCOMMAND_EXEC, "Open a shell in a running named session").arg(
+        Arg::new(FLAG_COMMAND)
+            .long(FLAG_COMMAND)
+            .help("Run a command instead of an interactive shell")
+            .num_args(1)
+            .action(ArgAction::Set),
+    )
+}
+
+fn stop_subcommand() -> Command {
+    session_subcommand(SUBCOMMAND_STOP, "Stop a named session")
+}
+
+fn rm_subcommand() -> Command {
+    session_subcommand(SUBCOMMAND_RM, "Remove a named session container").arg(
+        Arg::new(FLAG_FORCE)
+            .long(FLAG_FORCE)
+            .help("Remove the session even if it is running")
+            .action(ArgAction::SetTrue),
+    )
+}
+
+fn session_subcommand(name: &'static str, about: &'static str) -> Command {
+    Command::new(name)
+        .disable_help_flag(true)
+        .about(about)
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_PODMAN_ARG)
+                .long(FLAG_PODMAN_ARG)
+                .help("Append an extra podman argument before the subcommand (repeatable)")
+                .allow_hyphen_values(true)
+                .num_args(1)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new(ARG_SESSION)
+                .help("Session name given to `dungeon run --name`")
+                .required(true)
+                .num_args(1)
+                .action(ArgAction::Set),
+        )
+}
+
+fn ps_subcommand() -> Command {
+    Command::new(SUBCOMMAND_PS)
+        .disable_help_flag(true)
+        .about("List dungeon containers")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_PODMAN_ARG)
+                .long(FLAG_PODMAN_ARG)
+                .help("Append an extra podman argument before the subcommand (repeatable)")
+                .allow_hyphen_values(true)
+                .num_args(1)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new(FLAG_ALL)
+                .long(FLAG_ALL)
+                .help("Include stopped containers")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_JSON)
+                .long(FLAG_JSON)
+                .help("Print containers as JSON")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn diff_subcommand() -> Command {
+    workspace_subcommand(
+        SUBCOMMAND_DIFF,
+        "Show changes made in an overlay or snapshot workspace",
+    )
+}
+
+fn apply_subcommand() -> Command {
+    workspace_subcommand(
+        SUBCOMMAND_APPLY,
+        "Copy changes from an overlay or snapshot workspace back to the host",
+    )
+    .arg(
+        Arg::new(FLAG_DISCARD)
+            .long(FLAG_DISCARD)
+            .help("Drop the changes instead of applying them")
+            .action(ArgAction::SetTrue),
+    )
+}
+
+fn workspace_subcommand(name: &'static str, about: &'static str) -> Command {
+    Command::new(name)
+        .disable_help_flag(true)
+        .about(about)
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(ARG_PATHS)
+                .help("Workspace paths (default: current directory)")
+                .num_args(0..)
+                .action(ArgAction::Append),
+        )
+}
+
+fn trust_subcommand() -> Command {
+    Command::new(SUBCOMMAND_TRUST)
+        .disable_help_flag(true)
+        .about("Trust the project .dungeon.toml found from the current directory")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn config_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG)
+        .disable_help_flag(true)
+        .about("Inspect dungeon configuration")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(config_show_subcommand())
+        .subcommand(config_validate_subcommand())
+        .subcommand(config_get_subcommand())
+        .subcommand(config_set_subcommand())
+        .subcommand(config_unset_subcommand())
+        .subcommand(config_edit_subcommand())
+        .subcommand(config_init_subcommand())
+        .subcommand(config_schema_subcommand())
+        .subcommand(config_migrate_subcommand())
+}
+
+fn config_show_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_SHOW)
+        .disable_help_flag(true)
+        .about("Show the resolved settings and where each value comes from")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_GROUPS)
+                .long(FLAG_GROUPS)
+                .help("Enable groups as with their run flags (comma-separated, repeatable)")
+                .num_args(1)
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new(FLAG_JSON)
+                .long(FLAG_JSON)
+                .help("Print settings as JSON")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn groups_subcommand() -> Command {
+    Command::new(SUBCOMMAND_GROUPS)
+        .disable_help_flag(true)
+        .about("Inspect configured groups")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new(SUBCOMMAND_GROUPS_LIST)
+                .disable_help_flag(true)
+                .about("List groups with their description and source file")
+                .arg(
+                    Arg::new(FLAG_HELP)
+                        .long(FLAG_HELP)
+                        .help("Show help information")
+                        .help_heading("Options")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new(SUBCOMMAND_GROUPS_SHOW)
+                .disable_help_flag(true)
+                .about("Show a group's settings and the groups it enables")
+                .arg(
+                    Arg::new(FLAG_HELP)
+                        .long(FLAG_HELP)
+                        .help("Show help information")
+                        .help_heading("Options")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new(ARG_GROUP)
+                        .help("Group name")
+                        .required_unless_present(FLAG_HELP)
+                        .num_args(1),
+                ),
+        )
+        .subcommand(
+            Command::new(SUBCOMMAND_GROUPS_GRAPH)
+                .disable_help_flag(true)
+                .about("Print the include_groups dependency graph")
+                .arg(
+                    Arg::new(FLAG_HELP)
+                        .long(FLAG_HELP)
+                        .help("Show help information")
+                        .help_heading("Options")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new(FLAG_FORMAT)
+                        .long(FLAG_FORMAT)
+                        .help("Output format")
+                        .value_parser(["tree", "dot"])
+                        .default_value("tree")
+                        .num_args(1),
+                ),
+        )
+}
+
+fn config_get_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_GET)
+        .disable_help_flag(true)
+        .about("Print a value from the user config file")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(config_key_arg())
+}
+
+fn config_set_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_SET)
+        .disable_help_flag(true)
+        .about("Set a value in the user config file, keeping its formatting")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_APPEND)
+                .long(FLAG_APPEND)
+                .help("Add the values to a list instead of replacing it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(config_key_arg())
+        .arg(
+            Arg::new(ARG_VALUES)
+                .help("New value; list settings take one or more values")
+                .required_unless_present(FLAG_HELP)
+                .allow_hyphen_values(true)
+                .num_args(1..),
+        )
+}
+
+fn config_unset_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_UNSET)
+        .disable_help_flag(true)
+        .about("Remove a value from the user config file")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(config_key_arg())
+}
+
+fn config_edit_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_EDIT)
+        .disable_help_flag(true)
+        .about("Open the user config file in $VISUAL or $EDITOR and validate it")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn config_init_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_INIT)
+        .disable_help_flag(true)
+        .about("Write a commented starter config with settings suggested for this directory")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_PROJECT)
+                .long(FLAG_PROJECT)
+                .help("Write .dungeon.toml in the current directory instead of the user config")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_FORCE)
+                .long(FLAG_FORCE)
+                .help("Overwrite an existing config file")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn config_schema_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_SCHEMA)
+        .disable_help_flag(true)
+        .about("Print a JSON Schema for config files, for editor completion and linting")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn config_migrate_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_MIGRATE)
+        .disable_help_flag(true)
+        .about("Rewrite config files in the current format version")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(FLAG_PROJECT)
+                .long(FLAG_PROJECT)
+                .help("Migrate the project .dungeon.toml instead of the user config")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+fn config_key_arg() -> Arg {
+    Arg::new(ARG_KEY)
+        .help("Setting key such as general.image, codex.mounts or profile.work.image")
+        .required_unless_present(FLAG_HELP)
+        .num_args(1)
+}
+
+fn config_validate_subcommand() -> Command {
+    Command::new(SUBCOMMAND_CONFIG_VALIDATE)
+        .disable_help_flag(true)
+        .about("Check the user and project config files and report every problem")
+        .arg(
+            Arg::new(FLAG_HELP)
+                .long(FLAG_HELP)
+                .help("Show help information")
+                .help_heading("Options")
+                .action(ArgAction::SetTrue),
+        )
+}
@@ -3,55 +3,114 @@ pub(crate) const SUBCOMMAND_IMAGE: &str = "image";
 pub(crate) const SUBCOMMAND_IMAGE_BUILD: &str = "build";
 pub(crate) const SUBCOMMAND_CACHE: &str = "cache";
 pub(crate) const SUBCOMMAND_CACHE_RESET: &str = "reset";
+pub(crate) const SUBCOMMAND_ATTACH: &str = "attach";
+pub(crate) const SUBCOMMAND_EXEC: &str = "exec";
+pub(crate) const SUBCOMMAND_STOP: &str = "stop";
+pub(crate) const SUBCOMMAND_RM: &str = "rm";
+pub(crate) const SUBCOMMAND_PS: &str = "ps";
+pub(crate) const SUBCOMMAND_DIFF: &str = "diff";
+pub(crate) const SUBCOMMAND_APPLY: &str = "apply";
+pub(crate) const SUBCOMMAND_TRUST: &str = "trust";
+pub(crate) const SUBCOMMAND_CONFIG: &str = "config";
+pub(crate) const SUBCOMMAND_CONFIG_SHOW: &str = "show";
+pub(crate) const SUBCOMMAND_CONFIG_VALIDATE: &str = "validate";
+pub(crate) const SUBCOMMAND_CONFIG_GET: &str = "get";
+pub(crate) const SUBCOMMAND_CONFIG_SET: &str = "set";
+pub(crate) const SUBCOMMAND_CONFIG_UNSET: &str = "unset";
+pub(crate) const SUBCOMMAND_CONFIG_EDIT: &str = "edit";
+pub(crate) const SUBCOMMAND_CONFIG_INIT: &str = "init";
+pub(crate) const SUBCOMMAND_CONFIG_SCHEMA: &str = "schema";
+pub(crate) const SUBCOMMAND_CONFIG_MIGRATE: &str = "migrate";
+pub(crate) const SUBCOMMAND_GROUPS: &str = "groups";
+pub(crate) const SUBCOMMAND_GROUPS_LIST: &str = "list";
+pub(crate) const SUBCOMMAND_GROUPS_SHOW: &str = "show";
+pub(crate) const SUBCOMMAND_GROUPS_GRAPH: &str = "graph";
 
 pub(crate) const FLAG_HELP: &str = "help";
 pub(crate) const FLAG_VERSION: &str = "version";
 pub(crate) const FLAG_DEBUG: &str = "debug";
+pub(crate) const FLAG_PROFILE: &str = "profile";
+pub(crate) const FLAG_EXPLAIN_GROUPS: &str = "explain-groups";
 pub(crate) const FLAG_COMMAND: &str = "command";
-pub(crate) const FLAG_IMAGE: &str = "image";
-pub(crate) const FLAG_PORT: &str = "port";
-pub(crate) const FLAG_DYNAMIC_PORT: &str = "dynamic-port";
-pub(crate) const FLAG_EXPOSE_HOST_PORT: &str = "expose-host-port";
-pub(crate) const FLAG_CACHE: &str = "cache";
-pub(crate) const FLAG_MOUNT: &str = "mount";
-pub(crate) const FLAG_ENV: &str = "env";
-pub(crate) const FLAG_ENV_FILE: &str = "env-file";
 pub(crate) const FLAG_PODMAN_ARG: &str = "podman-arg";
-pub(crate) const FLAG_RUN_ARG: &str = "run-arg";
 pub(crate) const FLAG_SKIP_CWD: &str = "skip-cwd";
-pub(crate) const FLAG_MOUNT_GIT_METADATA: &str = "mount-git-metadata";
-pub(crate) const FLAG_NO_MOUNT_GIT_METADATA: &str = "no-mount-git-metadata";
+pub(crate) const FLAG_ALLOW_SENSITIVE_MOUNT: &str = "allow-sensitive-mount";
+pub(crate) const FLAG_WORKTREE: &str = "worktree";
 pub(crate) const FLAG_TAG: &str = "tag";
 pub(crate) const FLAG_NO_CACHE: &str = "no-cache";
 pub(crate) const FLAG_CONTEXT: &str = "context";
+pub(crate) const FLAG_NAME: &str = "name";
+pub(crate) const FLAG_DETACH: &str = "detach";
+pub(crate) const FLAG_FORCE: &str = "force";
+pub(crate) const FLAG_ALL: &str = "all";
+pub(crate) const FLAG_JSON: &str = "json";
+pub(crate) const FLAG_GROUPS: &str = "groups";
+pub(crate) const FLAG_WITHOUT: &str = "without";
+pub(crate) const FLAG_FORMAT: &str = "format";
+pub(crate) const FLAG_APPEND: &str = "append";
+pub(crate) const FLAG_PROJECT: &str = "project";
+pub(crate) const FLAG_DISCARD: &str = "discard";
+pub(crate) const NEGATED_GROUP_PREFIX: &str = "no-";
 pub(crate) const ARG_PATHS: &str = "paths";
+pub(crate) const ARG_SESSION: &str = "session";
+pub(crate) const ARG_KEY: &str = "key";
+pub(crate) const ARG_VALUES: &str = "values";
+pub(crate) const ARG_GROUP: &str = "group";
 
 pub(crate) const RESERVED_GROUP_NAMES: &[&str] = &[
     "general",
+    "include",
     FLAG_HELP,
     FLAG_VERSION,
+    FLAG_PROFILE,
     FLAG_DEBUG,
+    FLAG_EXPLAIN_GROUPS,
     FLAG_COMMAND,
-    FLAG_IMAGE,
-    FLAG_PORT,
-    FLAG_DYNAMIC_PORT,
-    FLAG_EXPOSE_HOST_PORT,
-    FLAG_CACHE,
-    FLAG_MOUNT,
-    FLAG_ENV,
-    FLAG_ENV_FILE,
     FLAG_PODMAN_ARG,
-    FLAG_RUN_ARG,
     FLAG_SKIP_CWD,
-    FLAG_MOUNT_GIT_METADATA,
-    FLAG_NO_MOUNT_GIT_METADATA,
+    FLAG_ALLOW_SENSITIVE_MOUNT,
+    FLAG_WORKTREE,
     FLAG_TAG,
     FLAG_NO_CACHE,
     FLAG_CONTEXT,
+    FLAG_NAME,
+    FLAG_DETACH,
+    FLAG_FORCE,
+    FLAG_ALL,
+    FLAG_JSON,
+    FLAG_GROUPS,
+    FLAG_WITHOUT,
+    FLAG_FORMAT,
+    FLAG_APPEND,
+    FLAG_PROJECT,
+    FLAG_DISCARD,
     ARG_PATHS,
+    ARG_SESSION,
+    ARG_GROUP,
     SUBCOMMAND_RUN,
     SUBCOMMAND_IMAGE,
     SUBCOMMAND_IMAGE_BUILD,
     SUBCOMMAND_CACHE,
     SUBCOMMAND_CACHE_RESET,
+    SUBCOMMAND_ATTACH,
+    SUBCOMMAND_EXEC,
+    SUBCOMMAND_STOP,
+    SUBCOMMAND_RM,
+    SUBCOMMAND_PS,
+    SUBCOMMAND_DIFF,
+    SUBCOMMAND_APPLY,
+    SUBCOMMAND_TRUST,
+    SUBCOMMAND_CONFIG,
+    SUBCOMMAND_CONFIG_SHOW,
+    SUBCOMMAND_CONFIG_VALIDATE,
+    SUBCOMMAND_GROUPS_LIST,
+    SUBCOMMAND_GROUPS_GRAPH,
 ];
+
+pub(crate) fn is_reserved_group_name(name: &str) -> bool {
+    RESERVED_GROUP_NAMES.contains(&name)
+        || name.starts_with(NEGATED_GROUP_PREFIX)
+        || crate::config::SETTING_FIELDS
+            .iter()
+            .any(|field| field.flag == name || field.negated_flag == Some(name))
+}
@@ -4,6 +4,16 @@ mod parse;
 mod types;
 mod validate;
 
-pub use parse::{collect_group_flags_from_names, parse_args, parse_args_with_sources};
-pub use types::{Action, CacheResetAction, GroupFlag, ImageBuildAction, ParsedCLI, build_version};
-pub use validate::validate_settings;
+pub(crate) use constants::is_reserved_group_name;
+pub use parse::{
+    collect_group_flags_from_names, parse_args, parse_args_with_loaded_sources,
+    parse_args_with_sources, profile_from_args,
+};
+pub use types::{
+    Action, ApplyAction, CacheResetAction, ConfigInitAction, ConfigKeyAction, ConfigMigrateAction,
+    ConfigSetAction, ConfigShowAction, ExecAction, GroupFlag, GroupsGraphAction, GroupsShowAction,
+    ImageBuildAction, ParsedCLI, PsAction, RemoveAction, SessionAction, WorkspaceAction,
+    build_version,
+};
+pub(crate) use validate::is_valid_exposed_host_port_spec;
+pub use validate::{validate_session_name, validate_settings};
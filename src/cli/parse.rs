@@ -10,22 +10,73 @@ use crate::{
 use super::{
     build::{base_command, print_targeted_help},
     constants::{
-        ARG_PATHS, FLAG_CACHE, FLAG_COMMAND, FLAG_CONTEXT, FLAG_DEBUG, FLAG_DYNAMIC_PORT, FLAG_ENV,
-        FLAG_ENV_FILE, FLAG_EXPOSE_HOST_PORT, FLAG_IMAGE, FLAG_MOUNT, FLAG_MOUNT_GIT_METADATA,
-        FLAG_NO_CACHE, FLAG_NO_MOUNT_GIT_METADATA, FLAG_PODMAN_ARG, FLAG_PORT, FLAG_RUN_ARG,
-        FLAG_SKIP_CWD, FLAG_TAG, FLAG_VERSION, SUBCOMMAND_CACHE, SUBCOMMAND_CACHE_RESET,
-        SUBCOMMAND_IMAGE, SUBCOMMAND_IMAGE_BUILD, SUBCOMMAND_RUN,
+        ARG_GROUP, ARG_KEY, ARG_PATHS, ARG_SESSION, ARG_VALUES, FLAG_ALL,
+        FLAG_ALLOW_SENSITIVE_MOUNT, FLAG_APPEND, FLAG_COMMAND, FLAG_CONTEXT, FLAG_DEBUG,
+        FLAG_DETACH, FLAG_DISCARD, FLAG_EXPLAIN_GROUPS, FLAG_FORCE, FLAG_FORMAT, FLAG_GROUPS,
+        FLAG_JSON, FLAG_NAME, FLAG_NO_CACHE, FLAG_PODMAN_ARG, FLAG_PROFILE, FLAG_PROJECT,
+        FLAG_SKIP_CWD, FLAG_TAG, FLAG_VERSION, FLAG_WITHOUT, FLAG_WORKTREE, NEGATED_GROUP_PREFIX,
+        SUBCOMMAND_APPLY, SUBCOMMAND_ATTACH, SUBCOMMAND_CACHE, SUBCOMMAND_CACHE_RESET,
+        SUBCOMMAND_CONFIG, SUBCOMMAND_CONFIG_EDIT, SUBCOMMAND_CONFIG_GET, SUBCOMMAND_CONFIG_INIT,
+        SUBCOMMAND_CONFIG_MIGRATE, SUBCOMMAND_CONFIG_SCHEMA, SUBCOMMAND_CONFIG_SET,
+        SUBCOMMAND_CONFIG_SHOW, SUBCOMMAND_CONFIG_UNSET, SUBCOMMAND_CONFIG_VALIDATE,
+        SUBCOMMAND_DIFF, SUBCOMMAND_EXEC, SUBCOMMAND_GROUPS, SUBCOMMAND_GROUPS_GRAPH,
+        SUBCOMMAND_GROUPS_LIST, SUBCOMMAND_GROUPS_SHOW, SUBCOMMAND_IMAGE, SUBCOMMAND_IMAGE_BUILD,
+        SUBCOMMAND_PS, SUBCOMMAND_RM, SUBCOMMAND_RUN, SUBCOMMAND_STOP, SUBCOMMAND_TRUST,
+    },
+    types::{
+        Action, ApplyAction, CacheResetAction, ConfigInitAction, ConfigKeyAction,
+        ConfigMigrateAction, ConfigSetAction, ConfigShowAction, ExecAction, GroupFlag,
+        GroupsGraphAction, GroupsShowAction, ImageBuildAction, ParsedCLI, PsAction, RemoveAction,
+        SessionAction, WorkspaceAction,
     },
-    types::{Action, CacheResetAction, GroupFlag, ImageBuildAction, ParsedCLI},
     validate::{
         validate_cli_flag_conflicts, validate_cli_settings, validate_group_names,
-        validate_skip_cwd_with_paths,
+        validate_session_name, validate_skip_cwd_with_paths,
     },
 };
 
 pub fn parse_args(args: Vec<String>) -> Result<ParsedCLI, AppError> {
-    let sources = config::load_sources()?;
-    parse_args_with_sources(args, &sources.defaults, &sources.file, &sources.env)
+    let sources = config::load_sources_for_profile(profile_from_args(&args).as_deref())?;
+    parse_args_with_loaded_sources(args, &sources)
+}
+
+/// Finds `--profile` before the config is loaded. Values of other options
+/// are skipped, so `--run-arg --profile` does not select a profile.
+pub fn profile_from_args(args: &[String]) -> Option<String> {
+    let flag = format!("--{}", FLAG_PROFILE);
+    let mut value_options = Vec::new();
+    collect_value_options(&base_command(&BTreeMap::new()), &mut value_options);
+    let mut profile = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if *arg == flag {
+            profile = args.next().cloned();
+        } else if let Some(value) = arg.strip_prefix(&format!("{}=", flag)) {
+            profile = Some(value.to_string());
+        } else if value_options.contains(arg) {
+            args.next();
+        }
+    }
+    profile
+}
+
+fn collect_value_options(cmd: &Command, options: &mut Vec<String>) {
+    for arg in cmd.get_arguments() {
+        if arg.get_action().takes_values()
+            && arg
+                .get_num_args()
+                .is_none_or(|range| range.min_values() > 0)
+            && let Some(long) = arg.get_long()
+        {
+            options.push(format!("--{}", long));
+        }
+    }
+    for subcommand in cmd.get_subcommands() {
+        collect_value_options(subcommand, options);
+    }
 }
 
 pub fn parse_args_with_sources(
@@ -34,16 +85,33 @@ pub fn parse_args_with_sources(
     file_cfg: &config::Config,
     env_cfg: &config::Config,
 ) -> Result<ParsedCLI, AppError> {
-    let group_defs = config::merge_group_definitions(&defaults.groups, &file_cfg.groups)?;
+    let sources = config::LoadedConfigSources {
+        defaults: defaults.clone(),
+        file: file_cfg.clone(),
+        env: env_cfg.clone(),
+        ..config::LoadedConfigSources::default()
+    };
+    parse_args_with_loaded_sources(args, &sources)
+}
+
+pub fn parse_args_with_loaded_sources(
+    args: Vec<String>,
+    sources: &config::LoadedConfigSources,
+) -> Result<ParsedCLI, AppError> {
+    let group_defs = sources.group_definitions()?;
     validate_group_names(&group_defs)?;
 
-    let base_groups =
-        config::resolve_include_groups(defaults, file_cfg, env_cfg, &config::Config::default());
-    let base_order = config::normalize_group_order(&base_groups)?;
+    let base_order = sources.base_group_order()?;
     config::validate_group_selection(&group_defs, &base_order)?;
 
     let mut cmd = base_command(&group_defs);
-    let matches = parse_matches(&mut cmd, args)?;
+    let matches = match parse_matches(&mut cmd, args) {
+        Ok(matches) => matches,
+        Err(err) => match &sources.untrusted_project {
+            Some(project) => return Err(config::untrusted_project_error(project)),
+            None => return Err(err),
+        },
+    };
 
     if print_targeted_help(&matches, &group_defs)? {
         return Ok(empty_parsed(true, false));
@@ -52,16 +120,77 @@ pub fn parse_args_with_sources(
         return Ok(empty_parsed(false, true));
     }
 
+    if let Some(project) = &sources.untrusted_project
+        && !skips_trust_check(&matches)
+    {
+        return Err(config::untrusted_project_error(project));
+    }
+
     match matches.subcommand() {
         Some((SUBCOMMAND_RUN, run_matches)) => parse_run_action(run_matches, &group_defs),
         Some((SUBCOMMAND_IMAGE, image_matches)) => parse_image_action(image_matches),
         Some((SUBCOMMAND_CACHE, cache_matches)) => parse_cache_action(cache_matches),
+        Some((SUBCOMMAND_ATTACH, session_matches)) => {
+            let session = session_from_matches(session_matches)?;
+            Ok(action_parsed(
+                Action::Attach(SessionAction { session }),
+                podman_settings_from_matches(session_matches),
+            ))
+        }
+        Some((SUBCOMMAND_EXEC, session_matches)) => {
+            let session = session_from_matches(session_matches)?;
+            let command = session_matches
+                .get_one::<String>(FLAG_COMMAND)
+                .map(|value| value.to_string());
+            Ok(action_parsed(
+                Action::Exec(ExecAction { session, command }),
+                podman_settings_from_matches(session_matches),
+            ))
+        }
+        Some((SUBCOMMAND_STOP, session_matches)) => {
+            let session = session_from_matches(session_matches)?;
+            Ok(action_parsed(
+                Action::Stop(SessionAction { session }),
+                podman_settings_from_matches(session_matches),
+            ))
+        }
+        Some((SUBCOMMAND_RM, session_matches)) => {
+            let session = session_from_matches(session_matches)?;
+            let force = session_matches.get_flag(FLAG_FORCE);
+            Ok(action_parsed(
+                Action::Remove(RemoveAction { session, force }),
+                podman_settings_from_matches(session_matches),
+            ))
+        }
+        Some((SUBCOMMAND_PS, ps_matches)) => Ok(action_parsed(
+            Action::Ps(PsAction {
+                all: ps_matches.get_flag(FLAG_ALL),
+                json: ps_matches.get_flag(FLAG_JSON),
+            }),
+            podman_settings_from_matches(ps_matches),
+        )),
+        Some((SUBCOMMAND_DIFF, diff_matches)) => Ok(action_parsed(
+            Action::Diff(WorkspaceAction {
+                paths: workspace_paths(diff_matches),
+            }),
+            Settings::default(),
+        )),
+        Some((SUBCOMMAND_APPLY, apply_matches)) => Ok(action_parsed(
+            Action::Apply(ApplyAction {
+                paths: workspace_paths(apply_matches),
+                discard: apply_matches.get_flag(FLAG_DISCARD),
+            }),
+            Settings::default(),
+        )),
+        Some((SUBCOMMAND_CONFIG, config_matches)) => parse_config_action(config_matches),
+        Some((SUBCOMMAND_TRUST, _)) => Ok(action_parsed(Action::Trust, Settings::default())),
+        Some((SUBCOMMAND_GROUPS, groups_matches)) => parse_groups_action(groups_matches),
         Some((name, _)) => Err(AppError::message(format!(
             "ERROR: unknown subcommand '{}'",
             name
         ))),
         None => Err(AppError::message(
-            "ERROR: missing subcommand (use: run, image, cache)",
+            "ERROR: missing subcommand (use: run, image, cache, attach, exec, stop, rm, ps, diff, apply, trust, config, groups)",
         )),
     }
 }
@@ -89,8 +218,21 @@ fn empty_parsed(show_help: bool, show_version: bool) -> ParsedCLI {
         show_help,
         show_version,
         debug: false,
+        explain_groups: false,
         group_flags: BTreeMap::new(),
         skip_cwd: false,
+        allow_sensitive_mount: false,
+        worktree: None,
+        session_name: None,
+        detach: false,
+    }
+}
+
+fn action_parsed(action: Action, settings: Settings) -> ParsedCLI {
+    ParsedCLI {
+        action,
+        settings,
+        ..empty_parsed(false, false)
     }
 }
 
@@ -98,7 +240,7 @@ fn parse_run_action(
     matches: &ArgMatches,
     group_defs: &BTreeMap<String, config::GroupConfig>,
 ) -> Result<ParsedCLI, AppError> {
-    let group_flags = collect_group_flags(matches, group_defs);
+    let group_flags = collect_group_flags(matches, group_defs)?;
     let paths = collect_paths(matches);
 
     validate_cli_flag_conflicts(matches)?;
@@ -107,6 +249,13 @@ fn parse_run_action(
     let settings = settings_from_matches(matches)?;
     validate_cli_settings(&settings)?;
 
+    let session_name = matches
+        .get_one::<String>(FLAG_NAME)
+        .map(|value| value.to_string());
+    if let Some(name) = &session_name {
+        validate_session_name(name)?;
+    }
+
     Ok(ParsedCLI {
         action: Action::Run,
         settings,
@@ -114,8 +263,15 @@ fn parse_run_action(
         show_help: false,
         show_version: false,
         debug: matches.get_flag(FLAG_DEBUG),
+        explain_groups: matches.get_flag(FLAG_EXPLAIN_GROUPS),
         group_flags,
         skip_cwd: matches.get_flag(FLAG_SKIP_CWD),
+        allow_sensitive_mount: matches.get_flag(FLAG_ALLOW_SENSITIVE_MOUNT),
+        worktree: matches
+            .get_one::<String>(FLAG_WORKTREE)
+            .map(|value| value.to_string()),
+        session_name,
+        detach: matches.get_flag(FLAG_DETACH),
     })
 }
 
@@ -141,20 +297,169 @@ fn parse_image_action(matches: &ArgMatches) -> Result<ParsedCLI, AppError> {
         .map(|value| value.to_string())
         .unwrap_or_else(|| ".".to_string());
 
-    Ok(ParsedCLI {
-        action: Action::ImageBuild(ImageBuildAction {
+    Ok(action_parsed(
+        Action::ImageBuild(ImageBuildAction {
             tag,
             no_cache,
             context,
         }),
-        settings: podman_settings_from_matches(sub_matches),
-        paths: Vec::new(),
-        show_help: false,
-        show_version: false,
-        debug: false,
-        group_flags: BTreeMap::new(),
-        skip_cwd: false,
-    })
+        podman_settings_from_matches(sub_matches),
+    ))
+}
+
+fn workspace_paths(matches: &ArgMatches) -> Vec<String> {
+    matches
+        .get_many::<String>(ARG_PATHS)
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default()
+}
+
+fn skips_trust_check(matches: &ArgMatches) -> bool {
+    match matches.subcommand() {
+        Some((SUBCOMMAND_TRUST | SUBCOMMAND_DIFF | SUBCOMMAND_APPLY, _)) => true,
+        Some((SUBCOMMAND_CONFIG, config_matches)) => matches!(
+            config_matches.subcommand(),
+            Some((
+                SUBCOMMAND_CONFIG_VALIDATE
+                    | SUBCOMMAND_CONFIG_GET
+                    | SUBCOMMAND_CONFIG_SET
+                    | SUBCOMMAND_CONFIG_UNSET
+                    | SUBCOMMAND_CONFIG_EDIT
+                    | SUBCOMMAND_CONFIG_INIT
+                    | SUBCOMMAND_CONFIG_SCHEMA
+                    | SUBCOMMAND_CONFIG_MIGRATE,
+                _
+            ))
+        ),
+        _ => false,
+    }
+}
+
+fn parse_config_action(matches: &ArgMatches) -> Result<ParsedCLI, AppError> {
+    let (sub_name, sub_matches) = matches.subcommand().ok_or_else(|| {
+        AppError::message(
+            "ERROR: config requires a subcommand (use: config show, config validate, config get, config set, config unset, config edit, config init, config schema, config migrate)",
+        )
+    })?;
+
+    let key = || {
+        sub_matches
+            .get_one::<String>(ARG_KEY)
+            .map(|key| key.trim().to_string())
+            .ok_or_else(|| AppError::message(format!("ERROR: config {} requires a key", sub_name)))
+    };
+    match sub_name {
+        SUBCOMMAND_CONFIG_SHOW => {}
+        SUBCOMMAND_CONFIG_VALIDATE => {
+            return Ok(action_parsed(Action::ConfigValidate, Settings::default()));
+        }
+        SUBCOMMAND_CONFIG_EDIT => {
+            return Ok(action_parsed(Action::ConfigEdit, Settings::default()));
+        }
+        SUBCOMMAND_CONFIG_MIGRATE => {
+            return Ok(action_parsed(
+                Action::ConfigMigrate(ConfigMigrateAction {
+                    project: sub_matches.get_flag(FLAG_PROJECT),
+                }),
+                Settings::default(),
+            ));
+        }
+        SUBCOMMAND_CONFIG_SCHEMA => {
+            return Ok(action_parsed(Action::ConfigSchema, Settings::default()));
+        }
+        SUBCOMMAND_CONFIG_INIT => {
+            return Ok(action_parsed(
+                Action::ConfigInit(ConfigInitAction {
+                    project: sub_matches.get_flag(FLAG_PROJECT),
+                    force: sub_matches.get_flag(FLAG_FORCE),
+                }),
+                Settings::default(),
+            ));
+        }
+        SUBCOMMAND_CONFIG_GET => {
+            return Ok(action_parsed(
+                Action::ConfigGet(ConfigKeyAction { key: key()? }),
+                Settings::default(),
+            ));
+        }
+        SUBCOMMAND_CONFIG_SET | SUBCOMMAND_CONFIG_UNSET => {
+            let values = || {
+                sub_matches
+                    .get_many::<String>(ARG_VALUES)
+                    .map(|values| values.cloned().collect())
+                    .unwrap_or_default()
+            };
+            let edit = if sub_name == SUBCOMMAND_CONFIG_UNSET {
+                config::ConfigEdit::Unset
+            } else if sub_matches.get_flag(FLAG_APPEND) {
+                config::ConfigEdit::Append(values())
+            } else {
+                config::ConfigEdit::Set(values())
+            };
+            return Ok(action_parsed(
+                Action::ConfigSet(ConfigSetAction { key: key()?, edit }),
+                Settings::default(),
+            ));
+        }
+        _ => {
+            return Err(AppError::message(format!(
+                "ERROR: unknown config subcommand '{}'",
+                sub_name
+            )));
+        }
+    }
+
+    let groups = sub_matches
+        .get_many::<String>(FLAG_GROUPS)
+        .map(|values| {
+            values
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(action_parsed(
+        Action::ConfigShow(ConfigShowAction {
+            groups,
+            json: sub_matches.get_flag(FLAG_JSON),
+        }),
+        Settings::default(),
+    ))
+}
+
+fn parse_groups_action(matches: &ArgMatches) -> Result<ParsedCLI, AppError> {
+    let (sub_name, sub_matches) = matches.subcommand().ok_or_else(|| {
+        AppError::message(
+            "ERROR: groups requires a subcommand (use: groups list, groups show, groups graph)",
+        )
+    })?;
+
+    let action = match sub_name {
+        SUBCOMMAND_GROUPS_LIST => Action::GroupsList,
+        SUBCOMMAND_GROUPS_SHOW => Action::GroupsShow(GroupsShowAction {
+            name: sub_matches
+                .get_one::<String>(ARG_GROUP)
+                .map(|name| name.trim().to_string())
+                .ok_or_else(|| AppError::message("ERROR: groups show requires a group name"))?,
+        }),
+        SUBCOMMAND_GROUPS_GRAPH => Action::GroupsGraph(GroupsGraphAction {
+            format: match sub_matches
+                .get_one::<String>(FLAG_FORMAT)
+                .map(String::as_str)
+            {
+                Some("dot") => config::GraphFormat::Dot,
+                _ => config::GraphFormat::Tree,
+            },
+        }),
+        _ => {
+            return Err(AppError::message(format!(
+                "ERROR: unknown groups subcommand '{}'",
+                sub_name
+            )));
+        }
+    };
+    Ok(action_parsed(action, Settings::default()))
 }
 
 fn parse_cache_action(matches: &ArgMatches) -> Result<ParsedCLI, AppError> {
@@ -169,16 +474,10 @@ fn parse_cache_action(matches: &ArgMatches) -> Result<ParsedCLI, AppError> {
         )));
     }
 
-    Ok(ParsedCLI {
-        action: Action::CacheReset(CacheResetAction),
-        settings: podman_settings_from_matches(_sub_matches),
-        paths: Vec::new(),
-        show_help: false,
-        show_version: false,
-        debug: false,
-        group_flags: BTreeMap::new(),
-        skip_cwd: false,
-    })
+    Ok(action_parsed(
+        Action::CacheReset(CacheResetAction),
+        podman_settings_from_matches(_sub_matches),
+    ))
 }
 
 fn parse_matches(cmd: &mut Command, args: Vec<String>) -> Result<ArgMatches, AppError> {
@@ -193,20 +492,57 @@ fn parse_matches(cmd: &mut Command, args: Vec<String>) -> Result<ArgMatches, App
 fn collect_group_flags(
     matches: &ArgMatches,
     groups: &BTreeMap<String, config::GroupConfig>,
-) -> BTreeMap<String, GroupFlag> {
+) -> Result<BTreeMap<String, GroupFlag>, AppError> {
+    let without = matches
+        .get_many::<String>(FLAG_WITHOUT)
+        .into_iter()
+        .flatten()
+        .map(|name| name.trim())
+        .collect::<Vec<_>>();
+    for name in &without {
+        if !groups.contains_key(*name) {
+            return Err(AppError::message(format!(
+                "ERROR: --without names unknown group \"{}\"",
+                name
+            )));
+        }
+    }
+
     let mut flags = BTreeMap::new();
     let mut order = 0;
 
     for name in groups.keys() {
         let set = matches.get_flag(name);
+        let negated = format!("{}{}", NEGATED_GROUP_PREFIX, name);
+        let disabled = matches.get_flag(&negated) || without.contains(&name.as_str());
+        if set && disabled {
+            return Err(AppError::message(format!(
+                "ERROR: --{} and --{} are mutually exclusive",
+                name, negated
+            )));
+        }
         if set {
             order += 1;
-            flags.insert(name.clone(), GroupFlag { set: true, order });
-        } else {
-            flags.insert(name.clone(), GroupFlag::default());
         }
+        flags.insert(
+            name.clone(),
+            GroupFlag {
+                set,
+                disabled,
+                order: if set { order } else { 0 },
+            },
+        );
     }
-    flags
+    Ok(flags)
+}
+
+fn session_from_matches(matches: &ArgMatches) -> Result<String, AppError> {
+    let session = matches
+        .get_one::<String>(ARG_SESSION)
+        .map(|value| value.to_string())
+        .ok_or_else(|| AppError::message("ERROR: missing session name"))?;
+    validate_session_name(&session)?;
+    Ok(session)
 }
 
 fn collect_paths(matches: &ArgMatches) -> Vec<String> {
@@ -219,46 +555,35 @@ fn collect_paths(matches: &ArgMatches) -> Vec<String> {
 fn settings_from_matches(matches: &ArgMatches) -> Result<Settings, AppError> {
     let mut settings = Settings::default();
 
-    if let Some(value) = matches.get_one::<String>(FLAG_COMMAND) {
-        settings.command = Some(value.to_string());
-    }
-    if let Some(value) = matches.get_one::<String>(FLAG_IMAGE) {
-        settings.image = Some(value.to_string());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_PORT) {
-        settings.ports = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_DYNAMIC_PORT) {
-        let names: Vec<String> = values.map(|value| value.to_string()).collect();
-        config::validate_dynamic_port_names(&names, "--dynamic-port")?;
-        settings.dynamic_ports = Some(names);
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_EXPOSE_HOST_PORT) {
-        settings.expose_host_ports = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_CACHE) {
-        settings.cache = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_MOUNT) {
-        settings.mounts = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_ENV) {
-        settings.env_vars = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_ENV_FILE) {
-        settings.env_files = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_PODMAN_ARG) {
-        settings.podman_args = Some(values.map(|value| value.to_string()).collect());
-    }
-    if let Some(values) = matches.get_many::<String>(FLAG_RUN_ARG) {
-        settings.run_args = Some(values.map(|value| value.to_string()).collect());
-    }
-    if matches.get_flag(FLAG_MOUNT_GIT_METADATA) {
-        settings.mount_git_metadata = Some(true);
-    }
-    if matches.get_flag(FLAG_NO_MOUNT_GIT_METADATA) {
-        settings.mount_git_metadata = Some(false);
+    for field in config::SETTING_FIELDS {
+        let label = format!("--{}", field.flag);
+        let value = match field.kind {
+            config::SettingKind::Scalar => matches
+                .get_one::<String>(field.flag)
+                .map(|value| toml::Value::String(value.to_string())),
+            config::SettingKind::List => matches.get_many::<String>(field.flag).map(|values| {
+                toml::Value::Array(
+                    values
+                        .map(|value| toml::Value::String(value.to_string()))
+                        .collect(),
+                )
+            }),
+            config::SettingKind::Flag => {
+                if matches.get_flag(field.flag) {
+                    Some(toml::Value::Boolean(true))
+                } else if field
+                    .negated_flag
+                    .is_some_and(|negated_flag| matches.get_flag(negated_flag))
+                {
+                    Some(toml::Value::Boolean(false))
+                } else {
+                    None
+                }
+            }
+        };
+        if let Some(value) = value {
+            settings.set(field.key, &label, &value)?;
+        }
     }
     Ok(settings)
 }
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use crate::config::Settings;
+use crate::config::{ConfigEdit, GraphFormat, Settings};
 
 #[derive(Debug, Clone)]
 pub struct ParsedCLI {
@@ -10,8 +10,13 @@ pub struct ParsedCLI {
     pub show_help: bool,
     pub show_version: bool,
     pub debug: bool,
+    pub explain_groups: bool,
     pub group_flags: BTreeMap<String, GroupFlag>,
     pub skip_cwd: bool,
+    pub allow_sensitive_mount: bool,
+    pub worktree: Option<String>,
+    pub session_name: Option<String>,
+    pub detach: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +25,25 @@ pub enum Action {
     Run,
     ImageBuild(ImageBuildAction),
     CacheReset(CacheResetAction),
+    Attach(SessionAction),
+    Exec(ExecAction),
+    Stop(SessionAction),
+    Remove(RemoveAction),
+    Ps(PsAction),
+    Diff(WorkspaceAction),
+    Apply(ApplyAction),
+    Trust,
+    ConfigShow(ConfigShowAction),
+    ConfigValidate,
+    ConfigGet(ConfigKeyAction),
+    ConfigSet(ConfigSetAction),
+    ConfigEdit,
+    ConfigInit(ConfigInitAction),
+    ConfigSchema,
+    ConfigMigrate(ConfigMigrateAction),
+    GroupsList,
+    GroupsShow(GroupsShowAction),
+    GroupsGraph(GroupsGraphAction),
 }
 
 #[derive(Debug, Clone)]
@@ -32,9 +56,82 @@ pub struct ImageBuildAction {
 #[derive(Debug, Clone)]
 pub struct CacheResetAction;
 
+#[derive(Debug, Clone)]
+pub struct SessionAction {
+    pub session: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecAction {
+    pub session: String,
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoveAction {
+    pub session: String,
+    pub force: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PsAction {
+    pub all: bool,
+    pub json: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceAction {
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApplyAction {
+    pub paths: Vec<String>,
+    pub discard: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigShowAction {
+    pub groups: Vec<String>,
+    pub json: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigKeyAction {
+    pub key: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigSetAction {
+    pub key: String,
+    pub edit: ConfigEdit,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigInitAction {
+    pub project: bool,
+    pub force: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigMigrateAction {
+    pub project: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupsShowAction {
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupsGraphAction {
+    pub format: GraphFormat,
+}
+
 #[derive(Default, Clone, Debug)]
 pub struct GroupFlag {
     pub set: bool,
+    pub disabled: bool,
     pub order: usize,
 }
 
@@ -3,12 +3,12 @@ use std::collections::BTreeMap;
 use clap::ArgMatches;
 
 use crate::{
-    config::{self, Settings},
+    config::{self, Engine, Settings},
     error::AppError,
 };
 
 use super::constants::{
-    FLAG_MOUNT_GIT_METADATA, FLAG_NO_MOUNT_GIT_METADATA, FLAG_SKIP_CWD, RESERVED_GROUP_NAMES,
+    FLAG_DETACH, FLAG_NAME, FLAG_SKIP_CWD, FLAG_WORKTREE, is_reserved_group_name,
 };
 
 pub(crate) fn validate_skip_cwd_with_paths(
@@ -20,6 +20,27 @@ pub(crate) fn validate_skip_cwd_with_paths(
             "ERROR: --skip-cwd cannot be used with explicit paths",
         ));
     }
+    if matches.contains_id(FLAG_WORKTREE) && (matches.get_flag(FLAG_SKIP_CWD) || !paths.is_empty())
+    {
+        return Err(AppError::message(
+            "ERROR: --worktree cannot be used with --skip-cwd or explicit paths",
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_session_name(name: &str) -> Result<(), AppError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric())
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '-'));
+    if !valid {
+        return Err(AppError::message(format!(
+            "ERROR: invalid session name \"{}\"; expected [a-zA-Z0-9][a-zA-Z0-9_.-]*",
+            name
+        )));
+    }
     Ok(())
 }
 
@@ -33,10 +54,19 @@ pub(crate) fn validate_cli_settings(settings: &Settings) -> Result<(), AppError>
 }
 
 pub(crate) fn validate_cli_flag_conflicts(matches: &ArgMatches) -> Result<(), AppError> {
-    if matches.get_flag(FLAG_MOUNT_GIT_METADATA) && matches.get_flag(FLAG_NO_MOUNT_GIT_METADATA) {
-        return Err(AppError::message(
-            "ERROR: --mount-git-metadata and --no-mount-git-metadata are mutually exclusive",
-        ));
+    if matches.get_flag(FLAG_DETACH) && matches.get_one::<String>(FLAG_NAME).is_none() {
+        return Err(AppError::message("ERROR: --detach requires --name"));
+    }
+    for field in config::SETTING_FIELDS {
+        if let Some(negated_flag) = field.negated_flag
+            && matches.get_flag(field.flag)
+            && matches.get_flag(negated_flag)
+        {
+            return Err(AppError::message(format!(
+                "ERROR: --{} and --{} are mutually exclusive",
+                field.flag, negated_flag
+            )));
+        }
     }
     Ok(())
 }
@@ -45,7 +75,7 @@ pub(crate) fn validate_group_names(
     group_defs: &BTreeMap<String, config::GroupConfig>,
 ) -> Result<(), AppError> {
     for name in group_defs.keys() {
-        if RESERVED_GROUP_NAMES.contains(&name.as_str()) {
+        if is_reserved_group_name(name) {
             return Err(AppError::message(format!(
                 "ERROR: group name '{}' conflicts with a reserved CLI flag",
                 name
@@ -56,8 +86,14 @@ pub(crate) fn validate_group_names(
 }
 
 fn validate_exposed_host_ports(settings: &Settings) -> Result<(), AppError> {
-    let exposed_host_ports = settings.expose_host_ports.as_deref().unwrap_or(&[]);
-    for spec in exposed_host_ports {
+    let exposed_host_ports = settings
+        .expose_host_ports
+        .as_deref()
+        .unwrap_or(&[])
+        .iter()
+        .filter(|spec| !config::is_list_edit(spec))
+        .collect::<Vec<_>>();
+    for spec in &exposed_host_ports {
         if !is_valid_exposed_host_port_spec(spec.trim()) {
             return Err(AppError::message(format!(
                 "ERROR: invalid exposed host port specification \"{}\"; expected PORT, PORT:HOST_PORT, RANGE, or RANGE:HOST_RANGE",
@@ -66,6 +102,12 @@ fn validate_exposed_host_ports(settings: &Settings) -> Result<(), AppError> {
         }
     }
 
+    if !exposed_host_ports.is_empty() && settings.engine == Some(Engine::Docker) {
+        return Err(AppError::message(
+            "ERROR: expose_host_ports requires the podman engine; docker has no pasta network mode",
+        ));
+    }
+
     if !exposed_host_ports.is_empty() && uses_explicit_network(settings) {
         return Err(AppError::message(
             "ERROR: expose_host_ports cannot be combined with --network or --net in run_args",
@@ -75,7 +117,7 @@ fn validate_exposed_host_ports(settings: &Settings) -> Result<(), AppError> {
     Ok(())
 }
 
-fn is_valid_exposed_host_port_spec(spec: &str) -> bool {
+pub(crate) fn is_valid_exposed_host_port_spec(spec: &str) -> bool {
     let mut parts = spec.split(':');
     let Some(namespace_range) = parts.next().and_then(parse_port_range) else {
         return false;
@@ -151,6 +193,10 @@ fn uses_remote_podman_environment() -> bool {
 }
 
 fn validate_remote_runtime(settings: &Settings) -> Result<(), AppError> {
+    if settings.engine.unwrap_or_default() != Engine::Podman {
+        return Ok(());
+    }
+
     let uses_connection = uses_podman_connection(settings) || uses_remote_podman_environment();
     let uses_runtime = settings
         .run_args
@@ -0,0 +1,185 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use super::GroupConfig;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupCondition {
+    pub path: Vec<String>,
+    pub file_exists: Vec<String>,
+    pub git_remote: Vec<String>,
+}
+
+pub(crate) const CONDITION_KEYS: &[&str] = &["path", "file_exists", "git_remote"];
+
+impl GroupCondition {
+    pub(crate) fn patterns(&self) -> [(&'static str, &[String]); 3] {
+        [
+            ("path", &self.path),
+            ("file_exists", &self.file_exists),
+            ("git_remote", &self.git_remote),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionMatch {
+    pub dir: PathBuf,
+    pub matched: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for ConditionMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let matched = self
+            .matched
+            .iter()
+            .map(|(key, pattern)| format!("when.{} = \"{}\"", key, pattern))
+            .collect::<Vec<_>>();
+        write!(f, "{} matched {}", matched.join(", "), self.dir.display())
+    }
+}
+
+pub(crate) type GroupMatches = Vec<(String, ConditionMatch)>;
+
+pub(crate) fn matching_groups(
+    groups: &BTreeMap<String, GroupConfig>,
+    dirs: &[PathBuf],
+) -> GroupMatches {
+    groups
+        .iter()
+        .filter_map(|(name, group)| {
+            let condition = group.when.as_ref()?;
+            dirs.iter()
+                .find_map(|dir| condition_match(condition, dir))
+                .map(|matched| (name.clone(), matched))
+        })
+        .collect()
+}
+
+fn condition_match(condition: &GroupCondition, dir: &Path) -> Option<ConditionMatch> {
+    let mut matched = Vec::new();
+    if !condition.path.is_empty() {
+        let text = dir.to_string_lossy();
+        let pattern = condition
+            .path
+            .iter()
+            .find(|pattern| path_matches(&expand_home(pattern), &text))?;
+        matched.push(("path", pattern.clone()));
+    }
+    if !condition.file_exists.is_empty() {
+        let roots = project_dirs(dir);
+        let file = condition
+            .file_exists
+            .iter()
+            .find(|file| roots.iter().any(|root| root.join(file).exists()))?;
+        matched.push(("file_exists", file.clone()));
+    }
+    if !condition.git_remote.is_empty() {
+        let urls = git_remote_urls(dir);
+        let pattern = condition.git_remote.iter().find(|pattern| {
+            urls.iter()
+                .any(|url| glob_matches(pattern.as_bytes(), url.as_bytes(), None))
+        })?;
+        matched.push(("git_remote", pattern.clone()));
+    }
+    if matched.is_empty() {
+        return None;
+    }
+    Some(ConditionMatch {
+        dir: dir.to_path_buf(),
+        matched,
+    })
+}
+
+pub(super) fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home.display(), rest)
+        }
+        _ => pattern.to_string(),
+    }
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    glob_matches(pattern.as_bytes(), path.as_bytes(), Some(b'/'))
+        || pattern
+            .strip_suffix("/**")
+            .is_some_and(|base| glob_matches(base.as_bytes(), path.as_bytes(), Some(b'/')))
+}
+
+pub(super) fn glob_matches(pattern: &[u8], text: &[u8], separator: Option<u8>) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            (0..=text.len()).any(|index| glob_matches(rest, &text[index..], separator))
+        }
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&index| index == 0 || Some(text[index - 1]) != separator)
+            .any(|index| glob_matches(rest, &text[index..], separator)),
+        [b'?', rest @ ..] => text.split_first().is_some_and(|(ch, text)| {
+            Some(*ch) != separator && glob_matches(rest, text, separator)
+        }),
+        [ch, rest @ ..] => text
+            .split_first()
+            .is_some_and(|(first, text)| first == ch && glob_matches(rest, text, separator)),
+    }
+}
+
+pub(super) fn project_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for ancestor in dir.ancestors() {
+        dirs.push(ancestor.to_path_buf());
+        if ancestor.join(".git").exists() {
+            return dirs;
+        }
+    }
+    vec![dir.to_path_buf()]
+}
+
+fn git_remote_urls(dir: &Path) -> Vec<String> {
+    let Some(git_dir) = dir
+        .ancestors()
+        .map(|ancestor| ancestor.join(".git"))
+        .find(|entry| entry.exists())
+        .and_then(|entry| git_common_dir(&entry))
+    else {
+        return Vec::new();
+    };
+    let Ok(config) = fs::read_to_string(git_dir.join("config")) else {
+        return Vec::new();
+    };
+
+    let mut urls = Vec::new();
+    let mut in_remote = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_remote = line.starts_with("[remote ");
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=')
+            && in_remote
+            && key.trim() == "url"
+        {
+            urls.push(value.trim().trim_matches('"').to_string());
+        }
+    }
+    urls
+}
+
+fn git_common_dir(entry: &Path) -> Option<PathBuf> {
+    let git_dir = if entry.is_dir() {
+        entry.to_path_buf()
+    } else {
+        let raw = fs::read_to_string(entry).ok()?;
+        let path = PathBuf::from(raw.trim().strip_prefix("gitdir:")?.trim());
+        entry.parent()?.join(path)
+    };
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common_dir) => Some(git_dir.join(common_dir.trim())),
+        Err(_) => Some(git_dir),
+    }
+}
@@ -0,0 +1,273 @@
+use std::{fs, path::Path};
+
+use toml_edit::{Array, DocumentMut, Item, Table, TableLike, Value};
+
+use crate::{cli, error::AppError};
+
+use super::{SettingKind, Settings, is_list_edit, parse, setting_field};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigEdit {
+    Set(Vec<String>),
+    Append(Vec<String>),
+    Unset,
+}
+
+struct ConfigKey<'a> {
+    name: &'a str,
+    tables: Vec<&'a str>,
+    key: &'a str,
+}
+
+impl<'a> ConfigKey<'a> {
+    fn parse(name: &'a str) -> Result<Self, AppError> {
+        let mut parts = name.split('.').map(str::trim).collect::<Vec<_>>();
+        let valid_depth = match parts.first() {
+            Some(&parse::PROFILE_TABLE) => (3..=4).contains(&parts.len()),
+            _ => parts.len() == 2,
+        };
+        if !valid_depth || parts.iter().any(|part| part.is_empty()) {
+            return Err(AppError::message(format!(
+                "ERROR: config key \"{}\" must be <table>.<key>, for example general.image, codex.mounts or profile.work.image",
+                name
+            )));
+        }
+        let key = parts.pop().unwrap_or_default();
+        Ok(Self {
+            name,
+            tables: parts,
+            key,
+        })
+    }
+
+    fn scope(&self) -> String {
+        self.tables.join(".")
+    }
+
+    fn is_group(&self) -> bool {
+        !matches!(self.tables[..], ["general"] | [parse::PROFILE_TABLE, _])
+    }
+
+    fn kind(&self) -> Result<SettingKind, AppError> {
+        match self.key {
+            "include_groups" => Ok(SettingKind::List),
+            "description" if self.is_group() => Ok(SettingKind::Scalar),
+            key => setting_field(key).map(|field| field.kind).ok_or_else(|| {
+                AppError::message(format!(
+                    "ERROR: {} has unknown key \"{}\"",
+                    self.scope(),
+                    key
+                ))
+            }),
+        }
+    }
+}
+
+pub fn get_config_value(path: &Path, name: &str) -> Result<String, AppError> {
+    let key = ConfigKey::parse(name)?;
+    let doc = read_document(path)?;
+    let mut table: &dyn TableLike = doc.as_table();
+    for name in &key.tables {
+        table = table
+            .get(name)
+            .and_then(Item::as_table_like)
+            .ok_or_else(|| not_set(&key, path))?;
+    }
+    let value = table
+        .get(key.key)
+        .and_then(Item::as_value)
+        .ok_or_else(|| not_set(&key, path))?;
+
+    Ok(match value {
+        Value::Array(entries) => entries
+            .iter()
+            .map(plain_value)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        value => plain_value(value),
+    })
+}
+
+pub fn edit_config_file(path: &Path, name: &str, edit: ConfigEdit) -> Result<(), AppError> {
+    let key = ConfigKey::parse(name)?;
+    let kind = key.kind()?;
+    let mut doc = read_document(path)?;
+
+    match edit {
+        ConfigEdit::Set(values) => {
+            let value = new_value(&key, kind, &values)?;
+            let table = table_mut(&mut doc, &key.tables)?;
+            match table.get_mut(key.key) {
+                Some(Item::Value(existing)) => {
+                    let decor = existing.decor().clone();
+                    *existing = value;
+                    *existing.decor_mut() = decor;
+                }
+                _ => {
+                    table.insert(key.key, Item::Value(value));
+                }
+            }
+        }
+        ConfigEdit::Append(values) => {
+            if kind != SettingKind::List {
+                return Err(AppError::message(format!(
+                    "ERROR: --append only applies to list settings; {} takes a single value",
+                    key.name
+                )));
+            }
+            new_value(&key, kind, &values)?;
+            let table = table_mut(&mut doc, &key.tables)?;
+            let item = table
+                .entry(key.key)
+                .or_insert(Item::Value(Value::Array(Array::new())));
+            let entries = item.as_array_mut().ok_or_else(|| {
+                AppError::message(format!(
+                    "ERROR: {} is not a list in {}",
+                    key.name,
+                    path.display()
+                ))
+            })?;
+            for value in values {
+                let decor = (entries.len() > 1)
+                    .then(|| entries.iter().last().map(|last| last.decor().clone()))
+                    .flatten();
+                match decor {
+                    Some(decor) => {
+                        let mut value = Value::from(value);
+                        *value.decor_mut() = decor;
+                        entries.push_formatted(value);
+                    }
+                    None => entries.push(value),
+                }
+            }
+        }
+        ConfigEdit::Unset => {
+            if !unset(doc.as_table_mut(), &key.tables, key.key) {
+                return Err(not_set(&key, path));
+            }
+        }
+    }
+
+    let data = doc.to_string().trim_start().to_string();
+    parse::parse_config(&data).map_err(|err| {
+        AppError::message(format!("ERROR: not saving {}: {}", path.display(), err))
+    })?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, data)
+        .map_err(|err| AppError::message(format!("write config {}: {}", path.display(), err)))
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut, AppError> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(AppError::message(format!(
+                "read config {}: {}",
+                path.display(),
+                err
+            )));
+        }
+    };
+    data.parse::<DocumentMut>()
+        .map_err(|err| AppError::message(format!("parse config {}: {}", path.display(), err)))
+}
+
+fn new_value(key: &ConfigKey<'_>, kind: SettingKind, values: &[String]) -> Result<Value, AppError> {
+    let checked = match kind {
+        SettingKind::List => toml::Value::Array(
+            values
+                .iter()
+                .map(|value| toml::Value::String(value.clone()))
+                .collect(),
+        ),
+        _ if values.len() != 1 => {
+            return Err(AppError::message(format!(
+                "ERROR: {} takes exactly one value",
+                key.name
+            )));
+        }
+        SettingKind::Flag => {
+            toml::Value::Boolean(parse::parse_bool_value(key.name, values[0].trim())?)
+        }
+        SettingKind::Scalar => toml::Value::String(values[0].clone()),
+    };
+    if setting_field(key.key).is_some() {
+        parse::parse_settings_key(&mut Settings::default(), &key.scope(), key.key, &checked)?;
+    }
+    if key.key == "expose_host_ports"
+        && let Some(spec) = values
+            .iter()
+            .find(|spec| !is_list_edit(spec) && !cli::is_valid_exposed_host_port_spec(spec.trim()))
+    {
+        return Err(AppError::message(format!(
+            "ERROR: invalid exposed host port specification \"{}\"; expected PORT, PORT:HOST_PORT, RANGE, or RANGE:HOST_RANGE",
+            spec
+        )));
+    }
+
+    Ok(match checked {
+        toml::Value::Boolean(flag) => Value::from(flag),
+        _ if kind == SettingKind::List => Value::Array(values.iter().map(String::as_str).collect()),
+        _ => Value::from(values[0].as_str()),
+    })
+}
+
+fn table_mut<'a>(
+    doc: &'a mut DocumentMut,
+    tables: &[&str],
+) -> Result<&'a mut dyn TableLike, AppError> {
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for (depth, name) in tables.iter().enumerate() {
+        if table.get(name).is_none() {
+            let mut created = Table::new();
+            created.set_implicit(depth + 1 < tables.len());
+            table.insert(name, Item::Table(created));
+        }
+        table = table
+            .get_mut(name)
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| {
+                AppError::message(format!(
+                    "ERROR: {} is not a table",
+                    tables[..=depth].join(".")
+                ))
+            })?;
+    }
+    Ok(table)
+}
+
+fn unset(table: &mut dyn TableLike, tables: &[&str], key: &str) -> bool {
+    let Some((name, rest)) = tables.split_first() else {
+        return table.remove(key).is_some();
+    };
+    let Some(child) = table.get_mut(name).and_then(Item::as_table_like_mut) else {
+        return false;
+    };
+    let removed = unset(child, rest, key);
+    if removed && child.is_empty() {
+        table.remove(name);
+    }
+    removed
+}
+
+fn plain_value(value: &Value) -> String {
+    match value.as_str() {
+        Some(text) => text.to_string(),
+        None => {
+            let mut value = value.clone();
+            value.decor_mut().clear();
+            value.to_string()
+        }
+    }
+}
+
+fn not_set(key: &ConfigKey<'_>, path: &Path) -> AppError {
+    AppError::message(format!(
+        "ERROR: {} is not set in {}",
+        key.name,
+        path.display()
+    ))
+}
@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{error::AppError, table::render_columns};
+
+use super::{GroupConfig, Origin, SETTING_FIELDS, groups};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    #[default]
+    Tree,
+    Dot,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupInfo {
+    pub name: String,
+    pub origin: Origin,
+    pub group: GroupConfig,
+}
+
+pub fn render_group_list(groups: &[GroupInfo]) -> String {
+    let header = ["NAME", "DESCRIPTION", "SOURCE"].map(String::from);
+    let mut rows = vec![header];
+    for info in groups {
+        rows.push([
+            info.name.clone(),
+            info.group
+                .description
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+            info.origin.to_string(),
+        ]);
+    }
+
+    render_columns(&rows)
+}
+
+pub fn render_group(
+    info: &GroupInfo,
+    definitions: &BTreeMap<String, GroupConfig>,
+) -> Result<String, AppError> {
+    let group = &info.group;
+    let mut lines = vec![format!("[{}]", info.name), format!("# {}", info.origin)];
+    if let Some(description) = &group.description {
+        lines.push(format!(
+            "description = {}",
+            toml::Value::from(description.as_str())
+        ));
+    }
+    if !group.include_groups.is_empty() {
+        lines.push(format!(
+            "include_groups = {}",
+            toml_list(&group.include_groups)
+        ));
+    }
+    if let Some(when) = &group.when {
+        for (key, patterns) in when.patterns() {
+            if !patterns.is_empty() {
+                lines.push(format!("when.{} = {}", key, toml_list(patterns)));
+            }
+        }
+    }
+    for field in SETTING_FIELDS {
+        if let Some(value) = group.settings.get(field.key) {
+            lines.push(format!("{} = {}", field.key, value));
+        }
+    }
+
+    let order = groups::expanded_group_order(definitions, &info.name)?;
+    lines.push(String::new());
+    lines.push(format!("# enables: {}", order.join(" -> ")));
+    Ok(lines.join("\n"))
+}
+
+pub fn render_group_graph(
+    definitions: &BTreeMap<String, GroupConfig>,
+    format: GraphFormat,
+) -> String {
+    match format {
+        GraphFormat::Tree => {
+            let included = definitions
+                .values()
+                .flat_map(|group| group.include_groups.iter().map(|name| name.trim()))
+                .collect::<BTreeSet<_>>();
+            let mut lines = Vec::new();
+            for name in definitions.keys() {
+                if !included.contains(name.as_str()) {
+                    lines.push(name.clone());
+                    render_tree_children(name, definitions, "", &mut lines);
+                }
+            }
+            lines.join("\n")
+        }
+        GraphFormat::Dot => {
+            let mut lines = vec!["digraph groups {".to_string()];
+            for name in definitions.keys() {
+                lines.push(format!("  \"{}\";", name));
+            }
+            for (name, group) in definitions {
+                for included in &group.include_groups {
+                    lines.push(format!("  \"{}\" -> \"{}\";", name, included.trim()));
+                }
+            }
+            lines.push("}".to_string());
+            lines.join("\n")
+        }
+    }
+}
+
+fn render_tree_children(
+    name: &str,
+    definitions: &BTreeMap<String, GroupConfig>,
+    prefix: &str,
+    lines: &mut Vec<String>,
+) {
+    let Some(group) = definitions.get(name) else {
+        return;
+    };
+    let count = group.include_groups.len();
+    for (index, included) in group.include_groups.iter().enumerate() {
+        let last = index + 1 == count;
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        lines.push(format!("{}{}{}", prefix, branch, included.trim()));
+        render_tree_children(
+            included.trim(),
+            definitions,
+            &format!("{}{}", prefix, indent),
+            lines,
+        );
+    }
+}
+
+fn toml_list(values: &[String]) -> String {
+    toml::Value::Array(values.iter().cloned().map(toml::Value::String).collect()).to_string()
+}
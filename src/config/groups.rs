@@ -1,8 +1,40 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
 
 use crate::error::AppError;
 
-use super::GroupConfig;
+use super::{ConditionMatch, GroupConfig};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupReason {
+    Flag,
+    Included(String),
+    Condition(ConditionMatch),
+    Dependency(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupExplanation {
+    pub name: String,
+    pub reason: GroupReason,
+}
+
+impl fmt::Display for GroupExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            GroupReason::Flag => write!(f, "{}: cli --{}", self.name, self.name),
+            GroupReason::Included(source) => {
+                write!(f, "{}: include_groups in {}", self.name, source)
+            }
+            GroupReason::Condition(matched) => write!(f, "{}: {}", self.name, matched),
+            GroupReason::Dependency(parent) => {
+                write!(f, "{}: included by group {}", self.name, parent)
+            }
+        }
+    }
+}
 
 pub fn merge_group_definitions(
     base: &BTreeMap<String, GroupConfig>,
@@ -54,7 +86,13 @@ pub fn resolve_group_order(
     flags: &BTreeMap<String, crate::cli::GroupFlag>,
     groups: &BTreeMap<String, GroupConfig>,
 ) -> Result<Vec<String>, AppError> {
+    let disabled: BTreeSet<String> = flags
+        .iter()
+        .filter(|(_, flag)| flag.disabled)
+        .map(|(name, _)| name.clone())
+        .collect();
     let mut order = root_groups.to_vec();
+    order.retain(|name| !disabled.contains(name));
     let selected: Vec<(String, usize)> = flags
         .iter()
         .filter(|(_, flag)| flag.set)
@@ -62,7 +100,7 @@ pub fn resolve_group_order(
         .collect();
 
     if selected.is_empty() {
-        return expand_group_order(groups, &order);
+        return expand_group_order(groups, &order, &disabled);
     }
 
     let mut selected_sorted = selected;
@@ -76,7 +114,7 @@ pub fn resolve_group_order(
     for (name, _) in selected_sorted {
         order.push(name);
     }
-    expand_group_order(groups, &order)
+    expand_group_order(groups, &order, &disabled)
 }
 
 fn validate_group_inclusions(groups: &BTreeMap<String, GroupConfig>) -> Result<(), AppError> {
@@ -125,14 +163,22 @@ fn validate_group_inclusions_from(
     Ok(())
 }
 
+pub(crate) fn expanded_group_order(
+    groups: &BTreeMap<String, GroupConfig>,
+    name: &str,
+) -> Result<Vec<String>, AppError> {
+    expand_group_order(groups, &[name.to_string()], &BTreeSet::new())
+}
+
 fn expand_group_order(
     groups: &BTreeMap<String, GroupConfig>,
     roots: &[String],
+    disabled: &BTreeSet<String>,
 ) -> Result<Vec<String>, AppError> {
     let mut expanded = Vec::new();
     let mut included = BTreeSet::new();
     for name in roots {
-        expand_group(name, groups, &mut included, &mut expanded)?;
+        expand_group(name, groups, disabled, &mut included, &mut expanded)?;
     }
     Ok(expanded)
 }
@@ -140,6 +186,7 @@ fn expand_group_order(
 fn expand_group(
     name: &str,
     groups: &BTreeMap<String, GroupConfig>,
+    disabled: &BTreeSet<String>,
     included: &mut BTreeSet<String>,
     expanded: &mut Vec<String>,
 ) -> Result<(), AppError> {
@@ -152,7 +199,13 @@ fn expand_group(
         .ok_or_else(|| AppError::message(format!("ERROR: unknown group \"{}\"", name)))?;
     for dependency in &group.include_groups {
         let dependency = normalize_group_name(dependency)?;
-        expand_group(&dependency, groups, included, expanded)?;
+        if disabled.contains(&dependency) {
+            return Err(AppError::message(format!(
+                "ERROR: cannot disable group \"{}\": it is included by enabled group \"{}\"",
+                dependency, name
+            )));
+        }
+        expand_group(&dependency, groups, disabled, included, expanded)?;
     }
     expanded.push(name.to_string());
     Ok(())
@@ -0,0 +1,175 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::error::AppError;
+
+use super::{Config, conditions, merge::merge_configs, parse};
+
+pub(super) const INCLUDE_KEY: &str = "include";
+const FRAGMENT_DIR: &str = "config.d";
+
+pub(super) fn load_user_config(path: &Path) -> Result<Config, AppError> {
+    let mut config = Config::default();
+    for file in root_files(path)? {
+        config = merge_configs(config, load_config_file(&file, &mut Vec::new())?)
+            .map_err(|err| file_error(&file, err))?;
+    }
+    Ok(config)
+}
+
+pub(super) fn user_config_files(path: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let mut files = Vec::new();
+    for file in root_files(path)? {
+        collect_files(&file, &mut Vec::new(), &mut files);
+    }
+    Ok(files)
+}
+
+fn root_files(path: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let dir = path.with_file_name(FRAGMENT_DIR);
+    let mut files = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+            .collect::<Vec<_>>(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            return Err(AppError::message(format!(
+                "read config directory {}: {}",
+                dir.display(),
+                err
+            )));
+        }
+    };
+    files.sort();
+    if path.exists() {
+        files.push(path.to_path_buf());
+    }
+    Ok(files)
+}
+
+fn load_config_file(path: &Path, active: &mut Vec<PathBuf>) -> Result<Config, AppError> {
+    let identity = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if active.contains(&identity) {
+        let cycle = active
+            .iter()
+            .chain([&identity])
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        return Err(AppError::message(format!(
+            "ERROR: config include cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    let data = fs::read_to_string(path)
+        .map_err(|err| AppError::message(format!("read config {}: {}", path.display(), err)))?;
+    let mut config = parse::parse_config(&data)
+        .map(|config| parse::with_file_source(config, path))
+        .map_err(|err| AppError::message(format!("parse config {}: {}", path.display(), err)))?;
+    for group in config.groups.values_mut() {
+        group.source = Some(path.to_path_buf());
+    }
+    config.setting_sources = vec![(path.to_path_buf(), config.settings.clone())];
+    for profile in config.profiles.values_mut() {
+        profile.setting_sources = vec![(path.to_path_buf(), profile.settings.clone())];
+    }
+
+    active.push(identity);
+    let mut merged = Config::default();
+    for pattern in std::mem::take(&mut config.include) {
+        for file in expand_include(path, &pattern)? {
+            merged = merge_configs(merged, load_config_file(&file, active)?)
+                .map_err(|err| file_error(&file, err))?;
+        }
+    }
+    active.pop();
+
+    merge_configs(merged, config).map_err(|err| file_error(path, err))
+}
+
+fn collect_files(path: &Path, active: &mut Vec<PathBuf>, files: &mut Vec<PathBuf>) {
+    let identity = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if active.contains(&identity) || files.contains(&path.to_path_buf()) {
+        return;
+    }
+    let includes = fs::read_to_string(path)
+        .ok()
+        .and_then(|data| toml::from_str::<toml::Table>(&data).ok())
+        .and_then(|table| table.get(INCLUDE_KEY).cloned())
+        .and_then(|value| parse::parse_string_vec("config", INCLUDE_KEY, &value).ok())
+        .unwrap_or_default();
+
+    active.push(identity);
+    for pattern in includes {
+        for file in expand_include(path, &pattern).unwrap_or_default() {
+            collect_files(&file, active, files);
+        }
+    }
+    active.pop();
+    files.push(path.to_path_buf());
+}
+
+fn expand_include(including: &Path, pattern: &str) -> Result<Vec<PathBuf>, AppError> {
+    let expanded = PathBuf::from(conditions::expand_home(pattern.trim()));
+    let base = including.parent().unwrap_or(Path::new("."));
+    let pattern_path = base.join(&expanded);
+
+    if !pattern.contains(['*', '?']) {
+        if !pattern_path.is_file() {
+            return Err(AppError::message(format!(
+                "ERROR: {} includes \"{}\", which does not exist",
+                including.display(),
+                pattern
+            )));
+        }
+        return Ok(vec![pattern_path]);
+    }
+
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern_path.components() {
+        let Component::Normal(part) = component else {
+            paths.iter_mut().for_each(|path| path.push(component));
+            continue;
+        };
+        let part = part.to_string_lossy();
+        if !part.contains(['*', '?']) {
+            paths.iter_mut().for_each(|path| path.push(part.as_ref()));
+            continue;
+        }
+        paths = paths
+            .iter()
+            .flat_map(|dir| matching_entries(dir, &part))
+            .collect();
+    }
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    Ok(paths)
+}
+
+fn matching_entries(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.')
+                && conditions::glob_matches(pattern.as_bytes(), name.as_bytes(), Some(b'/'))
+        })
+        .map(|entry| entry.path())
+        .collect()
+}
+
+fn file_error(path: &Path, err: AppError) -> AppError {
+    let message = err.to_string();
+    if message.contains(&path.display().to_string()) {
+        return err;
+    }
+    AppError::message(format!("config {}: {}", path.display(), message))
+}
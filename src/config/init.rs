@@ -0,0 +1,148 @@
+use std::{fs, path::Path};
+
+use toml_edit::Value;
+
+use crate::error::AppError;
+
+use super::{conditions, migrate, parse};
+
+struct Preset {
+    group: &'static str,
+    marker: &'static str,
+    description: &'static str,
+    caches: &'static [&'static str],
+    dynamic_ports: &'static [&'static str],
+}
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        group: "rust",
+        marker: "Cargo.toml",
+        description: "Rust registry and git caches",
+        caches: &["/home/dungeon/.cargo/registry", "/home/dungeon/.cargo/git"],
+        dynamic_ports: &[],
+    },
+    Preset {
+        group: "node",
+        marker: "package.json",
+        description: "npm cache and a dev server port",
+        caches: &["/home/dungeon/.npm"],
+        dynamic_ports: &["dev"],
+    },
+    Preset {
+        group: "python",
+        marker: "pyproject.toml",
+        description: "pip and uv caches",
+        caches: &["/home/dungeon/.cache/pip", "/home/dungeon/.cache/uv"],
+        dynamic_ports: &[],
+    },
+];
+
+pub fn init_config(path: &Path, dir: &Path, project: bool, force: bool) -> Result<(), AppError> {
+    if path.exists() && !force {
+        return Err(AppError::message(format!(
+            "ERROR: {} already exists; use --force to overwrite it",
+            path.display()
+        )));
+    }
+    let data = render_init_config(dir, project);
+    if project {
+        parse::parse_project_config(path, &data)?;
+    } else {
+        parse::parse_config(&data)?;
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)
+        .map_err(|err| AppError::message(format!("write config {}: {}", path.display(), err)))
+}
+
+pub fn render_init_config(dir: &Path, project: bool) -> String {
+    let dirs = conditions::project_dirs(dir);
+    let presets = PRESETS
+        .iter()
+        .filter(|preset| dirs.iter().any(|dir| dir.join(preset.marker).is_file()))
+        .collect::<Vec<_>>();
+    let worktree = dir
+        .ancestors()
+        .map(|ancestor| ancestor.join(".git"))
+        .find(|entry| entry.exists())
+        .is_some_and(|entry| entry.is_file());
+
+    let mut lines = vec![if project {
+        "# Project config for dungeon, generated by `dungeon config init --project`.".to_string()
+    } else {
+        "# User config for dungeon, generated by `dungeon config init`.".to_string()
+    }];
+    let mut detected = presets
+        .iter()
+        .map(|preset| preset.marker)
+        .collect::<Vec<_>>();
+    if worktree {
+        detected.push(".git file (worktree)");
+    }
+    if !detected.is_empty() {
+        lines.push(format!("# Suggestions based on: {}.", detected.join(", ")));
+    }
+    lines.push(String::new());
+    lines.push(
+        "# version 2 enables `!` removal entries and ${...} variables; files without it read them literally."
+            .to_string(),
+    );
+    lines.push(format!(
+        "{} = {}",
+        migrate::VERSION_KEY,
+        migrate::CONFIG_VERSION
+    ));
+
+    let mut general = Vec::new();
+    if worktree {
+        general.push("mount_git_metadata = true".to_string());
+    }
+    if project && !presets.is_empty() {
+        general.push(format!(
+            "include_groups = {}",
+            list(presets.iter().map(|preset| preset.group))
+        ));
+    }
+    if !general.is_empty() {
+        lines.push(String::new());
+        lines.push("[general]".to_string());
+        lines.extend(general);
+    }
+
+    for preset in &presets {
+        lines.push(String::new());
+        lines.push(format!("[{}]", preset.group));
+        lines.push(format!("description = {}", Value::from(preset.description)));
+        if !project {
+            lines.push(format!("when.file_exists = {}", Value::from(preset.marker)));
+        }
+        lines.push(format!("caches = {}", list(preset.caches.iter().copied())));
+        if !preset.dynamic_ports.is_empty() {
+            lines.push(format!(
+                "dynamic_ports = {}",
+                list(preset.dynamic_ports.iter().copied())
+            ));
+        }
+    }
+
+    lines.push(String::new());
+    lines.push("# Built-in defaults, for reference. Uncomment a line to override it;".to_string());
+    lines.push("# an empty table such as [codex] removes a default group.".to_string());
+    lines.push("#".to_string());
+    for line in include_str!("defaults.toml").lines() {
+        lines.push(if line.is_empty() {
+            "#".to_string()
+        } else {
+            format!("# {}", line)
+        });
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn list<'a>(values: impl Iterator<Item = &'a str>) -> Value {
+    Value::Array(values.collect())
+}
@@ -0,0 +1,145 @@
+use std::{collections::BTreeMap, env, path::Path};
+
+use crate::error::AppError;
+
+use super::{SETTING_FIELDS, SettingKind, Settings, settings};
+
+const PORT_PREFIX: &str = "port:";
+
+enum Pass<'a> {
+    Variables {
+        project_dir: &'a Path,
+        dynamic_ports: &'a [String],
+    },
+    Ports(&'a BTreeMap<String, u16>),
+}
+
+pub(crate) fn interpolate_settings(
+    settings: &mut Settings,
+    project_dir: &Path,
+) -> Result<(), AppError> {
+    let dynamic_ports = settings.dynamic_ports.clone().unwrap_or_default();
+    interpolate_variables(settings, project_dir, &dynamic_ports)
+}
+
+/// Expands variables in `settings`, which may hold only part of the merged
+/// settings, against the final `dynamic_ports`.
+pub(crate) fn interpolate_variables(
+    settings: &mut Settings,
+    project_dir: &Path,
+    dynamic_ports: &[String],
+) -> Result<(), AppError> {
+    interpolate_fields(
+        settings,
+        &Pass::Variables {
+            project_dir,
+            dynamic_ports,
+        },
+    )
+}
+
+pub fn expand_port_references(
+    settings: &mut Settings,
+    ports: &BTreeMap<String, u16>,
+) -> Result<(), AppError> {
+    interpolate_fields(settings, &Pass::Ports(ports))
+}
+
+fn interpolate_fields(settings: &mut Settings, pass: &Pass<'_>) -> Result<(), AppError> {
+    for field in SETTING_FIELDS {
+        settings.map_text(field.key, &mut |value| {
+            let value = interpolate(field.key, value, pass)?;
+            match pass {
+                Pass::Variables { .. } if field.kind == SettingKind::List => {
+                    Ok(settings::unescape_list_entry(&value).to_string())
+                }
+                _ => Ok(value),
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn interpolate(key: &str, value: &str, pass: &Pass<'_>) -> Result<String, AppError> {
+    let error = |reason: String| {
+        AppError::message(format!("ERROR: {} in {} value \"{}\"", reason, key, value))
+    };
+    let mut output = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            let is_port = escaped.starts_with(PORT_PREFIX);
+            match pass {
+                Pass::Variables { .. } if is_port => output.push_str("$${"),
+                Pass::Ports(_) if !is_port => output.push_str("$${"),
+                _ => output.push_str("${"),
+            }
+            rest = escaped;
+            continue;
+        }
+        let Some(expression) = rest.strip_prefix("${") else {
+            output.push('$');
+            rest = &rest[1..];
+            continue;
+        };
+        let end = expression
+            .find('}')
+            .ok_or_else(|| error("unterminated \"${\"".to_string()))?;
+        let name = &expression[..end];
+        let replacement = match (pass, name.strip_prefix(PORT_PREFIX)) {
+            (Pass::Variables { dynamic_ports, .. }, Some(port)) => {
+                if !dynamic_ports.iter().any(|name| name == port) {
+                    return Err(error(format!(
+                        "\"${{{}}}\" refers to \"{}\", which is not in dynamic_ports",
+                        name, port
+                    )));
+                }
+                format!("${{{}}}", name)
+            }
+            (Pass::Variables { project_dir, .. }, None) => {
+                resolve_variable(name, project_dir).map_err(error)?
+            }
+            (Pass::Ports(ports), Some(port)) => ports
+                .get(port)
+                .map(|port| port.to_string())
+                .ok_or_else(|| error(format!("no dynamic port was reserved for \"{}\"", port)))?,
+            (Pass::Ports(_), None) => format!("${{{}}}", name),
+        };
+        output.push_str(&replacement);
+        rest = &expression[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn resolve_variable(expression: &str, project_dir: &Path) -> Result<String, String> {
+    let (name, default) = match expression.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (expression, None),
+    };
+    let value = match name {
+        "project_dir" => Some(project_dir.display().to_string()),
+        "project_name" => project_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        _ if is_variable_name(name) => env::var(name).ok(),
+        _ => return Err(format!("invalid variable name \"{}\"", name)),
+    };
+    match (value, default) {
+        (Some(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.to_string()),
+        (None, None) => Err(format!("undefined variable \"{}\"", name)),
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
@@ -1,93 +1,121 @@
+use std::collections::BTreeMap;
+
 use crate::error::AppError;
 
-use super::{Settings, Sources};
+use super::{
+    Config, GroupConfig, SETTING_FIELDS, SettingKind, Settings, Sources, is_list_edit,
+    merge_group_definitions, settings::CLEAR_ENTRY,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Layer {
+    Default,
+    File,
+    Project,
+    Group(String),
+    Env,
+    Cli,
+}
 
 pub fn resolve_settings(
     sources: Sources,
-    groups: &std::collections::BTreeMap<String, super::GroupConfig>,
+    groups: &BTreeMap<String, GroupConfig>,
     group_order: &[String],
 ) -> Result<Settings, AppError> {
-    let mut settings = sources.defaults;
-    settings = merge_settings(settings, sources.file);
+    Ok(settings_layers(sources, groups, group_order)?
+        .into_iter()
+        .fold(Settings::default(), |settings, (_, layer)| {
+            settings.merge(layer)
+        }))
+}
+
+pub(crate) fn settings_layers(
+    sources: Sources,
+    groups: &BTreeMap<String, GroupConfig>,
+    group_order: &[String],
+) -> Result<Vec<(Layer, Settings)>, AppError> {
+    let removals = project_removals(&sources.project)?;
+    let mut layers = vec![
+        (Layer::Default, sources.defaults),
+        (Layer::File, sources.file),
+        (Layer::Project, sources.project),
+    ];
     for name in group_order {
         let group = groups
             .get(name)
             .ok_or_else(|| AppError::message(format!("ERROR: unknown group \"{}\"", name)))?;
-        settings = merge_settings(settings, group.settings.clone());
+        layers.push((Layer::Group(name.clone()), group.settings.clone()));
     }
-    settings = merge_settings(settings, sources.env);
-    settings = merge_settings(settings, sources.cli);
-    Ok(settings)
+    layers.push((Layer::Project, removals));
+    layers.push((Layer::Env, sources.env));
+    layers.push((Layer::Cli, sources.cli));
+    Ok(layers)
 }
 
-pub fn resolve_include_groups(
-    defaults: &super::Config,
-    file: &super::Config,
-    env: &super::Config,
-    cli: &super::Config,
-) -> Vec<String> {
-    let mut groups = Vec::new();
-    if let Some(list) = &defaults.include_groups {
-        groups.extend(list.clone());
-    }
-    if let Some(list) = &file.include_groups {
-        groups.extend(list.clone());
-    }
-    if let Some(list) = &env.include_groups {
-        groups.extend(list.clone());
-    }
-    if let Some(list) = &cli.include_groups {
-        groups.extend(list.clone());
-    }
-    groups
+/// Project `!` entries are applied again after the groups so they also remove
+/// what an enabled group adds. `!*` only clears the layers before the project.
+fn project_removals(project: &Settings) -> Result<Settings, AppError> {
+    let mut removals = Settings::default();
+    for field in SETTING_FIELDS
+        .iter()
+        .filter(|field| field.kind == SettingKind::List)
+    {
+        let Some(toml::Value::Array(entries)) = project.get(field.key) else {
+            continue;
+        };
+        let entries = entries
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .as_str()
+                    .is_some_and(|entry| is_list_edit(entry) && entry != CLEAR_ENTRY)
+            })
+            .collect::<Vec<_>>();
+        if !entries.is_empty() {
+            removals.set(field.key, field.key, &toml::Value::Array(entries))?;
+        }
+    }
+    Ok(removals)
 }
 
-fn merge_settings(base: Settings, override_settings: Settings) -> Settings {
-    let mut merged = base;
-    if let Some(value) = override_settings.engine {
-        merged.engine = Some(value);
-    }
-    if let Some(value) = override_settings.command {
-        merged.command = Some(value);
-    }
-    if let Some(value) = override_settings.image {
-        merged.image = Some(value);
-    }
-    if let Some(values) = override_settings.ports {
-        merged.ports = Some(append_strings(merged.ports, values));
-    }
-    if let Some(values) = override_settings.dynamic_ports {
-        merged.dynamic_ports = Some(append_strings(merged.dynamic_ports, values));
-    }
-    if let Some(values) = override_settings.expose_host_ports {
-        merged.expose_host_ports = Some(append_strings(merged.expose_host_ports, values));
-    }
-    if let Some(values) = override_settings.cache {
-        merged.cache = Some(append_strings(merged.cache, values));
-    }
-    if let Some(values) = override_settings.mounts {
-        merged.mounts = Some(append_strings(merged.mounts, values));
-    }
-    if let Some(values) = override_settings.env_vars {
-        merged.env_vars = Some(append_strings(merged.env_vars, values));
-    }
-    if let Some(values) = override_settings.env_files {
-        merged.env_files = Some(append_strings(merged.env_files, values));
-    }
-    if let Some(values) = override_settings.podman_args {
-        merged.podman_args = Some(append_strings(merged.podman_args, values));
-    }
-    if let Some(values) = override_settings.run_args {
-        merged.run_args = Some(append_strings(merged.run_args, values));
-    }
-    if let Some(value) = override_settings.mount_git_metadata {
-        merged.mount_git_metadata = Some(value);
-    }
-    merged
+pub(crate) fn merge_configs(base: Config, overlay: Config) -> Result<Config, AppError> {
+    let mut groups = merge_group_definitions(&base.groups, &overlay.groups)?;
+    for (name, group) in overlay.groups {
+        if group.disabled {
+            groups.insert(name.trim().to_string(), group);
+        }
+    }
+    let include_groups = match (base.include_groups, overlay.include_groups) {
+        (Some(mut base), Some(overlay)) => {
+            base.extend(overlay);
+            Some(base)
+        }
+        (base, overlay) => overlay.or(base),
+    };
+    let mut profiles = base.profiles;
+    profiles.extend(overlay.profiles);
+    let mut warnings = base.warnings;
+    warnings.extend(overlay.warnings);
+    let mut setting_sources = base.setting_sources;
+    setting_sources.extend(overlay.setting_sources);
+
+    Ok(Config {
+        settings: base.settings.merge(overlay.settings),
+        include_groups,
+        groups,
+        profiles,
+        include: Vec::new(),
+        warnings,
+        setting_sources,
+    })
 }
 
-fn append_strings(base: Option<Vec<String>>, extra: Vec<String>) -> Vec<String> {
-    let mut merged = base.unwrap_or_default();
-    merged.extend(extra);
-    merged
+pub fn resolve_include_groups(layers: &[&super::Config]) -> Vec<String> {
+    let mut groups = Vec::new();
+    for layer in layers {
+        if let Some(list) = &layer.include_groups {
+            groups.extend(list.clone());
+        }
+    }
+    groups
 }
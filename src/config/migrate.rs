@@ -0,0 +1,241 @@
+use std::{fs, path::Path};
+
+use toml_edit::{DocumentMut, Formatted, Item, TableLike, Value};
+
+use crate::error::AppError;
+
+use super::{SettingKind, parse, setting_field};
+
+pub(super) const VERSION_KEY: &str = "version";
+pub const CONFIG_VERSION: i64 = 2;
+pub(super) const MIGRATE_HINT: &str = "run `dungeon config migrate`";
+pub(super) const PROJECT_MIGRATE_HINT: &str = "run `dungeon config migrate --project`";
+
+pub(super) fn file_version(root: &toml::Table) -> Result<i64, AppError> {
+    let Some(value) = root.get(VERSION_KEY) else {
+        return Ok(1);
+    };
+    match value.as_integer() {
+        Some(version) if (1..=CONFIG_VERSION).contains(&version) => Ok(version),
+        Some(version) if version > CONFIG_VERSION => Err(AppError::message(format!(
+            "config version {} is newer than this dungeon supports ({}); upgrade dungeon",
+            version, CONFIG_VERSION
+        ))),
+        _ => Err(AppError::message(format!(
+            "version must be an integer between 1 and {}",
+            CONFIG_VERSION
+        ))),
+    }
+}
+
+pub(super) fn migrate_config(root: &mut toml::Table, version: i64, warnings: &mut Vec<String>) {
+    if version >= CONFIG_VERSION {
+        return;
+    }
+    for (name, value) in root.iter_mut() {
+        let Some(table) = value.as_table_mut() else {
+            continue;
+        };
+        if name != parse::PROFILE_TABLE {
+            migrate_table(name, table, warnings);
+            continue;
+        }
+        for (profile, value) in table.iter_mut() {
+            let Some(table) = value.as_table_mut() else {
+                continue;
+            };
+            let scope = format!("{}.{}", parse::PROFILE_TABLE, profile);
+            for (group, value) in table.iter_mut() {
+                if let Some(table) = value.as_table_mut() {
+                    migrate_table(&format!("{}.{}", scope, group), table, warnings);
+                }
+            }
+            migrate_table(&scope, table, warnings);
+        }
+    }
+}
+
+fn migrate_table(scope: &str, table: &mut toml::Table, warnings: &mut Vec<String>) {
+    for (key, value) in table.iter_mut() {
+        let Some(field) = setting_field(key) else {
+            continue;
+        };
+        escape_value(value, field.kind == SettingKind::List, &mut |text, _| {
+            warnings.push(format!(
+                "{}.{} value \"{}\" is read literally because the file has no version; {}",
+                scope, key, text, MIGRATE_HINT
+            ))
+        });
+    }
+}
+
+fn escape_value(value: &mut toml::Value, list: bool, changed: &mut dyn FnMut(&str, &str)) {
+    match value {
+        toml::Value::String(text) => {
+            if let Some(escaped) = escape_literal(text, list) {
+                changed(text, &escaped);
+                *text = escaped;
+            }
+        }
+        toml::Value::Array(items) => {
+            for item in items {
+                escape_value(item, list, changed);
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                escape_value(item, false, changed);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Version 1 had no `${...}` interpolation and no `!` list edits, so its
+/// values are escaped to keep reading them literally.
+fn escape_literal(text: &str, list: bool) -> Option<String> {
+    let mut escaped = text.replace("${", "$${");
+    if list && escaped.starts_with('!') {
+        escaped.insert(0, '!');
+    }
+    (escaped != text).then_some(escaped)
+}
+
+pub fn migrate_config_file(path: &Path) -> Result<Vec<String>, AppError> {
+    let data = fs::read_to_string(path)
+        .map_err(|err| AppError::message(format!("read config {}: {}", path.display(), err)))?;
+    let raw = toml::from_str::<toml::Table>(&data)
+        .map_err(|err| AppError::message(format!("parse config {}: {}", path.display(), err)))?;
+    let version = file_version(&raw)
+        .map_err(|err| AppError::message(format!("{}: {}", path.display(), err)))?;
+    if version == CONFIG_VERSION {
+        return Ok(Vec::new());
+    }
+    let mut doc = data
+        .parse::<DocumentMut>()
+        .map_err(|err| AppError::message(format!("parse config {}: {}", path.display(), err)))?;
+
+    let mut changes = Vec::new();
+    for (name, item) in doc.as_table_mut().iter_mut() {
+        let Some(table) = item.as_table_like_mut() else {
+            continue;
+        };
+        if name.get() != parse::PROFILE_TABLE {
+            migrate_document_table(name.get(), table, &mut changes);
+            continue;
+        }
+        for (profile, item) in table.iter_mut() {
+            let Some(table) = item.as_table_like_mut() else {
+                continue;
+            };
+            let scope = format!("{}.{}", parse::PROFILE_TABLE, profile.get());
+            for (group, item) in table.iter_mut() {
+                if let Some(table) = item.as_table_like_mut() {
+                    let scope = format!("{}.{}", scope, group.get());
+                    migrate_document_table(&scope, table, &mut changes);
+                }
+            }
+            migrate_document_table(&scope, table, &mut changes);
+        }
+    }
+
+    let data = match doc.get_mut(VERSION_KEY).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = Value::from(CONFIG_VERSION);
+            *existing.decor_mut() = decor;
+            doc.to_string()
+        }
+        None => with_version_line(&doc.to_string()),
+    };
+    parse::parse_config(&data).map_err(|err| {
+        AppError::message(format!("ERROR: not saving {}: {}", path.display(), err))
+    })?;
+    fs::write(path, data)
+        .map_err(|err| AppError::message(format!("write config {}: {}", path.display(), err)))?;
+    changes.push(format!("{} = {}", VERSION_KEY, CONFIG_VERSION));
+    Ok(changes)
+}
+
+fn migrate_document_table(scope: &str, table: &mut dyn TableLike, changes: &mut Vec<String>) {
+    for (key, item) in table.iter_mut() {
+        let Some(field) = setting_field(key.get()) else {
+            continue;
+        };
+        escape_item(
+            item,
+            field.kind == SettingKind::List,
+            &mut |text, escaped| {
+                changes.push(format!(
+                    "{}.{}: \"{}\" -> \"{}\"",
+                    scope,
+                    key.get(),
+                    text,
+                    escaped
+                ))
+            },
+        );
+    }
+}
+
+fn escape_item(item: &mut Item, list: bool, changed: &mut dyn FnMut(&str, &str)) {
+    match item {
+        Item::Value(value) => escape_document_value(value, list, changed),
+        Item::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                escape_item(item, false, changed);
+            }
+        }
+        Item::ArrayOfTables(tables) => {
+            for table in tables.iter_mut() {
+                for (_, item) in table.iter_mut() {
+                    escape_item(item, false, changed);
+                }
+            }
+        }
+        Item::None => {}
+    }
+}
+
+fn escape_document_value(value: &mut Value, list: bool, changed: &mut dyn FnMut(&str, &str)) {
+    match value {
+        Value::String(text) => {
+            if let Some(escaped) = escape_literal(text.value(), list) {
+                changed(text.value(), &escaped);
+                let decor = text.decor().clone();
+                *text = Formatted::new(escaped);
+                *text.decor_mut() = decor;
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                escape_document_value(item, list, changed);
+            }
+        }
+        Value::InlineTable(table) => {
+            for (_, item) in table.iter_mut() {
+                escape_document_value(item, false, changed);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn with_version_line(data: &str) -> String {
+    let lines = data.lines().collect::<Vec<_>>();
+    let header = lines
+        .iter()
+        .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with('#'))
+        .count();
+    let mut output = lines[..header].to_vec();
+    let version = format!("{} = {}", VERSION_KEY, CONFIG_VERSION);
+    output.push(&version);
+    if lines
+        .get(header)
+        .is_some_and(|line| line.trim_start().starts_with('['))
+    {
+        output.push("");
+    }
+    output.extend(&lines[header..]);
+    output.join("\n") + "\n"
+}
@@ -1,87 +1,337 @@
+mod conditions;
+mod edit;
+mod group_info;
 mod groups;
+mod includes;
+mod init;
+mod interpolate;
 mod merge;
+mod migrate;
+mod mounts;
 mod parse;
+mod provenance;
+mod schema;
+mod settings;
+mod trust;
 mod types;
+mod validate;
 
+pub use conditions::{ConditionMatch, GroupCondition};
+pub use edit::{ConfigEdit, edit_config_file, get_config_value};
+pub use group_info::{GraphFormat, GroupInfo, render_group, render_group_graph, render_group_list};
 pub use groups::{
-    merge_group_definitions, normalize_group_order, resolve_group_order, validate_group_selection,
+    GroupExplanation, GroupReason, merge_group_definitions, normalize_group_order,
+    resolve_group_order, validate_group_selection,
 };
+pub use init::{init_config, render_init_config};
+pub use interpolate::expand_port_references;
 pub use merge::{resolve_include_groups, resolve_settings};
-pub use types::{Config, Engine, GroupConfig, ResolvedConfig, Settings, Sources};
+pub use migrate::{CONFIG_VERSION, migrate_config_file};
+pub use mounts::{MountEntry, MountTable};
+pub use provenance::{Origin, SettingsTrace, TracedSetting, TracedValue, render_trace};
+pub use schema::config_schema;
+pub use settings::{
+    Merge, SETTING_FIELDS, SettingField, SettingKind, SettingValue, Settings, is_list_edit,
+    setting_field,
+};
+pub use trust::{TrustState, UntrustedProject, untrusted_project_error};
+pub(crate) use trust::{hex, path_key};
+pub use types::{Config, Engine, GroupConfig, ResolvedConfig, Sources, WorkspaceMode};
+pub use validate::{Diagnostic, validate_files};
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use crate::cli;
 use crate::error::AppError;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct LoadedConfigSources {
     pub defaults: Config,
     pub file: Config,
+    pub project: Config,
+    pub project_path: Option<PathBuf>,
+    pub untrusted_project: Option<UntrustedProject>,
     pub env: Config,
 }
 
+impl LoadedConfigSources {
+    pub fn group_definitions(&self) -> Result<BTreeMap<String, GroupConfig>, AppError> {
+        let groups = merge_group_definitions(&self.defaults.groups, &self.file.groups)?;
+        merge_group_definitions(&groups, &self.project.groups)
+    }
+
+    pub fn group_infos(&self) -> Result<Vec<GroupInfo>, AppError> {
+        let file_origin = Origin::File(config_path()?.display().to_string());
+        let project_origin = Origin::Project(
+            self.project_path
+                .as_deref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+        );
+        let mut origins = BTreeMap::new();
+        for (config, origin) in [
+            (&self.defaults, Origin::Default),
+            (&self.file, file_origin),
+            (&self.project, project_origin),
+        ] {
+            for (name, group) in &config.groups {
+                if group.disabled {
+                    origins.remove(name.trim());
+                } else {
+                    let origin = match (&origin, &group.source) {
+                        (Origin::File(_), Some(source)) => {
+                            Origin::File(source.display().to_string())
+                        }
+                        _ => origin.clone(),
+                    };
+                    origins.insert(name.trim().to_string(), origin);
+                }
+            }
+        }
+
+        Ok(self
+            .group_definitions()?
+            .into_iter()
+            .map(|(name, group)| GroupInfo {
+                origin: origins.remove(&name).unwrap_or(Origin::Default),
+                name,
+                group,
+            })
+            .collect())
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        self.file
+            .warnings
+            .iter()
+            .chain(&self.project.warnings)
+            .cloned()
+            .collect()
+    }
+
+    pub fn base_group_order(&self) -> Result<Vec<String>, AppError> {
+        normalize_group_order(&resolve_include_groups(&[
+            &self.defaults,
+            &self.file,
+            &self.project,
+            &self.env,
+        ]))
+    }
+
+    fn root_group_order(
+        &self,
+        group_defs: &BTreeMap<String, GroupConfig>,
+        paths: &[String],
+    ) -> Result<(Vec<String>, conditions::GroupMatches), AppError> {
+        let mut order = self.base_group_order()?;
+        validate_group_selection(group_defs, &order)?;
+        let matches = conditions::matching_groups(group_defs, &condition_dirs(paths)?);
+        for (name, _) in &matches {
+            if !order.contains(name) {
+                order.push(name.clone());
+            }
+        }
+        Ok((order, matches))
+    }
+
+    fn project_dir(&self) -> Result<PathBuf, AppError> {
+        match self.project_path.as_deref().and_then(Path::parent) {
+            Some(dir) => Ok(dir.to_path_buf()),
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+
+    fn settings_sources(&self, cli: &Settings) -> Sources {
+        Sources {
+            defaults: self.defaults.settings.clone(),
+            file: self.file.settings.clone(),
+            project: self.project.settings.clone(),
+            env: self.env.settings.clone(),
+            cli: cli.clone(),
+        }
+    }
+}
+
 pub fn resolve(
     parsed: &cli::ParsedCLI,
     sources: &LoadedConfigSources,
 ) -> Result<ResolvedConfig, AppError> {
-    let group_defs = merge_group_definitions(&sources.defaults.groups, &sources.file.groups)?;
-    let base_order = normalize_group_order(&resolve_include_groups(
-        &sources.defaults,
-        &sources.file,
-        &sources.env,
-        &Config::default(),
-    ))?;
-    validate_group_selection(&group_defs, &base_order)?;
+    let group_defs = sources.group_definitions()?;
+    let (root_order, _) = sources.root_group_order(&group_defs, &parsed.paths)?;
 
     let group_flags = cli::collect_group_flags_from_names(parsed, &group_defs);
-    let group_order = resolve_group_order(&base_order, &group_flags, &group_defs)?;
+    let group_order = resolve_group_order(&root_order, &group_flags, &group_defs)?;
 
-    let final_settings = resolve_settings(
-        Sources {
-            defaults: sources.defaults.settings.clone(),
-            file: sources.file.settings.clone(),
-            env: sources.env.settings.clone(),
-            cli: parsed.settings.clone(),
-        },
+    let mut final_settings = resolve_settings(
+        sources.settings_sources(&parsed.settings),
         &group_defs,
         &group_order,
     )?;
+    interpolate::interpolate_settings(&mut final_settings, &sources.project_dir()?)?;
     crate::cli::validate_settings(&final_settings)?;
 
     Ok(ResolvedConfig {
         settings: final_settings,
         paths: parsed.paths.clone(),
         skip_cwd: parsed.skip_cwd,
+        allow_sensitive_mount: parsed.allow_sensitive_mount,
+        worktree: parsed.worktree.clone(),
+        session_name: parsed.session_name.clone(),
+        detach: parsed.detach,
+        groups: group_order,
     })
 }
 
-pub fn resolve_global_settings(
+pub fn trace(
+    selected_groups: &[String],
     cli_settings: &Settings,
     sources: &LoadedConfigSources,
-) -> Result<Settings, AppError> {
-    let group_defs = merge_group_definitions(&sources.defaults.groups, &sources.file.groups)?;
-    let group_order = normalize_group_order(&resolve_include_groups(
-        &sources.defaults,
-        &sources.file,
-        &sources.env,
-        &Config::default(),
-    ))?;
-    validate_group_selection(&group_defs, &group_order)?;
-    let group_order = resolve_group_order(
-        &group_order,
-        &std::collections::BTreeMap::new(),
+) -> Result<SettingsTrace, AppError> {
+    let group_defs = sources.group_definitions()?;
+    let (root_order, _) = sources.root_group_order(&group_defs, &[])?;
+
+    let mut group_flags = BTreeMap::new();
+    for (index, name) in normalize_group_order(selected_groups)?
+        .into_iter()
+        .enumerate()
+    {
+        if !group_defs.contains_key(&name) {
+            return Err(AppError::message(format!(
+                "ERROR: unknown group \"{}\"",
+                name
+            )));
+        }
+        group_flags.insert(
+            name,
+            cli::GroupFlag {
+                set: true,
+                disabled: false,
+                order: index + 1,
+            },
+        );
+    }
+    let group_order = resolve_group_order(&root_order, &group_flags, &group_defs)?;
+
+    let layers = merge::settings_layers(
+        sources.settings_sources(cli_settings),
         &group_defs,
+        &group_order,
     )?;
+    Ok(SettingsTrace {
+        settings: provenance::trace_settings(
+            layers,
+            &config_path()?,
+            &sources.file.setting_sources,
+            sources.project_path.as_deref(),
+            &sources.project_dir()?,
+        )?,
+        groups: group_order,
+    })
+}
 
-    resolve_settings(
-        Sources {
-            defaults: sources.defaults.settings.clone(),
-            file: sources.file.settings.clone(),
-            env: sources.env.settings.clone(),
-            cli: cli_settings.clone(),
-        },
+pub fn explain_groups(
+    parsed: &cli::ParsedCLI,
+    sources: &LoadedConfigSources,
+) -> Result<Vec<GroupExplanation>, AppError> {
+    let group_defs = sources.group_definitions()?;
+    let (root_order, matches) = sources.root_group_order(&group_defs, &parsed.paths)?;
+    let group_flags = cli::collect_group_flags_from_names(parsed, &group_defs);
+    let group_order = resolve_group_order(&root_order, &group_flags, &group_defs)?;
+
+    let include_sources = [
+        (&sources.defaults, "defaults".to_string()),
+        (&sources.file, config_path()?.display().to_string()),
+        (
+            &sources.project,
+            sources
+                .project_path
+                .as_deref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+        ),
+        (&sources.env, "DUNGEON_INCLUDE_GROUPS".to_string()),
+    ];
+
+    Ok(group_order
+        .iter()
+        .map(|name| {
+            let included_by = || {
+                include_sources.iter().find_map(|(config, source)| {
+                    config
+                        .include_groups
+                        .iter()
+                        .flatten()
+                        .any(|included| included.trim() == name)
+                        .then(|| GroupReason::Included(source.clone()))
+                })
+            };
+            let matched = || {
+                matches
+                    .iter()
+                    .find(|(matched, _)| matched == name)
+                    .map(|(_, matched)| GroupReason::Condition(matched.clone()))
+            };
+            let dependency = || {
+                group_order
+                    .iter()
+                    .find(|parent| {
+                        group_defs[parent.as_str()]
+                            .include_groups
+                            .iter()
+                            .any(|included| included.trim() == name)
+                    })
+                    .map(|parent| GroupReason::Dependency(parent.clone()))
+            };
+            let reason = group_flags
+                .get(name)
+                .filter(|flag| flag.set)
+                .map(|_| GroupReason::Flag)
+                .or_else(included_by)
+                .or_else(matched)
+                .or_else(dependency)
+                .unwrap_or(GroupReason::Flag);
+            GroupExplanation {
+                name: name.clone(),
+                reason,
+            }
+        })
+        .collect())
+}
+
+pub fn resolve_global_settings(
+    cli_settings: &Settings,
+    sources: &LoadedConfigSources,
+) -> Result<Settings, AppError> {
+    let group_defs = sources.group_definitions()?;
+    let (root_order, _) = sources.root_group_order(&group_defs, &[])?;
+    let group_order = resolve_group_order(&root_order, &BTreeMap::new(), &group_defs)?;
+
+    let mut settings = resolve_settings(
+        sources.settings_sources(cli_settings),
         &group_defs,
         &group_order,
-    )
+    )?;
+    interpolate::interpolate_settings(&mut settings, &sources.project_dir()?)?;
+    Ok(settings)
+}
+
+fn condition_dirs(paths: &[String]) -> Result<Vec<PathBuf>, AppError> {
+    let cwd = std::env::current_dir()?;
+    let mut dirs = vec![cwd.clone()];
+    for path in paths {
+        let Ok(path) = cwd.join(path).canonicalize() else {
+            continue;
+        };
+        if path.is_dir() {
+            dirs.push(path);
+        } else if let Some(parent) = path.parent() {
+            dirs.push(parent.to_path_buf());
+        }
+    }
+    Ok(dirs)
 }
 
 pub fn load_defaults() -> Result<Config, AppError> {
@@ -92,20 +342,125 @@ pub fn load_from_file() -> Result<Config, AppError> {
     parse::load_from_file()
 }
 
+pub fn config_path() -> Result<PathBuf, AppError> {
+    parse::config_path()
+}
+
+pub fn data_dir() -> Result<PathBuf, AppError> {
+    trust::data_dir()
+}
+
+pub fn user_config_files() -> Result<Vec<PathBuf>, AppError> {
+    includes::user_config_files(&config_path()?)
+}
+
+pub fn config_files() -> Result<Vec<PathBuf>, AppError> {
+    let mut paths = user_config_files()?;
+    paths.extend(find_project_config(&std::env::current_dir()?));
+    Ok(paths)
+}
+
 pub fn load_from_env() -> Result<Config, AppError> {
     parse::load_from_env()
 }
 
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    parse::find_project_config(start)
+}
+
+pub fn project_config_path(dir: &Path) -> PathBuf {
+    dir.join(parse::PROJECT_CONFIG_FILE)
+}
+
+pub fn load_from_project(path: &Path) -> Result<(Config, TrustState), AppError> {
+    let data = parse::read_project_config(path)?;
+    let config = parse::parse_project_config(path, &data)?;
+    Ok((config, trust::trust_state(path, &data)?))
+}
+
+pub fn trust_project_config(path: &Path) -> Result<(), AppError> {
+    let data = parse::read_project_config(path)?;
+    parse::parse_project_config(path, &data)?;
+    trust::trust_project(path, &data)
+}
+
 pub fn load_sources() -> Result<LoadedConfigSources, AppError> {
+    load_sources_for_profile(None)
+}
+
+pub fn load_sources_for_profile(profile: Option<&str>) -> Result<LoadedConfigSources, AppError> {
+    let mut file = load_from_file()?;
+    let profile = profile
+        .map(str::to_string)
+        .or_else(|| std::env::var(parse::PROFILE_ENV).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    if let Some(name) = &profile {
+        file = apply_profile(file, name)?;
+    }
+
+    let project_path = find_project_config(&std::env::current_dir()?);
+    let mut project = Config::default();
+    let mut untrusted_project = None;
+    if let Some(path) = &project_path {
+        let (config, state) = load_from_project(path)?;
+        if state == TrustState::Trusted {
+            project = config;
+        } else {
+            untrusted_project = Some(UntrustedProject {
+                path: path.clone(),
+                config,
+                state,
+            });
+        }
+    }
+
     Ok(LoadedConfigSources {
         defaults: load_defaults()?,
-        file: load_from_file()?,
+        file,
+        project,
+        project_path,
+        untrusted_project,
         env: load_from_env()?,
     })
 }
 
+fn apply_profile(mut file: Config, name: &str) -> Result<Config, AppError> {
+    let Some(profile) = file.profiles.remove(name) else {
+        let available = file.profiles.keys().cloned().collect::<Vec<_>>();
+        return Err(AppError::message(if available.is_empty() {
+            format!(
+                "ERROR: unknown profile \"{}\"; no profiles are defined",
+                name
+            )
+        } else {
+            format!(
+                "ERROR: unknown profile \"{}\"; expected one of: {}",
+                name,
+                available.join(", ")
+            )
+        }));
+    };
+
+    let include_groups = profile.include_groups.clone();
+    let mut file = merge::merge_configs(
+        file,
+        Config {
+            include_groups: None,
+            ..profile
+        },
+    )?;
+    if include_groups.is_some() {
+        file.include_groups = include_groups;
+    }
+    Ok(file)
+}
+
 pub fn validate_dynamic_port_names(names: &[String], field: &str) -> Result<(), AppError> {
-    for name in names {
+    for name in names
+        .iter()
+        .filter_map(|name| settings::list_entry_value(name))
+    {
         let mut chars = name.bytes();
         if !matches!(chars.next(), Some(b'a'..=b'z'))
             || !chars.all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'_')
@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::AppError;
+
+use super::Config;
+
+pub(super) const MOUNTS_KEY: &str = "mounts";
+pub(super) const MOUNT_TABLE_KEYS: &[&str] = &["source", "target", "mode", "create", "optional"];
+pub(super) const MOUNT_MODES: &[&str] = &["ro", "rw"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MountEntry {
+    Spec(String),
+    Table(MountTable),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MountTable {
+    pub source: String,
+    pub target: String,
+    pub mode: Option<String>,
+    pub create: bool,
+    pub optional: bool,
+    pub label: String,
+    pub file: Option<PathBuf>,
+}
+
+impl MountEntry {
+    pub fn text(&self) -> &str {
+        match self {
+            MountEntry::Spec(spec) => spec,
+            MountEntry::Table(table) => &table.source,
+        }
+    }
+
+    pub(super) fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        match value {
+            toml::Value::String(spec) => Ok(MountEntry::Spec(spec.clone())),
+            toml::Value::Table(table) => MountTable::from_toml(label, table).map(MountEntry::Table),
+            _ => Err(AppError::message(format!(
+                "{} must be a list of strings or mount tables",
+                label
+            ))),
+        }
+    }
+
+    pub(super) fn to_toml(&self) -> toml::Value {
+        match self {
+            MountEntry::Spec(spec) => toml::Value::String(spec.clone()),
+            MountEntry::Table(table) => {
+                let mut values = toml::Table::new();
+                values.insert("source".to_string(), table.source.clone().into());
+                values.insert("target".to_string(), table.target.clone().into());
+                if let Some(mode) = &table.mode {
+                    values.insert("mode".to_string(), mode.clone().into());
+                }
+                if table.create {
+                    values.insert("create".to_string(), true.into());
+                }
+                if table.optional {
+                    values.insert("optional".to_string(), true.into());
+                }
+                toml::Value::Table(values)
+            }
+        }
+    }
+}
+
+impl From<&str> for MountEntry {
+    fn from(spec: &str) -> Self {
+        MountEntry::Spec(spec.to_string())
+    }
+}
+
+impl MountTable {
+    fn from_toml(label: &str, table: &toml::Table) -> Result<Self, AppError> {
+        let mut mount = MountTable {
+            label: label.to_string(),
+            ..MountTable::default()
+        };
+        for (key, value) in table {
+            let name = format!("{}.{}", label, key);
+            match key.as_str() {
+                "source" => mount.source = string_value(&name, value)?,
+                "target" => mount.target = string_value(&name, value)?,
+                "mode" => {
+                    let mode = string_value(&name, value)?;
+                    if !MOUNT_MODES.contains(&mode.as_str()) {
+                        return Err(AppError::message(format!(
+                            "{} must be one of: {}",
+                            name,
+                            MOUNT_MODES.join(", ")
+                        )));
+                    }
+                    mount.mode = Some(mode);
+                }
+                "create" => mount.create = bool_value(&name, value)?,
+                "optional" => mount.optional = bool_value(&name, value)?,
+                _ => {
+                    return Err(AppError::message(format!(
+                        "{} entry has unknown key \"{}\"; expected one of: {}",
+                        label,
+                        key,
+                        MOUNT_TABLE_KEYS.join(", ")
+                    )));
+                }
+            }
+        }
+        for (key, value) in [("source", &mount.source), ("target", &mount.target)] {
+            if value.trim().is_empty() {
+                return Err(AppError::message(format!(
+                    "{} entry needs a non-empty {}",
+                    label, key
+                )));
+            }
+        }
+        Ok(mount)
+    }
+
+    pub fn defined_at(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{} in {}", self.label, file.display()),
+            None => self.label.clone(),
+        }
+    }
+}
+
+pub(super) fn entry_text(value: &toml::Value) -> &str {
+    match value {
+        toml::Value::Table(table) => table.get("source").and_then(toml::Value::as_str),
+        value => value.as_str(),
+    }
+    .unwrap_or_default()
+}
+
+pub(super) fn set_mount_files(config: &mut Config, path: &Path) {
+    let groups = config.groups.values_mut().map(|group| &mut group.settings);
+    for settings in std::iter::once(&mut config.settings).chain(groups) {
+        for entry in settings.mounts.iter_mut().flatten() {
+            if let MountEntry::Table(table) = entry {
+                table.file = Some(path.to_path_buf());
+            }
+        }
+    }
+    for profile in config.profiles.values_mut() {
+        set_mount_files(profile, path);
+    }
+}
+
+fn string_value(name: &str, value: &toml::Value) -> Result<String, AppError> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::message(format!("{} must be a string", name)))
+}
+
+fn bool_value(name: &str, value: &toml::Value) -> Result<bool, AppError> {
+    value
+        .as_bool()
+        .ok_or_else(|| AppError::message(format!("{} must be a boolean", name)))
+}
@@ -1,9 +1,20 @@
 use crate::error::AppError;
-use std::{env, fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
 
-use super::{Config, Engine, GroupConfig, Settings};
+use super::{
+    Config, GroupConfig, SETTING_FIELDS, SettingKind, Settings,
+    conditions::{CONDITION_KEYS, GroupCondition},
+    includes, migrate, mounts,
+};
 
 const ENV_PREFIX: &str = "DUNGEON_";
+pub(super) const PROJECT_CONFIG_FILE: &str = ".dungeon.toml";
+pub(super) const PROFILE_TABLE: &str = "profile";
+pub(super) const PROFILE_ENV: &str = "DUNGEON_PROFILE";
 
 pub fn load_defaults() -> Result<Config, AppError> {
     let data = include_str!("defaults.toml");
@@ -14,66 +25,63 @@ pub fn load_defaults() -> Result<Config, AppError> {
 }
 
 pub fn load_from_file() -> Result<Config, AppError> {
-    let path = config_path()?;
-    let data = match fs::read_to_string(&path) {
-        Ok(data) => data,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
-        Err(err) => {
-            return Err(AppError::message(format!(
-                "read config {}: {}",
-                path.display(),
-                err
-            )));
-        }
-    };
-    parse_config(&data)
-        .map_err(|err| AppError::message(format!("parse config {}: {}", path.display(), err)))
+    includes::load_user_config(&config_path()?)
 }
 
-pub fn load_from_env() -> Result<Config, AppError> {
-    let mut cfg = Config::default();
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
 
-    if let Ok(value) = env::var(format!("{}ENGINE", ENV_PREFIX)) {
-        cfg.settings.engine = Some(parse_engine_value("engine", value.trim())?);
-    }
-    if let Ok(value) = env::var(format!("{}COMMAND", ENV_PREFIX)) {
-        cfg.settings.command = Some(value.trim().to_string());
-    }
-    if let Ok(value) = env::var(format!("{}IMAGE", ENV_PREFIX)) {
-        cfg.settings.image = Some(value.trim().to_string());
-    }
-    if let Ok(value) = env::var(format!("{}PORTS", ENV_PREFIX)) {
-        cfg.settings.ports = Some(split_env_list(&value));
-    }
-    if let Ok(value) = env::var(format!("{}DYNAMIC_PORTS", ENV_PREFIX)) {
-        let names = split_env_list(&value);
-        super::validate_dynamic_port_names(&names, "DUNGEON_DYNAMIC_PORTS")?;
-        cfg.settings.dynamic_ports = Some(names);
-    }
-    if let Ok(value) = env::var(format!("{}EXPOSE_HOST_PORTS", ENV_PREFIX)) {
-        cfg.settings.expose_host_ports = Some(split_env_list(&value));
-    }
-    if let Ok(value) = env::var(format!("{}CACHES", ENV_PREFIX)) {
-        cfg.settings.cache = Some(split_env_list(&value));
-    }
-    if let Ok(value) = env::var(format!("{}MOUNTS", ENV_PREFIX)) {
-        cfg.settings.mounts = Some(split_env_list(&value));
-    }
-    if let Ok(value) = env::var(format!("{}ENVS", ENV_PREFIX)) {
-        cfg.settings.env_vars = Some(split_env_list(&value));
-    }
-    if let Ok(value) = env::var(format!("{}ENV_FILES", ENV_PREFIX)) {
-        cfg.settings.env_files = Some(split_env_list(&value));
+pub fn read_project_config(path: &Path) -> Result<String, AppError> {
+    fs::read_to_string(path).map_err(|err| {
+        AppError::message(format!("read project config {}: {}", path.display(), err))
+    })
+}
+
+pub fn parse_project_config(path: &Path, data: &str) -> Result<Config, AppError> {
+    let mut config = parse_config(data).map_err(|err| {
+        AppError::message(format!("parse project config {}: {}", path.display(), err))
+    })?;
+    if !config.profiles.is_empty() {
+        return Err(AppError::message(format!(
+            "parse project config {}: profiles can only be defined in the user config",
+            path.display()
+        )));
     }
-    if let Ok(value) = env::var(format!("{}PODMAN_ARGS", ENV_PREFIX)) {
-        cfg.settings.podman_args = Some(split_env_list(&value));
+    if !config.include.is_empty() {
+        return Err(AppError::message(format!(
+            "parse project config {}: include can only be used in the user config",
+            path.display()
+        )));
     }
-    if let Ok(value) = env::var(format!("{}RUN_ARGS", ENV_PREFIX)) {
-        cfg.settings.run_args = Some(split_env_list(&value));
+    for warning in &mut config.warnings {
+        *warning = warning.replace(migrate::MIGRATE_HINT, migrate::PROJECT_MIGRATE_HINT);
     }
-    if let Ok(value) = env::var(format!("{}MOUNT_GIT_METADATA", ENV_PREFIX)) {
-        cfg.settings.mount_git_metadata =
-            Some(parse_bool_value("mount_git_metadata", value.trim())?);
+    Ok(with_file_source(config, path))
+}
+
+pub fn load_from_env() -> Result<Config, AppError> {
+    let mut cfg = Config::default();
+
+    for field in SETTING_FIELDS {
+        let name = field.env_var();
+        let Ok(raw) = env::var(&name) else {
+            continue;
+        };
+        let value = match field.kind {
+            SettingKind::Scalar => toml::Value::String(raw.trim().to_string()),
+            SettingKind::List => toml::Value::Array(
+                split_env_list(&raw)
+                    .into_iter()
+                    .map(toml::Value::String)
+                    .collect(),
+            ),
+            SettingKind::Flag => toml::Value::Boolean(parse_bool_value(&name, raw.trim())?),
+        };
+        cfg.settings.set(field.key, &name, &value)?;
     }
     if let Ok(value) = env::var(format!("{}INCLUDE_GROUPS", ENV_PREFIX)) {
         cfg.include_groups = Some(split_env_list(&value));
@@ -82,18 +90,31 @@ pub fn load_from_env() -> Result<Config, AppError> {
     Ok(cfg)
 }
 
-fn parse_config(data: &str) -> Result<Config, AppError> {
-    let raw: toml::Value = toml::from_str(data)?;
+pub(super) fn parse_config(data: &str) -> Result<Config, AppError> {
+    let mut raw: toml::Value = toml::from_str(data)?;
     let table = raw
-        .as_table()
+        .as_table_mut()
         .ok_or_else(|| AppError::message("config root must be a table"))?;
     let mut cfg = Config::default();
+    let version = migrate::file_version(table)?;
+    migrate::migrate_config(table, version, &mut cfg.warnings);
 
-    for (name, value) in table {
+    for (name, value) in table.iter() {
+        if name == migrate::VERSION_KEY {
+            continue;
+        }
         if name == "general" {
             parse_general_config(value, &mut cfg)?;
             continue;
         }
+        if name == PROFILE_TABLE {
+            cfg.profiles = parse_profiles(value)?;
+            continue;
+        }
+        if name == includes::INCLUDE_KEY {
+            cfg.include = parse_string_vec("config", name, value)?;
+            continue;
+        }
 
         let group = parse_group_config(name, value)?;
         cfg.groups.insert(name.to_string(), group);
@@ -124,6 +145,36 @@ fn parse_general_config(value: &toml::Value, cfg: &mut Config) -> Result<(), App
     Ok(())
 }
 
+fn parse_profiles(value: &toml::Value) -> Result<BTreeMap<String, Config>, AppError> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| AppError::message("[profile] must be a table of profiles"))?;
+
+    let mut profiles = BTreeMap::new();
+    for (name, value) in table {
+        let scope = format!("{}.{}", PROFILE_TABLE, name);
+        let table = value
+            .as_table()
+            .ok_or_else(|| AppError::message(format!("[{}] must be a table", scope)))?;
+        let mut profile = Config::default();
+        for (key, value) in table {
+            if value.is_table() {
+                let group = parse_group_config(&format!("{}.{}", scope, key), value)?;
+                profile.groups.insert(key.to_string(), group);
+            } else if key == "include_groups" {
+                profile.include_groups = Some(parse_string_vec(&scope, key, value)?);
+            } else if !parse_settings_key(&mut profile.settings, &scope, key, value)? {
+                return Err(AppError::message(format!(
+                    "[{}] has unknown key \"{}\"",
+                    scope, key
+                )));
+            }
+        }
+        profiles.insert(name.trim().to_string(), profile);
+    }
+    Ok(profiles)
+}
+
 fn parse_group_config(name: &str, value: &toml::Value) -> Result<GroupConfig, AppError> {
     let table = value
         .as_table()
@@ -139,6 +190,10 @@ fn parse_group_config(name: &str, value: &toml::Value) -> Result<GroupConfig, Ap
     for (key, value) in table {
         if key == "include_groups" {
             group.include_groups = parse_string_vec(name, key, value)?;
+        } else if key == "when" {
+            group.when = Some(parse_group_condition(name, value)?);
+        } else if key == "description" {
+            group.description = Some(parse_group_description(name, value)?);
         } else if !parse_settings_key(&mut group.settings, name, key, value)? {
             return Err(AppError::message(format!(
                 "group \"{}\" has unknown key \"{}\"",
@@ -150,89 +205,74 @@ fn parse_group_config(name: &str, value: &toml::Value) -> Result<GroupConfig, Ap
     Ok(group)
 }
 
-fn parse_settings_key(
-    settings: &mut Settings,
-    scope: &str,
-    key: &str,
-    value: &toml::Value,
-) -> Result<bool, AppError> {
-    match key {
-        "engine" => {
-            let raw = parse_string(scope, key, value)?;
-            settings.engine = Some(parse_engine_value(
-                &format!("{}.{}", scope, key),
-                raw.trim(),
-            )?);
-            Ok(true)
-        }
-        "mounts" => {
-            settings.mounts = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "caches" => {
-            settings.cache = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "envs" => {
-            settings.env_vars = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "env_files" => {
-            settings.env_files = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "command" => {
-            settings.command = Some(parse_string(scope, key, value)?);
-            Ok(true)
-        }
-        "image" => {
-            settings.image = Some(parse_string(scope, key, value)?);
-            Ok(true)
-        }
-        "ports" => {
-            settings.ports = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "dynamic_ports" => {
-            let names = parse_string_vec(scope, key, value)?;
-            super::validate_dynamic_port_names(&names, &format!("{}.{}", scope, key))?;
-            settings.dynamic_ports = Some(names);
-            Ok(true)
-        }
-        "expose_host_ports" => {
-            settings.expose_host_ports = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "podman_args" => {
-            settings.podman_args = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "run_args" => {
-            settings.run_args = Some(parse_string_vec(scope, key, value)?);
-            Ok(true)
-        }
-        "mount_git_metadata" => {
-            settings.mount_git_metadata = Some(parse_bool(scope, key, value)?);
-            Ok(true)
-        }
-        _ => Ok(false),
+pub(super) fn with_file_source(mut config: Config, path: &Path) -> Config {
+    for warning in &mut config.warnings {
+        *warning = format!("{}: {}", path.display(), warning);
     }
+    mounts::set_mount_files(&mut config, path);
+    config
 }
 
-fn parse_string(group: &str, key: &str, value: &toml::Value) -> Result<String, AppError> {
+pub(super) fn parse_group_description(
+    group: &str,
+    value: &toml::Value,
+) -> Result<String, AppError> {
     value
         .as_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| AppError::message(format!("{}.{} must be a string", group, key)))
+        .map(|value| value.trim().to_string())
+        .ok_or_else(|| AppError::message(format!("{}.description must be a string", group)))
 }
 
-fn parse_bool(scope: &str, key: &str, value: &toml::Value) -> Result<bool, AppError> {
-    value
-        .as_bool()
-        .ok_or_else(|| AppError::message(format!("{}.{} must be a boolean", scope, key)))
+pub(super) fn parse_group_condition(
+    group: &str,
+    value: &toml::Value,
+) -> Result<GroupCondition, AppError> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| AppError::message(format!("{}.when must be a table", group)))?;
+    let mut condition = GroupCondition::default();
+    for (key, value) in table {
+        let patterns = match value {
+            toml::Value::String(pattern) => vec![pattern.clone()],
+            toml::Value::Array(_) => parse_string_vec(&format!("{}.when", group), key, value)?,
+            _ => {
+                return Err(AppError::message(format!(
+                    "{}.when.{} must be a string or a list of strings",
+                    group, key
+                )));
+            }
+        };
+        match key.as_str() {
+            "path" => condition.path = patterns,
+            "file_exists" => condition.file_exists = patterns,
+            "git_remote" => condition.git_remote = patterns,
+            _ => {
+                return Err(AppError::message(format!(
+                    "{}.when has unknown key \"{}\"; expected one of: {}",
+                    group,
+                    key,
+                    CONDITION_KEYS.join(", ")
+                )));
+            }
+        }
+    }
+    Ok(condition)
+}
+
+pub(super) fn parse_settings_key(
+    settings: &mut Settings,
+    scope: &str,
+    key: &str,
+    value: &toml::Value,
+) -> Result<bool, AppError> {
+    settings.set(key, &format!("{}.{}", scope, key), value)
 }
 
-fn parse_string_vec(group: &str, key: &str, value: &toml::Value) -> Result<Vec<String>, AppError> {
+pub(super) fn parse_string_vec(
+    group: &str,
+    key: &str,
+    value: &toml::Value,
+) -> Result<Vec<String>, AppError> {
     match value {
         toml::Value::Array(values) => values
             .iter()
@@ -258,7 +298,7 @@ fn split_env_list(value: &str) -> Vec<String> {
         .collect()
 }
 
-fn parse_bool_value(scope: &str, value: &str) -> Result<bool, AppError> {
+pub(super) fn parse_bool_value(scope: &str, value: &str) -> Result<bool, AppError> {
     match value {
         "1" | "true" | "TRUE" | "yes" | "YES" | "on" | "ON" => Ok(true),
         "0" | "false" | "FALSE" | "no" | "NO" | "off" | "OFF" => Ok(false),
@@ -266,17 +306,7 @@ fn parse_bool_value(scope: &str, value: &str) -> Result<bool, AppError> {
     }
 }
 
-fn parse_engine_value(scope: &str, value: &str) -> Result<Engine, AppError> {
-    match value {
-        "podman" => Ok(Engine::Podman),
-        _ => Err(AppError::message(format!(
-            "{} must be one of: podman",
-            scope
-        ))),
-    }
-}
-
-fn config_path() -> Result<PathBuf, AppError> {
+pub fn config_path() -> Result<PathBuf, AppError> {
     let config_home = env::var("XDG_CONFIG_HOME").ok();
     let base = if let Some(path) = config_home {
         PathBuf::from(path)
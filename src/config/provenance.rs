@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+
+use super::{
+    Merge, SETTING_FIELDS, SettingField, SettingKind, Settings, interpolate, merge::Layer, mounts,
+    settings::apply_list_entry,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "layer", content = "source", rename_all = "lowercase")]
+pub enum Origin {
+    Default,
+    File(String),
+    Project(String),
+    Group(String),
+    Env(String),
+    Cli(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::File(path) => write!(f, "file {}", path),
+            Origin::Project(path) => write!(f, "project {}", path),
+            Origin::Group(name) => write!(f, "group {}", name),
+            Origin::Env(var) => write!(f, "env {}", var),
+            Origin::Cli(flag) => write!(f, "cli {}", flag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TracedValue {
+    pub value: toml::Value,
+    pub origin: Origin,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TracedSetting {
+    Scalar(TracedValue),
+    List(Vec<TracedValue>),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SettingsTrace {
+    pub groups: Vec<String>,
+    pub settings: BTreeMap<&'static str, TracedSetting>,
+}
+
+pub(crate) fn trace_settings(
+    layers: Vec<(Layer, Settings)>,
+    file_path: &Path,
+    file_sources: &[(PathBuf, Settings)],
+    project_path: Option<&Path>,
+    project_dir: &Path,
+) -> Result<BTreeMap<&'static str, TracedSetting>, AppError> {
+    let mut traced = BTreeMap::new();
+    for (layer, settings) in layers {
+        for (field, value) in setting_values(&settings) {
+            match (field.merge, value) {
+                (Merge::Append, toml::Value::Array(values)) => {
+                    let entry = traced
+                        .entry(field.key)
+                        .or_insert_with(|| TracedSetting::List(Vec::new()));
+                    if let TracedSetting::List(entries) = entry {
+                        for value in values {
+                            let origin = origin(
+                                &layer,
+                                field,
+                                &value,
+                                file_path,
+                                file_sources,
+                                project_path,
+                            );
+                            apply_list_entry(entries, TracedValue { value, origin }, |entry| {
+                                mounts::entry_text(&entry.value)
+                            });
+                        }
+                    }
+                }
+                (_, value) => {
+                    let origin =
+                        origin(&layer, field, &value, file_path, file_sources, project_path);
+                    traced.insert(
+                        field.key,
+                        TracedSetting::Scalar(TracedValue { value, origin }),
+                    );
+                }
+            }
+        }
+    }
+    interpolate_trace(&mut traced, project_dir)?;
+    Ok(traced)
+}
+
+/// Expands `${...}` and `!!` in the surviving values the way `resolve` does
+/// for the merged settings, so the trace shows the values a run uses.
+fn interpolate_trace(
+    traced: &mut BTreeMap<&'static str, TracedSetting>,
+    project_dir: &Path,
+) -> Result<(), AppError> {
+    let dynamic_ports = match traced.get("dynamic_ports") {
+        Some(TracedSetting::List(entries)) => entries
+            .iter()
+            .filter_map(|entry| entry.value.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+    for field in SETTING_FIELDS {
+        let entries = match traced.get_mut(field.key) {
+            Some(TracedSetting::Scalar(entry)) => vec![entry],
+            Some(TracedSetting::List(entries)) => entries.iter_mut().collect(),
+            None => continue,
+        };
+        for entry in entries {
+            let value = match field.kind {
+                SettingKind::List => toml::Value::Array(vec![entry.value.clone()]),
+                _ => entry.value.clone(),
+            };
+            let mut settings = Settings::default();
+            settings.set(field.key, field.key, &value)?;
+            interpolate::interpolate_variables(&mut settings, project_dir, &dynamic_ports)?;
+            let Some(value) = settings.get(field.key) else {
+                continue;
+            };
+            entry.value = match (field.kind, value) {
+                (SettingKind::List, toml::Value::Array(mut values)) => match values.pop() {
+                    Some(value) => value,
+                    None => continue,
+                },
+                (_, value) => value,
+            };
+        }
+    }
+    Ok(())
+}
+
+pub fn render_trace(trace: &SettingsTrace) -> String {
+    let mut lines = vec![format!("groups = {}", toml_list(&trace.groups))];
+    for (key, setting) in &trace.settings {
+        match setting {
+            TracedSetting::Scalar(entry) => {
+                lines.push(format!("{} = {}  # {}", key, entry.value, entry.origin));
+            }
+            TracedSetting::List(entries) if entries.is_empty() => {
+                lines.push(format!("{} = []", key));
+            }
+            TracedSetting::List(entries) => {
+                lines.push(format!("{} = [", key));
+                for entry in entries {
+                    lines.push(format!("  {},  # {}", entry.value, entry.origin));
+                }
+                lines.push("]".to_string());
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+fn toml_list(values: &[String]) -> String {
+    toml::Value::Array(values.iter().cloned().map(toml::Value::String).collect()).to_string()
+}
+
+/// The last user config file (`config.toml`, a `config.d` fragment or an
+/// included file) that sets `value`.
+fn file_source<'a>(
+    file_path: &'a Path,
+    file_sources: &'a [(PathBuf, Settings)],
+    field: &SettingField,
+    value: &toml::Value,
+) -> &'a Path {
+    file_sources
+        .iter()
+        .rev()
+        .find(|(_, settings)| match settings.get(field.key) {
+            Some(toml::Value::Array(entries)) if field.merge == Merge::Append => {
+                entries.contains(value)
+            }
+            Some(set) => &set == value,
+            None => false,
+        })
+        .map_or(file_path, |(path, _)| path.as_path())
+}
+
+fn origin(
+    layer: &Layer,
+    field: &SettingField,
+    value: &toml::Value,
+    file_path: &Path,
+    file_sources: &[(PathBuf, Settings)],
+    project_path: Option<&Path>,
+) -> Origin {
+    match layer {
+        Layer::Default => Origin::Default,
+        Layer::File => Origin::File(
+            file_source(file_path, file_sources, field, value)
+                .display()
+                .to_string(),
+        ),
+        Layer::Project => Origin::Project(
+            project_path
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+        ),
+        Layer::Group(name) => Origin::Group(name.clone()),
+        Layer::Env => Origin::Env(field.env_var()),
+        Layer::Cli => match (value, field.negated_flag) {
+            (toml::Value::Boolean(false), Some(negated_flag)) => {
+                Origin::Cli(format!("--{}", negated_flag))
+            }
+            _ => Origin::Cli(format!("--{}", field.flag)),
+        },
+    }
+}
+
+fn setting_values(settings: &Settings) -> Vec<(&'static SettingField, toml::Value)> {
+    SETTING_FIELDS
+        .iter()
+        .filter_map(|field| settings.get(field.key).map(|value| (field, value)))
+        .collect()
+}
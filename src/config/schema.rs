@@ -0,0 +1,159 @@
+use serde_json::{Map, Value, json};
+
+use super::{
+    SETTING_FIELDS, SettingField, SettingKind, conditions::CONDITION_KEYS, includes, migrate,
+    mounts,
+};
+
+const SCHEMA_DIALECT: &str = "http://json-schema.org/draft-07/schema#";
+const LIST_EDIT_PATTERN: &str = "^!";
+
+pub fn config_schema() -> Value {
+    let mut conditions = Map::new();
+    for key in CONDITION_KEYS {
+        conditions.insert(
+            key.to_string(),
+            json!({
+                "anyOf": [
+                    { "type": "string" },
+                    { "type": "array", "items": { "type": "string" } },
+                ],
+            }),
+        );
+    }
+
+    let mut group = settings_properties();
+    group.insert(
+        "description".to_string(),
+        json!({ "type": "string", "description": "Help text for the group's CLI flag" }),
+    );
+    group.insert(
+        "when".to_string(),
+        json!({
+            "type": "object",
+            "description": "Enable the group when every listed condition matches",
+            "properties": conditions,
+            "additionalProperties": false,
+        }),
+    );
+
+    group.insert(
+        "include_groups".to_string(),
+        include_groups("Groups to apply before this one"),
+    );
+
+    let mut general = settings_properties();
+    let mut profile = settings_properties();
+    for properties in [&mut general, &mut profile] {
+        properties.insert(
+            "include_groups".to_string(),
+            include_groups("Root groups to enable"),
+        );
+    }
+
+    json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "dungeon config",
+        "type": "object",
+        "definitions": {
+            "group": {
+                "type": "object",
+                "description": "A group of settings, enabled with its CLI flag; an empty table removes the group",
+                "properties": group,
+                "additionalProperties": false,
+            },
+        },
+        "properties": {
+            migrate::VERSION_KEY: {
+                "type": "integer",
+                "description": "Config format version",
+                "minimum": 1,
+                "maximum": migrate::CONFIG_VERSION,
+            },
+            "general": {
+                "type": "object",
+                "description": "Global settings",
+                "properties": general,
+                "additionalProperties": false,
+            },
+            includes::INCLUDE_KEY: {
+                "type": "array",
+                "description": "Files or globs to load before this file, relative to it",
+                "items": { "type": "string" },
+            },
+            super::parse::PROFILE_TABLE: {
+                "type": "object",
+                "description": "Named profiles selected with --profile or DUNGEON_PROFILE",
+                "additionalProperties": {
+                    "type": "object",
+                    "properties": profile,
+                    "additionalProperties": { "$ref": "#/definitions/group" },
+                },
+            },
+        },
+        "additionalProperties": { "$ref": "#/definitions/group" },
+    })
+}
+
+fn include_groups(description: &str) -> Value {
+    json!({
+        "type": "array",
+        "description": description,
+        "items": { "type": "string" },
+    })
+}
+
+fn settings_properties() -> Map<String, Value> {
+    SETTING_FIELDS
+        .iter()
+        .map(|field| (field.key.to_string(), setting_schema(field)))
+        .collect()
+}
+
+fn setting_schema(field: &SettingField) -> Value {
+    let mut value = match field.kind {
+        SettingKind::Flag => json!({ "type": "boolean" }),
+        SettingKind::Scalar => string_schema(field),
+        SettingKind::List => {
+            let entry = string_schema(field);
+            let items = match field.pattern {
+                Some(_) => json!({
+                    "anyOf": [entry, { "type": "string", "pattern": LIST_EDIT_PATTERN }],
+                }),
+                None if field.key == mounts::MOUNTS_KEY => json!({
+                    "anyOf": [entry, mount_table_schema()],
+                }),
+                None => entry,
+            };
+            json!({ "type": "array", "items": items })
+        }
+    };
+    value["description"] = Value::from(field.help);
+    value
+}
+
+fn mount_table_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "source": { "type": "string", "description": "Host path; ~ and relative paths are expanded" },
+            "target": { "type": "string", "description": "Absolute path inside the container" },
+            "mode": { "type": "string", "enum": mounts::MOUNT_MODES },
+            "create": { "type": "boolean", "description": "Create a missing source directory" },
+            "optional": { "type": "boolean", "description": "Skip the mount with a warning when the source is missing" },
+        },
+        "required": ["source", "target"],
+        "additionalProperties": false,
+    })
+}
+
+fn string_schema(field: &SettingField) -> Value {
+    let mut value = json!({ "type": "string" });
+    if !field.values.is_empty() {
+        value["enum"] = Value::from(field.values);
+    }
+    if let Some(pattern) = field.pattern {
+        value["pattern"] = Value::from(pattern);
+    }
+    value
+}
@@ -0,0 +1,473 @@
+use crate::error::AppError;
+
+use super::{Engine, MountEntry, WorkspaceMode, validate_dynamic_port_names};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    Scalar,
+    List,
+    Flag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Merge {
+    Override,
+    Append,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingField {
+    pub key: &'static str,
+    pub kind: SettingKind,
+    pub merge: Merge,
+    pub flag: &'static str,
+    pub negated_flag: Option<&'static str>,
+    pub help: &'static str,
+    pub negated_help: &'static str,
+    pub heading: &'static str,
+    pub hyphen_values: bool,
+    pub values: &'static [&'static str],
+    pub pattern: Option<&'static str>,
+}
+
+impl SettingField {
+    pub fn env_var(&self) -> String {
+        format!("DUNGEON_{}", self.key.to_uppercase())
+    }
+}
+
+pub trait SettingValue: Sized {
+    const KIND: SettingKind;
+    const VALUES: &'static [&'static str] = &[];
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError>;
+    fn to_toml(&self) -> toml::Value;
+
+    fn append(_base: Option<Self>, other: Self) -> Self {
+        other
+    }
+
+    fn map_text(self, _f: &mut TextMap<'_>) -> Result<Self, AppError> {
+        Ok(self)
+    }
+}
+
+pub type TextMap<'a> = dyn FnMut(&str) -> Result<String, AppError> + 'a;
+
+impl SettingValue for String {
+    const KIND: SettingKind = SettingKind::Scalar;
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        value
+            .as_str()
+            .map(|value| value.to_string())
+            .ok_or_else(|| AppError::message(format!("{} must be a string", label)))
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        toml::Value::String(self.clone())
+    }
+
+    fn map_text(self, f: &mut TextMap<'_>) -> Result<Self, AppError> {
+        f(&self)
+    }
+}
+
+impl SettingValue for Engine {
+    const KIND: SettingKind = SettingKind::Scalar;
+    const VALUES: &'static [&'static str] = &["podman", "docker"];
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        match String::from_toml(label, value)?.trim() {
+            "podman" => Ok(Engine::Podman),
+            "docker" => Ok(Engine::Docker),
+            _ => Err(AppError::message(format!(
+                "{} must be one of: {}",
+                label,
+                Self::VALUES.join(", ")
+            ))),
+        }
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        toml::Value::String(self.binary().to_string())
+    }
+}
+
+impl SettingValue for WorkspaceMode {
+    const KIND: SettingKind = SettingKind::Scalar;
+    const VALUES: &'static [&'static str] = &["rw", "ro", "overlay", "snapshot"];
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        match String::from_toml(label, value)?.trim() {
+            "rw" => Ok(WorkspaceMode::Rw),
+            "ro" => Ok(WorkspaceMode::Ro),
+            "overlay" => Ok(WorkspaceMode::Overlay),
+            "snapshot" => Ok(WorkspaceMode::Snapshot),
+            _ => Err(AppError::message(format!(
+                "{} must be one of: {}",
+                label,
+                Self::VALUES.join(", ")
+            ))),
+        }
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        toml::Value::String(self.as_str().to_string())
+    }
+}
+
+impl SettingValue for bool {
+    const KIND: SettingKind = SettingKind::Flag;
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        value
+            .as_bool()
+            .ok_or_else(|| AppError::message(format!("{} must be a boolean", label)))
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        toml::Value::Boolean(*self)
+    }
+}
+
+impl SettingValue for Vec<String> {
+    const KIND: SettingKind = SettingKind::List;
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        let error = || AppError::message(format!("{} must be a list of strings", label));
+        value
+            .as_array()
+            .ok_or_else(error)?
+            .iter()
+            .map(|item| item.as_str().map(|item| item.to_string()).ok_or_else(error))
+            .collect()
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        toml::Value::Array(self.iter().cloned().map(toml::Value::String).collect())
+    }
+
+    fn append(base: Option<Self>, other: Self) -> Self {
+        let mut entries = base.unwrap_or_default();
+        for entry in other {
+            apply_list_entry(&mut entries, entry, |entry| entry.as_str());
+        }
+        entries
+    }
+
+    fn map_text(self, f: &mut TextMap<'_>) -> Result<Self, AppError> {
+        self.iter().map(|entry| f(entry)).collect()
+    }
+}
+
+impl SettingValue for Vec<MountEntry> {
+    const KIND: SettingKind = SettingKind::List;
+
+    fn from_toml(label: &str, value: &toml::Value) -> Result<Self, AppError> {
+        value
+            .as_array()
+            .ok_or_else(|| {
+                AppError::message(format!(
+                    "{} must be a list of strings or mount tables",
+                    label
+                ))
+            })?
+            .iter()
+            .map(|item| MountEntry::from_toml(label, item))
+            .collect()
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        toml::Value::Array(self.iter().map(MountEntry::to_toml).collect())
+    }
+
+    fn append(base: Option<Self>, other: Self) -> Self {
+        let mut entries = base.unwrap_or_default();
+        for entry in other {
+            apply_list_entry(&mut entries, entry, MountEntry::text);
+        }
+        entries
+    }
+
+    fn map_text(self, f: &mut TextMap<'_>) -> Result<Self, AppError> {
+        self.into_iter()
+            .map(|entry| match entry {
+                MountEntry::Spec(spec) => Ok(MountEntry::Spec(f(&spec)?)),
+                MountEntry::Table(mut table) => {
+                    table.source = f(&table.source)?;
+                    table.target = f(&table.target)?;
+                    Ok(MountEntry::Table(table))
+                }
+            })
+            .collect()
+    }
+}
+
+const DYNAMIC_PORT_NAME_PATTERN: &str = "^[a-z][a-z0-9_]*$";
+
+pub(crate) const CLEAR_ENTRY: &str = "!*";
+const REMOVE_PREFIX: char = '!';
+pub(crate) const LITERAL_PREFIX: &str = "!!";
+
+pub(crate) fn apply_list_entry<T>(entries: &mut Vec<T>, entry: T, text: impl Fn(&T) -> &str) {
+    let value = text(&entry);
+    if value == CLEAR_ENTRY {
+        entries.clear();
+    } else if !value.starts_with(LITERAL_PREFIX)
+        && let Some(pattern) = value.strip_prefix(REMOVE_PREFIX)
+    {
+        let pattern = pattern.to_string();
+        entries.retain(|existing| !removes(&pattern, text(existing)));
+    } else {
+        entries.push(entry);
+    }
+}
+
+pub(crate) fn list_entry_value(entry: &str) -> Option<&str> {
+    match entry {
+        CLEAR_ENTRY => None,
+        _ => Some(entry.strip_prefix(REMOVE_PREFIX).unwrap_or(entry)),
+    }
+}
+
+pub fn is_list_edit(entry: &str) -> bool {
+    entry.starts_with(REMOVE_PREFIX) && !entry.starts_with(LITERAL_PREFIX)
+}
+
+/// `!!` escapes an entry that really starts with `!`; the escape is dropped
+/// once the layers are merged.
+pub(crate) fn unescape_list_entry(entry: &str) -> &str {
+    match entry.strip_prefix(REMOVE_PREFIX) {
+        Some(rest) if rest.starts_with(REMOVE_PREFIX) => rest,
+        _ => entry,
+    }
+}
+
+fn removes(pattern: &str, entry: &str) -> bool {
+    entry
+        .strip_prefix(pattern)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([':', '=']))
+}
+
+fn merge_values<T: SettingValue>(merge: Merge, base: Option<T>, other: Option<T>) -> Option<T> {
+    match (merge, base, other) {
+        (Merge::Append, base, Some(other)) => Some(T::append(base, other)),
+        (_, base, other) => other.or(base),
+    }
+}
+
+macro_rules! define_settings {
+    ($(
+        $field:ident: $ty:ty {
+            key: $key:literal,
+            merge: $merge:ident,
+            flag: $flag:literal,
+            $(negated_flag: $negated_flag:literal,)?
+            help: $help:literal,
+            $(negated_help: $negated_help:literal,)?
+            heading: $heading:literal,
+            $(hyphen_values: $hyphen_values:literal,)?
+            $(pattern: $pattern:expr,)?
+            $(validate: $validate:path,)?
+        }
+    ),* $(,)?) => {
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        pub struct Settings {
+            $(
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        pub const SETTING_FIELDS: &[SettingField] = &[$(
+            SettingField {
+                key: $key,
+                kind: <$ty as SettingValue>::KIND,
+                merge: Merge::$merge,
+                flag: $flag,
+                negated_flag: define_settings!(@option $($negated_flag)?),
+                help: $help,
+                negated_help: define_settings!(@str $($negated_help)?),
+                heading: $heading,
+                hyphen_values: define_settings!(@bool $($hyphen_values)?),
+                values: <$ty as SettingValue>::VALUES,
+                pattern: define_settings!(@option $($pattern)?),
+            },
+        )*];
+
+        impl Settings {
+            pub fn get(&self, key: &str) -> Option<toml::Value> {
+                match key {
+                    $($key => self.$field.as_ref().map(SettingValue::to_toml),)*
+                    _ => None,
+                }
+            }
+
+            pub fn set(
+                &mut self,
+                key: &str,
+                label: &str,
+                value: &toml::Value,
+            ) -> Result<bool, AppError> {
+                match key {
+                    $($key => {
+                        let parsed = <$ty as SettingValue>::from_toml(label, value)?;
+                        $($validate(&parsed, label)?;)?
+                        self.$field = Some(parsed);
+                        Ok(true)
+                    })*
+                    _ => Ok(false),
+                }
+            }
+
+            pub fn map_text(&mut self, key: &str, f: &mut TextMap<'_>) -> Result<(), AppError> {
+                match key {
+                    $($key => {
+                        if let Some(value) = self.$field.take() {
+                            self.$field = Some(SettingValue::map_text(value, f)?);
+                        }
+                    })*
+                    _ => {}
+                }
+                Ok(())
+            }
+
+            pub fn merge(self, other: Settings) -> Settings {
+                Settings {
+                    $($field: merge_values(Merge::$merge, self.$field, other.$field),)*
+                }
+            }
+        }
+    };
+    (@option) => { None };
+    (@option $value:expr) => { Some($value) };
+    (@str) => { "" };
+    (@str $value:literal) => { $value };
+    (@bool) => { false };
+    (@bool $value:literal) => { $value };
+}
+
+define_settings! {
+    engine: Engine {
+        key: "engine",
+        merge: Override,
+        flag: "engine",
+        help: "Select the container engine (podman or docker)",
+        heading: "Configurations",
+    },
+    command: String {
+        key: "command",
+        merge: Override,
+        flag: "command",
+        help: "Run a command inside the container",
+        heading: "Configurations",
+    },
+    image: String {
+        key: "image",
+        merge: Override,
+        flag: "image",
+        help: "Select the container image",
+        heading: "Configurations",
+    },
+    ports: Vec<String> {
+        key: "ports",
+        merge: Append,
+        flag: "port",
+        help: "Publish a container port (repeatable)",
+        heading: "Configurations",
+    },
+    dynamic_ports: Vec<String> {
+        key: "dynamic_ports",
+        merge: Append,
+        flag: "dynamic-port",
+        help: "Publish a dynamic loopback port and set its environment variable (repeatable)",
+        heading: "Configurations",
+        pattern: DYNAMIC_PORT_NAME_PATTERN,
+        validate: validate_dynamic_port_names,
+    },
+    expose_host_ports: Vec<String> {
+        key: "expose_host_ports",
+        merge: Append,
+        flag: "expose-host-port",
+        help: "Expose a host loopback TCP port or range inside the container (repeatable)",
+        heading: "Network",
+    },
+    cache: Vec<String> {
+        key: "caches",
+        merge: Append,
+        flag: "cache",
+        help: "Mount a cache volume target (repeatable)",
+        heading: "Configurations",
+    },
+    mounts: Vec<MountEntry> {
+        key: "mounts",
+        merge: Append,
+        flag: "mount",
+        help: "Bind-mount a host path (repeatable)",
+        heading: "Configurations",
+    },
+    workspace_mode: WorkspaceMode {
+        key: "workspace_mode",
+        merge: Override,
+        flag: "workspace-mode",
+        help: "Mount the workspace rw, ro, through an overlay or as a snapshot copy",
+        heading: "Configurations",
+    },
+    sensitive_paths: Vec<String> {
+        key: "sensitive_paths",
+        merge: Append,
+        flag: "sensitive-path",
+        help: "Refuse mounts of a host path, its contents or its parents (repeatable)",
+        heading: "Configurations",
+    },
+    allow_sensitive_paths: Vec<String> {
+        key: "allow_sensitive_paths",
+        merge: Append,
+        flag: "allow-sensitive-path",
+        help: "Allow mounting a host path covered by sensitive_paths (repeatable)",
+        heading: "Configurations",
+    },
+    env_vars: Vec<String> {
+        key: "envs",
+        merge: Append,
+        flag: "env",
+        help: "Add a container environment variable (repeatable)",
+        heading: "Configurations",
+    },
+    env_files: Vec<String> {
+        key: "env_files",
+        merge: Append,
+        flag: "env-file",
+        help: "Add a container env-file (repeatable)",
+        heading: "Configurations",
+    },
+    podman_args: Vec<String> {
+        key: "podman_args",
+        merge: Append,
+        flag: "podman-arg",
+        help: "Append an extra podman argument before the subcommand (repeatable)",
+        heading: "Configurations",
+        hyphen_values: true,
+    },
+    run_args: Vec<String> {
+        key: "run_args",
+        merge: Append,
+        flag: "run-arg",
+        help: "Append an extra podman run argument (repeatable)",
+        heading: "Configurations",
+        hyphen_values: true,
+    },
+    mount_git_metadata: bool {
+        key: "mount_git_metadata",
+        merge: Override,
+        flag: "mount-git-metadata",
+        negated_flag: "no-mount-git-metadata",
+        help: "Mount external Git metadata for worktrees",
+        negated_help: "Do not mount external Git metadata",
+        heading: "Configurations",
+    },
+}
+
+pub fn setting_field(key: &str) -> Option<&'static SettingField> {
+    SETTING_FIELDS.iter().find(|field| field.key == key)
+}
@@ -0,0 +1,154 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+
+use super::{Config, SETTING_FIELDS, Settings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustState {
+    Trusted,
+    Unknown,
+    Changed,
+}
+
+#[derive(Debug, Clone)]
+pub struct UntrustedProject {
+    pub path: PathBuf,
+    pub config: Config,
+    pub state: TrustState,
+}
+
+pub fn trust_state(path: &Path, data: &str) -> Result<TrustState, AppError> {
+    let entry = trust_entry_path(path)?;
+    let stored = match fs::read_to_string(&entry) {
+        Ok(stored) => stored,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(TrustState::Unknown),
+        Err(err) => {
+            return Err(AppError::message(format!(
+                "read trust store {}: {}",
+                entry.display(),
+                err
+            )));
+        }
+    };
+
+    let stored_hash = stored.split_whitespace().next().unwrap_or_default();
+    if stored_hash == content_hash(data) {
+        Ok(TrustState::Trusted)
+    } else {
+        Ok(TrustState::Changed)
+    }
+}
+
+pub fn trust_project(path: &Path, data: &str) -> Result<(), AppError> {
+    let entry = trust_entry_path(path)?;
+    if let Some(parent) = entry.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            AppError::message(format!("create trust store {}: {}", parent.display(), err))
+        })?;
+    }
+    fs::write(
+        &entry,
+        format!("{} {}\n", content_hash(data), path.display()),
+    )
+    .map_err(|err| AppError::message(format!("write trust store {}: {}", entry.display(), err)))
+}
+
+pub fn untrusted_project_error(project: &UntrustedProject) -> AppError {
+    let reason = match project.state {
+        TrustState::Changed => "has changed since it was trusted",
+        _ => "is not trusted",
+    };
+    let mut lines = vec![format!(
+        "ERROR: project config {} {}",
+        project.path.display(),
+        reason
+    )];
+    let summary = describe_config(&project.config);
+    if summary.is_empty() {
+        lines.push("It does not add any settings.".to_string());
+    } else {
+        lines.push("It would add:".to_string());
+        lines.extend(summary.into_iter().map(|line| format!("  {}", line)));
+    }
+    lines.push("Review it, then run `dungeon trust` to apply it.".to_string());
+    AppError::message(lines.join("\n"))
+}
+
+fn describe_config(cfg: &Config) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(groups) = &cfg.include_groups {
+        lines.push(format!("general.include_groups = {:?}", groups));
+    }
+    describe_settings("general", &cfg.settings, &mut lines);
+    for (name, group) in &cfg.groups {
+        if group.disabled {
+            lines.push(format!("{} removed", name));
+            continue;
+        }
+        if !group.include_groups.is_empty() {
+            lines.push(format!(
+                "{}.include_groups = {:?}",
+                name, group.include_groups
+            ));
+        }
+        if let Some(when) = &group.when {
+            for (key, patterns) in when.patterns() {
+                if !patterns.is_empty() {
+                    lines.push(format!(
+                        "{}.when.{} = {:?}  (enables {} automatically)",
+                        name, key, patterns, name
+                    ));
+                }
+            }
+        }
+        describe_settings(name, &group.settings, &mut lines);
+    }
+    lines
+}
+
+fn describe_settings(scope: &str, settings: &Settings, lines: &mut Vec<String>) {
+    for field in SETTING_FIELDS {
+        if let Some(value) = settings.get(field.key) {
+            lines.push(format!("{}.{} = {}", scope, field.key, value));
+        }
+    }
+}
+
+fn content_hash(data: &str) -> String {
+    hex(&Sha256::digest(data.as_bytes()))
+}
+
+fn trust_entry_path(path: &Path) -> Result<PathBuf, AppError> {
+    Ok(data_dir()?.join("trust").join(path_key(path)))
+}
+
+/// Hex SHA-256 of the canonical path, used to name per-path state files.
+pub(crate) fn path_key(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    hex(&Sha256::digest(path.to_string_lossy().as_bytes()))
+}
+
+pub(crate) fn data_dir() -> Result<PathBuf, AppError> {
+    let data_home = env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|path| !path.is_empty());
+    let base = if let Some(path) = data_home {
+        PathBuf::from(path)
+    } else {
+        dirs::home_dir()
+            .ok_or_else(|| AppError::message("unable to resolve home directory"))?
+            .join(".local")
+            .join("share")
+    };
+    Ok(base.join("dungeon"))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
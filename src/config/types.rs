@@ -1,34 +1,41 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::PathBuf};
+
+use super::{GroupCondition, Settings};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Engine {
     #[default]
     Podman,
+    Docker,
 }
 
 impl Engine {
     pub fn binary(self) -> &'static str {
         match self {
             Engine::Podman => "podman",
+            Engine::Docker => "docker",
         }
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
-pub struct Settings {
-    pub engine: Option<Engine>,
-    pub command: Option<String>,
-    pub image: Option<String>,
-    pub ports: Option<Vec<String>>,
-    pub dynamic_ports: Option<Vec<String>>,
-    pub expose_host_ports: Option<Vec<String>>,
-    pub cache: Option<Vec<String>>,
-    pub mounts: Option<Vec<String>>,
-    pub env_vars: Option<Vec<String>>,
-    pub env_files: Option<Vec<String>>,
-    pub podman_args: Option<Vec<String>>,
-    pub run_args: Option<Vec<String>>,
-    pub mount_git_metadata: Option<bool>,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WorkspaceMode {
+    #[default]
+    Rw,
+    Ro,
+    Overlay,
+    Snapshot,
+}
+
+impl WorkspaceMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WorkspaceMode::Rw => "rw",
+            WorkspaceMode::Ro => "ro",
+            WorkspaceMode::Overlay => "overlay",
+            WorkspaceMode::Snapshot => "snapshot",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -36,18 +43,27 @@ pub struct Config {
     pub settings: Settings,
     pub include_groups: Option<Vec<String>>,
     pub groups: BTreeMap<String, GroupConfig>,
+    pub profiles: BTreeMap<String, Config>,
+    pub include: Vec<String>,
+    pub warnings: Vec<String>,
+    pub setting_sources: Vec<(PathBuf, Settings)>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct GroupConfig {
     pub settings: Settings,
+    pub description: Option<String>,
     pub include_groups: Vec<String>,
+    pub when: Option<GroupCondition>,
     pub disabled: bool,
+    pub source: Option<PathBuf>,
 }
 
+#[derive(Default)]
 pub struct Sources {
     pub defaults: Settings,
     pub file: Settings,
+    pub project: Settings,
     pub env: Settings,
     pub cli: Settings,
 }
@@ -57,4 +73,9 @@ pub struct ResolvedConfig {
     pub settings: Settings,
     pub paths: Vec<String>,
     pub skip_cwd: bool,
+    pub allow_sensitive_mount: bool,
+    pub worktree: Option<String>,
+    pub session_name: Option<String>,
+    pub detach: bool,
+    pub groups: Vec<String>,
 }
@@ -0,0 +1,429 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use toml::{
+    Spanned,
+    de::{DeTable, DeValue},
+};
+
+use crate::{cli, error::AppError};
+
+use super::{Settings, includes, is_list_edit, migrate, parse};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+type Includes = Vec<(String, Option<Diagnostic>)>;
+type TableEntry<'a, 'de> = (&'a Spanned<Cow<'de, str>>, &'a Spanned<DeValue<'de>>);
+
+struct SourceFile<'a> {
+    path: &'a Path,
+    data: &'a str,
+}
+
+impl SourceFile<'_> {
+    fn at(&self, span: Range<usize>, message: impl Into<String>) -> Diagnostic {
+        let before = &self.data[..span.start.min(self.data.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        Diagnostic {
+            path: self.path.to_path_buf(),
+            line,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+pub fn validate_files(paths: &[PathBuf]) -> Result<Vec<Diagnostic>, AppError> {
+    let mut diagnostics = Vec::new();
+    let mut groups: BTreeMap<String, Includes> = parse::load_defaults()?
+        .groups
+        .into_iter()
+        .map(|(name, group)| {
+            let includes = group
+                .include_groups
+                .into_iter()
+                .map(|name| (name, None))
+                .collect();
+            (name, includes)
+        })
+        .collect();
+    let mut root_includes = Vec::new();
+
+    for path in paths {
+        let data = fs::read_to_string(path)
+            .map_err(|err| AppError::message(format!("read config {}: {}", path.display(), err)))?;
+        let file = SourceFile { path, data: &data };
+        check_file(&file, &mut groups, &mut root_includes, &mut diagnostics);
+    }
+
+    check_includes(&groups, &root_includes, &mut diagnostics);
+    diagnostics.sort_by_key(|diagnostic| {
+        (
+            paths.iter().position(|path| *path == diagnostic.path),
+            diagnostic.line,
+            diagnostic.column,
+        )
+    });
+    Ok(diagnostics)
+}
+
+fn check_file(
+    file: &SourceFile<'_>,
+    groups: &mut BTreeMap<String, Includes>,
+    root_includes: &mut Vec<(String, Diagnostic)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let (root, errors) = DeTable::parse_recoverable(file.data);
+    if !errors.is_empty() {
+        for err in errors {
+            diagnostics.push(file.at(err.span().unwrap_or(0..0), err.message()));
+        }
+        return;
+    }
+    let Ok(values) = toml::from_str::<toml::Table>(file.data) else {
+        return;
+    };
+
+    let mut entries = root.get_ref().iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(key, _)| key.span().start);
+    for (key, value) in entries {
+        let name = key.get_ref().as_ref();
+        if name == migrate::VERSION_KEY {
+            if let Err(err) = migrate::file_version(&values) {
+                diagnostics.push(file.at(value.span(), err.to_string()));
+            }
+            continue;
+        }
+        if is_project_file(file.path)
+            && let Some(message) = match name {
+                includes::INCLUDE_KEY => Some("include can only be used in the user config"),
+                parse::PROFILE_TABLE => Some("profiles can only be defined in the user config"),
+                _ => None,
+            }
+        {
+            diagnostics.push(file.at(key.span(), message));
+            continue;
+        }
+        if name == includes::INCLUDE_KEY {
+            if let Some(raw) = values.get(name)
+                && let Err(err) = parse::parse_string_vec("config", name, raw)
+            {
+                diagnostics.push(file.at(value.span(), err.to_string()));
+            }
+            continue;
+        }
+        let DeValue::Table(table) = value.get_ref() else {
+            let message = if name == "general" {
+                "[general] must be a table".to_string()
+            } else if name == parse::PROFILE_TABLE {
+                "[profile] must be a table of profiles".to_string()
+            } else {
+                format!("group \"{}\" must be a table", name)
+            };
+            diagnostics.push(file.at(value.span(), message));
+            continue;
+        };
+        let table_values = values.get(name).and_then(|value| value.as_table());
+
+        if name == "general" {
+            for (include, diagnostic) in check_table(
+                file,
+                "general",
+                table.iter().collect(),
+                table_values,
+                diagnostics,
+            ) {
+                root_includes.push((include, diagnostic));
+            }
+            continue;
+        }
+        if name == parse::PROFILE_TABLE {
+            check_profiles(
+                file,
+                table,
+                table_values,
+                groups,
+                root_includes,
+                diagnostics,
+            );
+            continue;
+        }
+
+        if cli::is_reserved_group_name(name.trim()) {
+            diagnostics.push(file.at(
+                key.span(),
+                format!(
+                    "group name '{}' conflicts with a reserved CLI flag",
+                    name.trim()
+                ),
+            ));
+        }
+        if table.is_empty() {
+            groups.remove(name.trim());
+            continue;
+        }
+        let includes = check_table(
+            file,
+            name,
+            table.iter().collect(),
+            table_values,
+            diagnostics,
+        )
+        .into_iter()
+        .map(|(include, diagnostic)| (include, Some(diagnostic)))
+        .collect();
+        groups.insert(name.trim().to_string(), includes);
+    }
+}
+
+/// `.dungeon.toml` follows the rules of `parse_project_config`.
+fn is_project_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == parse::PROJECT_CONFIG_FILE)
+}
+
+fn check_profiles(
+    file: &SourceFile<'_>,
+    table: &DeTable<'_>,
+    values: Option<&toml::Table>,
+    groups: &mut BTreeMap<String, Includes>,
+    root_includes: &mut Vec<(String, Diagnostic)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut profiles = table.iter().collect::<Vec<_>>();
+    profiles.sort_by_key(|(key, _)| key.span().start);
+    for (key, value) in profiles {
+        let name = key.get_ref().as_ref();
+        let scope = format!("{}.{}", parse::PROFILE_TABLE, name);
+        let DeValue::Table(profile) = value.get_ref() else {
+            diagnostics.push(file.at(value.span(), format!("[{}] must be a table", scope)));
+            continue;
+        };
+        let profile_values = values
+            .and_then(|values| values.get(name))
+            .and_then(|value| value.as_table());
+
+        let (group_entries, setting_entries): (Vec<_>, Vec<_>) = profile
+            .iter()
+            .partition(|(_, value)| matches!(value.get_ref(), DeValue::Table(_)));
+        root_includes.extend(check_table(
+            file,
+            &scope,
+            setting_entries,
+            profile_values,
+            diagnostics,
+        ));
+        for (key, value) in group_entries {
+            let group_name = key.get_ref().as_ref();
+            let DeValue::Table(group) = value.get_ref() else {
+                continue;
+            };
+            if cli::is_reserved_group_name(group_name.trim()) {
+                diagnostics.push(file.at(
+                    key.span(),
+                    format!(
+                        "group name '{}' conflicts with a reserved CLI flag",
+                        group_name.trim()
+                    ),
+                ));
+            }
+            if group.is_empty() {
+                continue;
+            }
+            let includes = check_table(
+                file,
+                group_name,
+                group.iter().collect(),
+                profile_values
+                    .and_then(|values| values.get(group_name))
+                    .and_then(|value| value.as_table()),
+                diagnostics,
+            )
+            .into_iter()
+            .map(|(include, diagnostic)| (include, Some(diagnostic)))
+            .collect();
+            groups.insert(group_name.trim().to_string(), includes);
+        }
+    }
+}
+
+fn is_general_scope(scope: &str) -> bool {
+    scope == "general" || scope.starts_with(&format!("{}.", parse::PROFILE_TABLE))
+}
+
+fn check_table(
+    file: &SourceFile<'_>,
+    scope: &str,
+    mut entries: Vec<TableEntry<'_, '_>>,
+    values: Option<&toml::Table>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<(String, Diagnostic)> {
+    let mut includes = Vec::new();
+    entries.sort_by_key(|(key, _)| key.span().start);
+    for (key, value) in entries {
+        let name = key.get_ref().as_ref();
+        let Some(raw) = values.and_then(|values| values.get(name)) else {
+            continue;
+        };
+
+        if name == "include_groups" {
+            match parse::parse_string_vec(scope, name, raw) {
+                Ok(_) => includes.extend(
+                    string_entries(value)
+                        .map(|(include, span)| (include.trim().to_string(), file.at(span, ""))),
+                ),
+                Err(err) => diagnostics.push(file.at(value.span(), err.to_string())),
+            }
+            continue;
+        }
+
+        if name == "when" && !is_general_scope(scope) {
+            if let Err(err) = parse::parse_group_condition(scope, raw) {
+                diagnostics.push(file.at(value.span(), err.to_string()));
+            }
+            continue;
+        }
+        if name == "description" && !is_general_scope(scope) {
+            if let Err(err) = parse::parse_group_description(scope, raw) {
+                diagnostics.push(file.at(value.span(), err.to_string()));
+            }
+            continue;
+        }
+
+        match parse::parse_settings_key(&mut Settings::default(), scope, name, raw) {
+            Ok(true) => {}
+            Ok(false) if is_general_scope(scope) => diagnostics.push(file.at(
+                key.span(),
+                format!("[{}] has unknown key \"{}\"", scope, name),
+            )),
+            Ok(false) => diagnostics.push(file.at(
+                key.span(),
+                format!("group \"{}\" has unknown key \"{}\"", scope, name),
+            )),
+            Err(err) => {
+                diagnostics.push(file.at(value.span(), err.to_string()));
+                continue;
+            }
+        }
+
+        if name == "expose_host_ports" {
+            for (spec, span) in string_entries(value) {
+                if !is_list_edit(spec) && !cli::is_valid_exposed_host_port_spec(spec.trim()) {
+                    diagnostics.push(file.at(
+                        span,
+                        format!(
+                            "invalid exposed host port specification \"{}\"; expected PORT, PORT:HOST_PORT, RANGE, or RANGE:HOST_RANGE",
+                            spec
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    includes
+}
+
+fn string_entries<'a>(
+    value: &'a Spanned<DeValue<'_>>,
+) -> impl Iterator<Item = (&'a str, Range<usize>)> {
+    let entries = match value.get_ref() {
+        DeValue::Array(entries) => &entries[..],
+        _ => &[],
+    };
+    entries.iter().filter_map(|entry| match entry.get_ref() {
+        DeValue::String(value) => Some((value.as_ref(), entry.span())),
+        _ => None,
+    })
+}
+
+fn check_includes(
+    groups: &BTreeMap<String, Includes>,
+    root_includes: &[(String, Diagnostic)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (name, location) in root_includes {
+        if !groups.contains_key(name) {
+            diagnostics.push(Diagnostic {
+                message: format!("include_groups includes unknown group \"{}\"", name),
+                ..location.clone()
+            });
+        }
+    }
+
+    for (name, includes) in groups {
+        for (included, location) in includes {
+            if let Some(location) = location
+                && !groups.contains_key(included)
+            {
+                diagnostics.push(Diagnostic {
+                    message: format!("group \"{}\" includes unknown group \"{}\"", name, included),
+                    ..location.clone()
+                });
+            }
+        }
+    }
+
+    let mut visited = BTreeSet::new();
+    for name in groups.keys() {
+        check_cycles(name, groups, &mut visited, &mut Vec::new(), diagnostics);
+    }
+}
+
+fn check_cycles(
+    name: &str,
+    groups: &BTreeMap<String, Includes>,
+    visited: &mut BTreeSet<String>,
+    active: &mut Vec<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if visited.contains(name) {
+        return;
+    }
+    active.push(name.to_string());
+    for (included, location) in groups.get(name).into_iter().flatten() {
+        if let Some(start) = active
+            .iter()
+            .position(|active_name| active_name == included)
+        {
+            if let Some(location) = location {
+                let mut cycle = active[start..].to_vec();
+                cycle.push(included.clone());
+                diagnostics.push(Diagnostic {
+                    message: format!("group inclusion cycle: {}", cycle.join(" -> ")),
+                    ..location.clone()
+                });
+            }
+            continue;
+        }
+        if groups.contains_key(included) {
+            check_cycles(included, groups, visited, active, diagnostics);
+        }
+    }
+    active.pop();
+    visited.insert(name.to_string());
+}
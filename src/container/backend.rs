@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Engine, error::AppError};
+
+pub const MANAGED_LABEL: &str = "dungeon.managed";
+pub const SESSION_LABEL: &str = "dungeon.session";
+pub const WORKSPACE_LABEL: &str = "dungeon.workspace";
+pub const GROUPS_LABEL: &str = "dungeon.groups";
+pub const IMAGE_LABEL: &str = "dungeon.image";
+pub const VERSION_LABEL: &str = "dungeon.version";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mount {
+    Bind(String),
+    Volume { name: String, spec: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunSession {
+    pub name: Option<String>,
+    pub detach: bool,
+    pub labels: Vec<String>,
+    pub workdir: String,
+    pub mounts: Vec<Mount>,
+    pub env_vars: Vec<String>,
+    pub env_files: Vec<String>,
+    pub ports: Vec<String>,
+    pub exposed_host_ports: Vec<String>,
+    pub run_args: Vec<String>,
+    pub image: String,
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageBuild {
+    pub containerfile: String,
+    pub tag: String,
+    pub no_cache: bool,
+    pub context: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContainerSummary {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub session: Option<String>,
+    pub workspace: String,
+    pub groups: Vec<String>,
+    pub image: String,
+    pub version: String,
+}
+
+pub trait ContainerEngine {
+    fn program(&self) -> &'static str;
+    fn run_session_args(&self, session: &RunSession) -> Vec<String>;
+    fn build_image_args(&self, build: &ImageBuild) -> Vec<String>;
+    fn remove_volume_args(&self, name: &str) -> Vec<String>;
+    fn list_containers_args(&self, label: &str, all: bool) -> Vec<String>;
+    fn parse_container_list(&self, output: &str) -> Result<Vec<ContainerSummary>, AppError>;
+    fn attach_args(&self, container: &str) -> Vec<String>;
+    fn exec_args(&self, container: &str, command: &[String]) -> Vec<String>;
+    fn stop_args(&self, container: &str) -> Vec<String>;
+    fn remove_container_args(&self, container: &str, force: bool) -> Vec<String>;
+}
+
+pub struct Podman;
+
+pub struct Docker;
+
+pub fn engine_backend(engine: Engine) -> &'static dyn ContainerEngine {
+    match engine {
+        Engine::Podman => &Podman,
+        Engine::Docker => &Docker,
+    }
+}
+
+impl ContainerEngine for Podman {
+    fn program(&self) -> &'static str {
+        Engine::Podman.binary()
+    }
+
+    fn run_session_args(&self, session: &RunSession) -> Vec<String> {
+        let mut args = run_prefix_args(session);
+        args.push("--userns=keep-id".to_string());
+        args.push("--user".to_string());
+        args.push("root".to_string());
+        args.push("-w".to_string());
+        args.push(session.workdir.clone());
+        append_lifecycle_args(&mut args, session);
+        append_exposed_host_port_args(&mut args, &session.exposed_host_ports);
+        append_session_args(&mut args, session);
+        args
+    }
+
+    fn build_image_args(&self, build: &ImageBuild) -> Vec<String> {
+        image_build_args(build)
+    }
+
+    fn remove_volume_args(&self, name: &str) -> Vec<String> {
+        volume_rm_args(name)
+    }
+
+    fn list_containers_args(&self, label: &str, all: bool) -> Vec<String> {
+        let mut args = ps_args(label, all);
+        args.push("--format".to_string());
+        args.push("json".to_string());
+        args
+    }
+
+    fn parse_container_list(&self, output: &str) -> Result<Vec<ContainerSummary>, AppError> {
+        if output.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let entries: Vec<PodmanContainer> = serde_json::from_str(output)
+            .map_err(|err| AppError::message(format!("parse podman ps output: {}", err)))?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let labels = entry.labels.unwrap_or_default();
+                container_summary(
+                    entry.id,
+                    entry.names.first().cloned().unwrap_or_default(),
+                    entry.status,
+                    |key| labels.get(key).cloned().unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    fn attach_args(&self, container: &str) -> Vec<String> {
+        attach_args(container)
+    }
+
+    fn exec_args(&self, container: &str, command: &[String]) -> Vec<String> {
+        exec_args(container, command)
+    }
+
+    fn stop_args(&self, container: &str) -> Vec<String> {
+        vec!["stop".to_string(), container.to_string()]
+    }
+
+    fn remove_container_args(&self, container: &str, force: bool) -> Vec<String> {
+        remove_container_args(container, force)
+    }
+}
+
+impl ContainerEngine for Docker {
+    fn program(&self) -> &'static str {
+        Engine::Docker.binary()
+    }
+
+    fn run_session_args(&self, session: &RunSession) -> Vec<String> {
+        let mut args = run_prefix_args(session);
+        args.push("--user".to_string());
+        args.push("root".to_string());
+        let (uid, gid) = caller_identity();
+        args.push("--env".to_string());
+        args.push(format!("{}={}", CALLER_UID_ENV, uid));
+        args.push("--env".to_string());
+        args.push(format!("{}={}", CALLER_GID_ENV, gid));
+        args.push("-w".to_string());
+        args.push(session.workdir.clone());
+        append_lifecycle_args(&mut args, session);
+        append_session_args(&mut args, session);
+        args
+    }
+
+    fn build_image_args(&self, build: &ImageBuild) -> Vec<String> {
+        image_build_args(build)
+    }
+
+    fn remove_volume_args(&self, name: &str) -> Vec<String> {
+        volume_rm_args(name)
+    }
+
+    fn list_containers_args(&self, label: &str, all: bool) -> Vec<String> {
+        let labels = [
+            SESSION_LABEL,
+            WORKSPACE_LABEL,
+            GROUPS_LABEL,
+            IMAGE_LABEL,
+            VERSION_LABEL,
+        ]
+        .iter()
+        .map(|key| format!("{{{{json (.Label \"{}\")}}}}", key))
+        .collect::<Vec<_>>()
+        .join(",");
+        let mut args = ps_args(label, all);
+        args.push("--format".to_string());
+        args.push(format!(
+            "[{{{{json .ID}}}},{{{{json .Names}}}},{{{{json .Status}}}},{}]",
+            labels
+        ));
+        args
+    }
+
+    fn parse_container_list(&self, output: &str) -> Result<Vec<ContainerSummary>, AppError> {
+        output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields: [String; 8] = serde_json::from_str(line)
+                    .map_err(|err| AppError::message(format!("parse docker ps output: {}", err)))?;
+                let [id, name, status, session, workspace, groups, image, version] = fields;
+                let labels = BTreeMap::from([
+                    (SESSION_LABEL, session),
+                    (WORKSPACE_LABEL, workspace),
+                    (GROUPS_LABEL, groups),
+                    (IMAGE_LABEL, image),
+                    (VERSION_LABEL, version),
+                ]);
+                Ok(container_summary(id, name, status, |key| {
+                    labels.get(key).cloned().unwrap_or_default()
+                }))
+            })
+            .collect()
+    }
+
+    fn attach_args(&self, container: &str) -> Vec<String> {
+        attach_args(container)
+    }
+
+    fn exec_args(&self, container: &str, command: &[String]) -> Vec<String> {
+        exec_args(container, command)
+    }
+
+    fn stop_args(&self, container: &str) -> Vec<String> {
+        vec!["stop".to_string(), container.to_string()]
+    }
+
+    fn remove_container_args(&self, container: &str, force: bool) -> Vec<String> {
+        remove_container_args(container, force)
+    }
+}
+
+#[derive(Deserialize)]
+struct PodmanContainer {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "Names", default)]
+    names: Vec<String>,
+    #[serde(rename = "Status", default)]
+    status: String,
+    #[serde(rename = "Labels", default)]
+    labels: Option<BTreeMap<String, String>>,
+}
+
+fn container_summary(
+    id: String,
+    name: String,
+    status: String,
+    label: impl Fn(&str) -> String,
+) -> ContainerSummary {
+    let session = label(SESSION_LABEL);
+    ContainerSummary {
+        id,
+        name,
+        status,
+        session: (!session.is_empty()).then_some(session),
+        workspace: label(WORKSPACE_LABEL),
+        groups: label(GROUPS_LABEL)
+            .split(',')
+            .filter(|group| !group.is_empty())
+            .map(|group| group.to_string())
+            .collect(),
+        image: label(IMAGE_LABEL),
+        version: label(VERSION_LABEL),
+    }
+}
+
+fn ps_args(label: &str, all: bool) -> Vec<String> {
+    let mut args = vec!["ps".to_string()];
+    if all {
+        args.push("--all".to_string());
+    }
+    args.push("--filter".to_string());
+    args.push(format!("label={}", label));
+    args
+}
+
+// Docker has no keep-id user namespace, so dungeon-bootstrap remaps the image's
+// dungeon user to these IDs before dropping privileges.
+const CALLER_UID_ENV: &str = "DUNGEON_UID";
+const CALLER_GID_ENV: &str = "DUNGEON_GID";
+
+fn caller_identity() -> (u32, u32) {
+    // SAFETY: getuid and getgid cannot fail and touch no memory.
+    unsafe { (libc::getuid(), libc::getgid()) }
+}
+
+fn run_prefix_args(session: &RunSession) -> Vec<String> {
+    let mut args = vec!["run".to_string()];
+    if session.detach {
+        args.push("-d".to_string());
+    }
+    args.push("-it".to_string());
+    args
+}
+
+fn append_lifecycle_args(args: &mut Vec<String>, session: &RunSession) {
+    match &session.name {
+        Some(name) => {
+            args.push("--name".to_string());
+            args.push(name.clone());
+        }
+        None => args.push("--rm".to_string()),
+    }
+    append_repeated_flag_args(args, "--label", &session.labels);
+}
+
+fn append_session_args(args: &mut Vec<String>, session: &RunSession) {
+    append_repeated_flag_args(args, "--env", &session.env_vars);
+    append_repeated_flag_args(args, "--env-file", &session.env_files);
+    append_repeated_flag_args(args, "-p", &session.ports);
+    args.extend(session.run_args.iter().cloned());
+    for mount in &session.mounts {
+        args.push("-v".to_string());
+        args.push(match mount {
+            Mount::Bind(spec) => spec.clone(),
+            Mount::Volume { name, spec } => format!("{}:{}", name, spec),
+        });
+    }
+    args.push(session.image.clone());
+    args.extend(session.command.iter().cloned());
+}
+
+fn append_repeated_flag_args(args: &mut Vec<String>, flag: &str, values: &[String]) {
+    for value in values {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        args.push(flag.to_string());
+        args.push(trimmed.to_string());
+    }
+}
+
+fn append_exposed_host_port_args(args: &mut Vec<String>, specs: &[String]) {
+    let options = specs
+        .iter()
+        .map(|spec| spec.trim())
+        .filter(|spec| !spec.is_empty())
+        .flat_map(|spec| ["-T", spec])
+        .collect::<Vec<_>>();
+
+    if !options.is_empty() {
+        args.push(format!("--network=pasta:{}", options.join(",")));
+    }
+}
+
+fn image_build_args(build: &ImageBuild) -> Vec<String> {
+    let mut args = vec![
+        "build".to_string(),
+        "-f".to_string(),
+        build.containerfile.clone(),
+        "-t".to_string(),
+        build.tag.clone(),
+    ];
+    if build.no_cache {
+        args.push("--no-cache".to_string());
+    }
+    args.push(build.context.clone());
+    args
+}
+
+fn volume_rm_args(name: &str) -> Vec<String> {
+    vec![
+        "volume".to_string(),
+        "rm".to_string(),
+        "-f".to_string(),
+        name.to_string(),
+    ]
+}
+
+fn attach_args(container: &str) -> Vec<String> {
+    vec!["attach".to_string(), container.to_string()]
+}
+
+fn exec_args(container: &str, command: &[String]) -> Vec<String> {
+    let mut args = vec![
+        "exec".to_string(),
+        "-it".to_string(),
+        "--user".to_string(),
+        "dungeon".to_string(),
+        container.to_string(),
+    ];
+    args.extend(command.iter().cloned());
+    args
+}
+
+fn remove_container_args(container: &str, force: bool) -> Vec<String> {
+    let mut args = vec!["rm".to_string()];
+    if force {
+        args.push("-f".to_string());
+    }
+    args.push(container.to_string());
+    args
+}
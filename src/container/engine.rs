@@ -1,15 +1,39 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     net::TcpListener,
     path::{Component, Path, PathBuf},
 };
 
 use crate::{
-    config::{Engine, Settings},
+    config::{self, Engine, MountEntry, MountTable, ResolvedConfig, Settings, WorkspaceMode},
     error::AppError,
 };
 
+use super::{
+    backend::{
+        ContainerEngine, GROUPS_LABEL, IMAGE_LABEL, ImageBuild, MANAGED_LABEL, Mount, RunSession,
+        SESSION_LABEL, VERSION_LABEL, WORKSPACE_LABEL, engine_backend,
+    },
+    workspace, worktree,
+};
+
 const WORKSPACE_ROOT: &str = "/workspace";
+const CACHE_VOLUME: &str = "dungeon-cache";
+const SESSION_CONTAINER_PREFIX: &str = "dungeon-";
+const SENSITIVE_HOME_PATHS: &[&str] = &[
+    ".ssh",
+    ".gnupg",
+    ".aws",
+    ".kube",
+    ".docker",
+    ".config/containers",
+];
+const SENSITIVE_SOCKETS: &[&str] = &[
+    "/var/run/docker.sock",
+    "/run/docker.sock",
+    "/run/podman/podman.sock",
+];
+const SENSITIVE_RUNTIME_SOCKETS: &[&str] = &["docker.sock", "podman/podman.sock"];
 
 #[derive(Debug, Clone)]
 pub struct CommandSpec {
@@ -24,10 +48,10 @@ pub struct DynamicPortReservations {
 
 pub fn reserve_dynamic_ports(settings: &mut Settings) -> Result<DynamicPortReservations, AppError> {
     let mut reservations = DynamicPortReservations::default();
-    let mut names = HashSet::new();
+    let mut ports = BTreeMap::new();
 
-    for name in settings.dynamic_ports.as_deref().unwrap_or(&[]) {
-        if !names.insert(name) {
+    for name in settings.dynamic_ports.clone().unwrap_or_default() {
+        if ports.contains_key(&name) {
             continue;
         }
         let listener = reserve_dynamic_port()?;
@@ -41,8 +65,10 @@ pub fn reserve_dynamic_ports(settings: &mut Settings) -> Result<DynamicPortReser
         env_vars.retain(|spec| !env_spec_has_name(spec, &env_key));
         env_vars.push(format!("{}={}", env_key, port));
         reservations.listeners.push(listener);
+        ports.insert(name, port);
     }
 
+    config::expand_port_references(settings, &ports)?;
     Ok(reservations)
 }
 
@@ -59,26 +85,30 @@ pub fn run_reserved_container_command(
 }
 
 pub fn build_podman_command(settings: &Settings, args: Vec<String>) -> CommandSpec {
-    let engine = settings.engine.unwrap_or_default();
+    build_engine_command(
+        engine_backend(settings.engine.unwrap_or_default()),
+        settings,
+        args,
+    )
+}
+
+pub fn build_engine_command(
+    engine: &dyn ContainerEngine,
+    settings: &Settings,
+    args: Vec<String>,
+) -> CommandSpec {
     let mut full_args = settings.podman_args.clone().unwrap_or_default();
     full_args.extend(args);
 
     CommandSpec {
-        program: engine.binary().to_string(),
+        program: engine.program().to_string(),
         args: full_args,
     }
 }
 
 pub fn build_cache_reset_command(settings: &Settings) -> CommandSpec {
-    build_podman_command(
-        settings,
-        vec![
-            "volume".to_string(),
-            "rm".to_string(),
-            "-f".to_string(),
-            "dungeon-cache".to_string(),
-        ],
-    )
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(engine, settings, engine.remove_volume_args(CACHE_VOLUME))
 }
 
 pub fn reset_cache_volume(settings: &Settings) -> Result<(), AppError> {
@@ -91,65 +121,205 @@ pub fn build_image_command(
     no_cache: bool,
     context: &str,
 ) -> CommandSpec {
-    let mut args = vec![
-        "build".to_string(),
-        "-f".to_string(),
-        "images/Containerfile".to_string(),
-        "-t".to_string(),
-        tag.to_string(),
-    ];
-    if no_cache {
-        args.push("--no-cache".to_string());
-    }
-    args.push(context.to_string());
-
-    build_podman_command(settings, args)
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    let build = ImageBuild {
+        containerfile: "images/Containerfile".to_string(),
+        tag: tag.to_string(),
+        no_cache,
+        context: context.to_string(),
+    };
+    build_engine_command(engine, settings, engine.build_image_args(&build))
 }
 
 pub fn build_container_command(
     settings: &Settings,
     paths: &[String],
     skip_cwd: bool,
+    allow_sensitive_mount: bool,
+) -> Result<CommandSpec, AppError> {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    let session = resolve_run_session(settings, paths, skip_cwd, allow_sensitive_mount)?;
+    Ok(build_engine_command(
+        engine,
+        settings,
+        engine.run_session_args(&session),
+    ))
+}
+
+pub fn build_resolved_container_command(
+    settings: &Settings,
+    resolved: &ResolvedConfig,
+    worktree: Option<&Path>,
 ) -> Result<CommandSpec, AppError> {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    let mut settings = settings.clone();
+    if worktree.is_some() {
+        settings.mount_git_metadata = Some(true);
+    }
+    let mut session = resolve_session(
+        &settings,
+        &resolved.paths,
+        resolved.skip_cwd,
+        resolved.allow_sensitive_mount,
+        worktree,
+    )?;
+    let workspace = workspace_sources(resolved, worktree, &std::env::current_dir()?)
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    session.labels.push(format!("{}=true", MANAGED_LABEL));
+    session
+        .labels
+        .push(format!("{}={}", WORKSPACE_LABEL, workspace));
+    session
+        .labels
+        .push(format!("{}={}", GROUPS_LABEL, resolved.groups.join(",")));
+    session
+        .labels
+        .push(format!("{}={}", IMAGE_LABEL, session.image));
+    session
+        .labels
+        .push(format!("{}={}", VERSION_LABEL, crate::cli::build_version()));
+    if let Some(name) = &resolved.session_name {
+        session.name = Some(session_container_name(name));
+        session.labels.push(format!("{}={}", SESSION_LABEL, name));
+    }
+    session.detach = resolved.detach;
+    Ok(build_engine_command(
+        engine,
+        &settings,
+        engine.run_session_args(&session),
+    ))
+}
+
+pub fn session_container_name(session: &str) -> String {
+    format!("{}{}", SESSION_CONTAINER_PREFIX, session)
+}
+
+pub fn build_attach_command(settings: &Settings, session: &str) -> CommandSpec {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(
+        engine,
+        settings,
+        engine.attach_args(&session_container_name(session)),
+    )
+}
+
+pub fn build_exec_command(
+    settings: &Settings,
+    session: &str,
+    command: Option<&str>,
+) -> CommandSpec {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(
+        engine,
+        settings,
+        engine.exec_args(&session_container_name(session), &session_command(command)),
+    )
+}
+
+pub fn build_stop_command(settings: &Settings, session: &str) -> CommandSpec {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(
+        engine,
+        settings,
+        engine.stop_args(&session_container_name(session)),
+    )
+}
+
+pub fn build_remove_command(settings: &Settings, session: &str, force: bool) -> CommandSpec {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(
+        engine,
+        settings,
+        engine.remove_container_args(&session_container_name(session), force),
+    )
+}
+
+pub fn resolve_run_session(
+    settings: &Settings,
+    paths: &[String],
+    skip_cwd: bool,
+    allow_sensitive_mount: bool,
+) -> Result<RunSession, AppError> {
+    resolve_session(settings, paths, skip_cwd, allow_sensitive_mount, None)
+}
+
+fn resolve_session(
+    settings: &Settings,
+    paths: &[String],
+    skip_cwd: bool,
+    allow_sensitive_mount: bool,
+    worktree: Option<&Path>,
+) -> Result<RunSession, AppError> {
     let cwd = std::env::current_dir()?;
     let home =
         dirs::home_dir().ok_or_else(|| AppError::message("unable to resolve home directory"))?;
-    let engine = settings.engine.unwrap_or_default();
-    let (workdir, mounts) = resolve_workdir_and_mounts(settings, paths, skip_cwd, &cwd, &home)?;
-    let mut args = vec!["run".to_string(), "-it".to_string()];
-    append_engine_identity_args(&mut args, engine);
-    args.push("-w".to_string());
-    args.push(workdir);
-
-    args.push("--rm".to_string());
-    append_exposed_host_port_args(
-        &mut args,
-        settings.expose_host_ports.as_deref().unwrap_or(&[]),
-    );
-
-    append_env_args(&mut args, settings.env_vars.as_deref().unwrap_or(&[]));
-    append_repeated_flag_args(
-        &mut args,
-        "--env-file",
-        settings.env_files.as_deref().unwrap_or(&[]),
-    );
-    append_repeated_flag_args(&mut args, "-p", settings.ports.as_deref().unwrap_or(&[]));
-    if let Some(args_list) = settings.run_args.as_deref() {
-        args.extend(args_list.iter().cloned());
-    }
-
-    args.extend(mounts);
+    let sensitive = if allow_sensitive_mount {
+        SensitivePaths::default()
+    } else {
+        SensitivePaths::new(settings, &cwd, &home)?
+    };
+    let workspace = worktree.unwrap_or(&cwd);
+    let (workdir, mounts) = resolve_workdir_and_mounts(
+        settings, paths, skip_cwd, &sensitive, &cwd, workspace, &home,
+    )?;
 
     let image = settings
         .image
         .as_deref()
         .filter(|value| !value.trim().is_empty())
         .unwrap_or("localhost/dungeon");
-    args.push(image.to_string());
 
-    append_command(&mut args, settings.command.as_deref());
+    Ok(RunSession {
+        name: None,
+        detach: false,
+        labels: Vec::new(),
+        workdir,
+        mounts,
+        env_vars: settings.env_vars.clone().unwrap_or_default(),
+        env_files: settings.env_files.clone().unwrap_or_default(),
+        ports: settings.ports.clone().unwrap_or_default(),
+        exposed_host_ports: settings.expose_host_ports.clone().unwrap_or_default(),
+        run_args: settings.run_args.clone().unwrap_or_default(),
+        image: image.to_string(),
+        command: session_command(settings.command.as_deref()),
+    })
+}
 
-    Ok(build_podman_command(settings, args))
+/// Creates the overlay or snapshot state the workspace mounts point at. Kept
+/// out of command building so `--debug` only prints the command.
+pub fn prepare_workspaces(
+    settings: &Settings,
+    resolved: &ResolvedConfig,
+    worktree: Option<&Path>,
+) -> Result<(), AppError> {
+    let mode = settings.workspace_mode.unwrap_or_default();
+    for source in workspace_sources(resolved, worktree, &std::env::current_dir()?) {
+        if source.is_dir() {
+            workspace::prepare(&source, mode)?;
+        }
+    }
+    Ok(())
+}
+
+fn workspace_sources(
+    resolved: &ResolvedConfig,
+    worktree: Option<&Path>,
+    cwd: &Path,
+) -> Vec<PathBuf> {
+    if !resolved.paths.is_empty() {
+        return resolved
+            .paths
+            .iter()
+            .map(|path| absolute_path(cwd, path))
+            .collect();
+    }
+    if resolved.skip_cwd {
+        return Vec::new();
+    }
+    vec![worktree.unwrap_or(cwd).to_path_buf()]
 }
 
 pub fn run_container_command(spec: CommandSpec) -> Result<(), AppError> {
@@ -160,21 +330,40 @@ fn resolve_workdir_and_mounts(
     settings: &Settings,
     paths: &[String],
     skip_cwd: bool,
+    sensitive: &SensitivePaths,
     cwd: &Path,
+    workspace: &Path,
     home: &Path,
-) -> Result<(String, Vec<String>), AppError> {
+) -> Result<(String, Vec<Mount>), AppError> {
     let mut mounts = Vec::new();
     let mut workspace_dirs = Vec::new();
 
-    for spec in settings.mounts.as_deref().unwrap_or(&[]) {
-        push_mount(&mut mounts, expand_mount_spec(spec, home));
+    for entry in settings.mounts.as_deref().unwrap_or(&[]) {
+        match entry {
+            MountEntry::Spec(spec) => {
+                let spec = expand_mount_spec(spec, home);
+                let source = spec.split(':').next().unwrap_or_default().trim();
+                if source.starts_with(['/', '.']) {
+                    sensitive.check(&absolute_path(cwd, source), &spec)?;
+                }
+                push_mount(&mut mounts, spec);
+            }
+            MountEntry::Table(table) => {
+                if let Some(spec) = resolve_mount_table(table, sensitive, cwd, home)? {
+                    push_mount(&mut mounts, spec);
+                }
+            }
+        }
     }
     for spec in settings.cache.as_deref().unwrap_or(&[]) {
-        push_mount(&mut mounts, format!("dungeon-cache:{}", spec));
+        mounts.push(Mount::Volume {
+            name: CACHE_VOLUME.to_string(),
+            spec: spec.to_string(),
+        });
     }
 
     if paths.is_empty() {
-        if !skip_cwd && same_dir(cwd, home) {
+        if !skip_cwd && same_dir(workspace, home) {
             return Err(AppError::message(
                 "ERROR: refusing to run from home directory",
             ));
@@ -183,13 +372,17 @@ fn resolve_workdir_and_mounts(
             return Ok((WORKSPACE_ROOT.to_string(), mounts));
         }
 
-        let base = cwd
+        let base = workspace
             .file_name()
             .and_then(|s| s.to_str())
             .unwrap_or("project");
         let workdir = format!("{}/{}", WORKSPACE_ROOT, base);
-        push_mount(&mut mounts, format!("{}:{}", cwd.display(), workdir));
-        workspace_dirs.push(cwd.to_path_buf());
+        sensitive.check(
+            workspace,
+            &format!("the current directory {}", workspace.display()),
+        )?;
+        push_mount(&mut mounts, workspace_mount(settings, workspace, &workdir)?);
+        workspace_dirs.push((workspace.to_path_buf(), workdir.clone()));
         append_git_metadata_mounts(&mut mounts, settings, &workspace_dirs)?;
         return Ok((workdir, mounts));
     }
@@ -197,46 +390,263 @@ fn resolve_workdir_and_mounts(
     let workdir = format!("{}/project", WORKSPACE_ROOT);
     for path in paths {
         let abs = absolute_path(cwd, path);
+        sensitive.check(&abs, path)?;
         let base = abs
             .file_name()
             .and_then(|s| s.to_str())
             .unwrap_or("project");
-        push_mount(
-            &mut mounts,
-            format!("{}:{}/{}", abs.display(), workdir, base),
-        );
+        let target = format!("{}/{}", workdir, base);
+        push_mount(&mut mounts, workspace_mount(settings, &abs, &target)?);
         if abs.is_dir() {
-            workspace_dirs.push(abs);
+            workspace_dirs.push((abs, target));
         }
     }
     append_git_metadata_mounts(&mut mounts, settings, &workspace_dirs)?;
     Ok((workdir, mounts))
 }
 
+fn workspace_mount(settings: &Settings, source: &Path, target: &str) -> Result<String, AppError> {
+    let mode = settings.workspace_mode.unwrap_or_default();
+    if matches!(mode, WorkspaceMode::Overlay | WorkspaceMode::Snapshot) && !source.is_dir() {
+        return Err(AppError::message(format!(
+            "ERROR: workspace_mode = \"{}\" needs a directory, but {} is not one",
+            mode.as_str(),
+            source.display()
+        )));
+    }
+    match mode {
+        WorkspaceMode::Rw => Ok(format!("{}:{}", source.display(), target)),
+        WorkspaceMode::Ro => Ok(format!("{}:{}:ro", source.display(), target)),
+        WorkspaceMode::Overlay => {
+            if settings.engine.unwrap_or_default() != Engine::Podman {
+                return Err(AppError::message(
+                    "ERROR: workspace_mode = \"overlay\" needs podman; use \"snapshot\" with docker",
+                ));
+            }
+            workspace::overlay_mount(source, target)
+        }
+        WorkspaceMode::Snapshot => Ok(format!(
+            "{}:{}",
+            workspace::snapshot_tree(source)?.display(),
+            target
+        )),
+    }
+}
+
+fn resolve_mount_table(
+    mount: &MountTable,
+    sensitive: &SensitivePaths,
+    cwd: &Path,
+    home: &Path,
+) -> Result<Option<String>, AppError> {
+    let target = mount.target.trim();
+    if !target.starts_with('/') {
+        return Err(AppError::message(format!(
+            "ERROR: mount target \"{}\" must be an absolute path (defined in {})",
+            target,
+            mount.defined_at()
+        )));
+    }
+    // Relative sources belong to the file that defines the mount, so a group
+    // mounts the same path wherever dungeon runs.
+    let base = match mount.file.as_deref().and_then(Path::parent) {
+        Some(dir) => dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf()),
+        None => cwd.to_path_buf(),
+    };
+    let source = absolute_path(&base, &expand_home_or_env(mount.source.trim(), home));
+    sensitive.check(
+        &source,
+        &format!(
+            "{} (defined in {})",
+            mount.source.trim(),
+            mount.defined_at()
+        ),
+    )?;
+    if !source.exists() {
+        if mount.create {
+            std::fs::create_dir_all(&source).map_err(|err| {
+                AppError::message(format!(
+                    "ERROR: create mount source {} (defined in {}): {}",
+                    source.display(),
+                    mount.defined_at(),
+                    err
+                ))
+            })?;
+        } else if mount.optional {
+            eprintln!(
+                "WARNING: skipping optional mount {}; source does not exist (defined in {})",
+                source.display(),
+                mount.defined_at()
+            );
+            return Ok(None);
+        } else {
+            return Err(AppError::message(format!(
+                "ERROR: mount source {} does not exist (defined in {}); set create = true to create it or optional = true to skip it",
+                source.display(),
+                mount.defined_at()
+            )));
+        }
+    }
+
+    let mut spec = format!("{}:{}", source.display(), target);
+    if let Some(mode) = &mount.mode {
+        spec.push(':');
+        spec.push_str(mode);
+    }
+    Ok(Some(spec))
+}
+
+#[derive(Default)]
+struct SensitivePaths {
+    denied: Vec<PathBuf>,
+    allowed: Vec<PathBuf>,
+}
+
+impl SensitivePaths {
+    fn new(settings: &Settings, cwd: &Path, home: &Path) -> Result<Self, AppError> {
+        let configured = |entries: &Option<Vec<String>>| {
+            entries
+                .iter()
+                .flatten()
+                .map(|entry| absolute_path(cwd, &expand_home_or_env(entry.trim(), home)))
+                .collect::<Vec<_>>()
+        };
+
+        let mut denied = SENSITIVE_HOME_PATHS
+            .iter()
+            .map(|path| home.join(path))
+            .collect::<Vec<_>>();
+        denied.extend(SENSITIVE_SOCKETS.iter().map(PathBuf::from));
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty())
+        {
+            let runtime_dir = PathBuf::from(runtime_dir);
+            denied.extend(
+                SENSITIVE_RUNTIME_SOCKETS
+                    .iter()
+                    .map(|socket| runtime_dir.join(socket)),
+            );
+        }
+        denied.extend(config::config_path()?.parent().map(Path::to_path_buf));
+        denied.push(config::data_dir()?);
+        denied.extend(configured(&settings.sensitive_paths));
+        let mut allowed = configured(&settings.allow_sensitive_paths);
+        allowed.push(config::data_dir()?.join(worktree::WORKTREES_DIR));
+
+        Ok(Self {
+            denied: denied.iter().map(|path| canonical_path(path)).collect(),
+            allowed: allowed.iter().map(|path| canonical_path(path)).collect(),
+        })
+    }
+
+    fn check(&self, source: &Path, mount: &str) -> Result<(), AppError> {
+        let source = canonical_path(source);
+        let allowed = |path: &Path| self.allowed.iter().any(|allowed| path.starts_with(allowed));
+        if allowed(&source) {
+            return Ok(());
+        }
+        let Some(denied) = self.denied.iter().find(|denied| {
+            (source.starts_with(denied) || denied.starts_with(&source)) && !allowed(denied)
+        }) else {
+            return Ok(());
+        };
+        let relation = if source == *denied {
+            "is the sensitive path"
+        } else if source.starts_with(denied) {
+            "is inside the sensitive path"
+        } else {
+            "contains the sensitive path"
+        };
+        Err(AppError::message(format!(
+            "ERROR: refusing to mount {}: {} {} {}; add it to allow_sensitive_paths or pass --allow-sensitive-mount",
+            mount,
+            source.display(),
+            relation,
+            denied.display()
+        )))
+    }
+}
+
 fn append_git_metadata_mounts(
-    mounts: &mut Vec<String>,
+    mounts: &mut Vec<Mount>,
     settings: &Settings,
-    workspace_dirs: &[PathBuf],
+    workspace_dirs: &[(PathBuf, String)],
 ) -> Result<(), AppError> {
     if settings.mount_git_metadata != Some(true) {
         return Ok(());
     }
 
     let mut generated = HashSet::new();
-    for workspace_dir in workspace_dirs {
-        let Some(source) = resolve_git_metadata_mount_source(workspace_dir)? else {
-            continue;
-        };
-        let spec = format!("{}:{}", source.display(), source.display());
-        if generated.insert(spec.clone()) {
-            push_mount(mounts, spec);
+    let mut sources: Vec<PathBuf> = Vec::new();
+    for (workspace_dir, target) in workspace_dirs {
+        let workspace_dir = normalize_absolute_path(workspace_dir);
+        for checkout in git_checkout_dirs(&workspace_dir) {
+            let Some((source, relative)) = resolve_git_metadata_mount_source(&checkout)? else {
+                continue;
+            };
+            if (relative && source.starts_with(&workspace_dir))
+                || sources.iter().any(|mounted| source.starts_with(mounted))
+            {
+                continue;
+            }
+            let destination = if relative {
+                container_path(&source, &workspace_dir, Path::new(target))
+            } else {
+                source.clone()
+            };
+            let spec = format!("{}:{}", source.display(), destination.display());
+            if generated.insert(spec.clone()) {
+                push_mount(mounts, spec);
+                sources.push(source);
+            }
         }
     }
 
     Ok(())
 }
 
-fn resolve_git_metadata_mount_source(workspace_dir: &Path) -> Result<Option<PathBuf>, AppError> {
+fn git_checkout_dirs(workspace_dir: &Path) -> Vec<PathBuf> {
+    let mut checkouts = Vec::new();
+    let mut pending = vec![workspace_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let modules = std::fs::read_to_string(dir.join(".gitmodules")).unwrap_or_default();
+        let mut submodules = modules
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "path").then(|| value.trim())
+            })
+            .filter(|path| {
+                !path.is_empty()
+                    && Path::new(path)
+                        .components()
+                        .all(|component| matches!(component, Component::Normal(_)))
+            })
+            .map(|path| dir.join(path))
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        submodules.reverse();
+        checkouts.push(dir);
+        pending.extend(submodules);
+    }
+    checkouts
+}
+
+fn container_path(source: &Path, workspace_dir: &Path, target: &Path) -> PathBuf {
+    let common = workspace_dir
+        .ancestors()
+        .find(|ancestor| source.starts_with(ancestor))
+        .unwrap_or(Path::new("/"));
+    let mut path = target.to_path_buf();
+    for _ in workspace_dir.strip_prefix(common).into_iter().flatten() {
+        path.push("..");
+    }
+    path.extend(source.strip_prefix(common).into_iter().flatten());
+    normalize_absolute_path(&path)
+}
+
+fn resolve_git_metadata_mount_source(
+    workspace_dir: &Path,
+) -> Result<Option<(PathBuf, bool)>, AppError> {
     let git_entry = workspace_dir.join(".git");
     if !git_entry.exists() {
         return Ok(None);
@@ -251,12 +661,12 @@ fn resolve_git_metadata_mount_source(workspace_dir: &Path) -> Result<Option<Path
         )));
     }
 
-    let git_dir = parse_gitdir_file(&git_entry)?;
-    let mount_source = resolve_git_mount_source(&git_dir)?;
-    Ok(Some(mount_source))
+    let (git_dir, relative) = parse_gitdir_file(&git_entry)?;
+    let (mount_source, absolute_common_dir) = resolve_git_mount_source(&git_dir)?;
+    Ok(Some((mount_source, relative && !absolute_common_dir)))
 }
 
-fn parse_gitdir_file(git_file: &Path) -> Result<PathBuf, AppError> {
+fn parse_gitdir_file(git_file: &Path) -> Result<(PathBuf, bool), AppError> {
     let raw = std::fs::read_to_string(git_file).map_err(|err| {
         AppError::message(format!("read git metadata {}: {}", git_file.display(), err))
     })?;
@@ -268,27 +678,24 @@ fn parse_gitdir_file(git_file: &Path) -> Result<PathBuf, AppError> {
         )));
     };
     let git_dir = PathBuf::from(path.trim());
-    if !git_dir.is_absolute() {
-        return Err(AppError::message(format!(
-            "ERROR: relative gitdir paths are unsupported in {}",
-            git_file.display()
-        )));
-    }
-
-    let git_dir = normalize_absolute_path(&git_dir);
+    let relative = !git_dir.is_absolute();
+    let git_dir = match git_file.parent() {
+        Some(parent) if relative => normalize_absolute_path(&parent.join(git_dir)),
+        _ => normalize_absolute_path(&git_dir),
+    };
     if !git_dir.exists() {
         return Err(AppError::message(format!(
             "ERROR: gitdir path does not exist: {}",
             git_dir.display()
         )));
     }
-    Ok(git_dir)
+    Ok((git_dir, relative))
 }
 
-fn resolve_git_mount_source(git_dir: &Path) -> Result<PathBuf, AppError> {
+fn resolve_git_mount_source(git_dir: &Path) -> Result<(PathBuf, bool), AppError> {
     let common_dir_file = git_dir.join("commondir");
     if !common_dir_file.exists() {
-        return Ok(git_dir.to_path_buf());
+        return Ok((git_dir.to_path_buf(), false));
     }
     if !common_dir_file.is_file() {
         return Err(AppError::message(format!(
@@ -312,7 +719,8 @@ fn resolve_git_mount_source(git_dir: &Path) -> Result<PathBuf, AppError> {
         )));
     }
 
-    let common_dir = if Path::new(value).is_absolute() {
+    let absolute = Path::new(value).is_absolute();
+    let common_dir = if absolute {
         normalize_absolute_path(Path::new(value))
     } else {
         normalize_absolute_path(&git_dir.join(value))
@@ -324,28 +732,7 @@ fn resolve_git_mount_source(git_dir: &Path) -> Result<PathBuf, AppError> {
         )));
     }
 
-    Ok(common_dir)
-}
-
-fn append_engine_identity_args(args: &mut Vec<String>, engine: Engine) {
-    match engine {
-        Engine::Podman => {
-            args.push("--userns=keep-id".to_string());
-            args.push("--user".to_string());
-            args.push("root".to_string());
-        }
-    }
-}
-
-fn append_env_args(args: &mut Vec<String>, env_specs: &[String]) {
-    for spec in env_specs {
-        let trimmed = spec.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        args.push("--env".to_string());
-        args.push(trimmed.to_string());
-    }
+    Ok((common_dir, absolute))
 }
 
 fn env_spec_has_name(spec: &str, name: &str) -> bool {
@@ -357,43 +744,19 @@ fn env_spec_has_name(spec: &str, name: &str) -> bool {
     key == name
 }
 
-fn append_repeated_flag_args(args: &mut Vec<String>, flag: &str, values: &[String]) {
-    for value in values {
-        let trimmed = value.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-        args.push(flag.to_string());
-        args.push(trimmed.to_string());
-    }
-}
-
-fn append_exposed_host_port_args(args: &mut Vec<String>, specs: &[String]) {
-    let options = specs
-        .iter()
-        .map(|spec| spec.trim())
-        .filter(|spec| !spec.is_empty())
-        .flat_map(|spec| ["-T", spec])
-        .collect::<Vec<_>>();
-
-    if !options.is_empty() {
-        args.push(format!("--network=pasta:{}", options.join(",")));
-    }
-}
-
-fn append_command(args: &mut Vec<String>, command: Option<&str>) {
-    args.push("zsh".to_string());
+fn session_command(command: Option<&str>) -> Vec<String> {
+    let mut args = vec!["zsh".to_string()];
     if let Some(command) = command
         && !command.trim().is_empty()
     {
         args.push("-ic".to_string());
         args.push(command.to_string());
     }
+    args
 }
 
-fn push_mount(mounts: &mut Vec<String>, spec: String) {
-    mounts.push("-v".to_string());
-    mounts.push(spec);
+fn push_mount(mounts: &mut Vec<Mount>, spec: String) {
+    mounts.push(Mount::Bind(spec));
 }
 
 fn absolute_path(cwd: &Path, path: &str) -> PathBuf {
@@ -421,6 +784,20 @@ fn normalize_absolute_path(path: &Path) -> PathBuf {
     normalized
 }
 
+fn canonical_path(path: &Path) -> PathBuf {
+    let path = normalize_absolute_path(path);
+    for ancestor in path.ancestors() {
+        let Ok(canonical) = ancestor.canonicalize() else {
+            continue;
+        };
+        return match path.strip_prefix(ancestor) {
+            Ok(rest) if !rest.as_os_str().is_empty() => canonical.join(rest),
+            _ => canonical,
+        };
+    }
+    path
+}
+
 fn same_dir(a: &Path, b: &Path) -> bool {
     a.canonicalize().ok() == b.canonicalize().ok()
 }
@@ -1,4 +1,8 @@
+pub mod backend;
 pub mod engine;
+pub mod ps;
+pub mod workspace;
+pub mod worktree;
 
 use std::process::{Command, Stdio};
 
@@ -23,3 +27,23 @@ pub(crate) fn run_attached_command(program: &str, args: &[String]) -> Result<(),
         Err(err) => Err(AppError::Io(err)),
     }
 }
+
+pub(crate) fn run_captured_command(program: &str, args: &[String]) -> Result<String, AppError> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(AppError::Io)?;
+
+    if !output.status.success() {
+        let code = output.status.code().unwrap_or(1);
+        return Err(AppError::Subprocess(
+            code,
+            format!("{} exited with code {}", program, code),
+        ));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|err| AppError::message(format!("{} produced invalid UTF-8: {}", program, err)))
+}
@@ -0,0 +1,83 @@
+use crate::{config::Settings, error::AppError, table::render_columns};
+
+use super::{
+    backend::{ContainerSummary, MANAGED_LABEL, SESSION_LABEL, engine_backend},
+    engine::{CommandSpec, build_engine_command, session_container_name},
+};
+
+pub fn build_list_command(settings: &Settings, all: bool) -> CommandSpec {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(
+        engine,
+        settings,
+        engine.list_containers_args(&format!("{}=true", MANAGED_LABEL), all),
+    )
+}
+
+pub fn build_session_lookup_command(settings: &Settings, session: &str) -> CommandSpec {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    build_engine_command(
+        engine,
+        settings,
+        engine.list_containers_args(&format!("{}={}", SESSION_LABEL, session), true),
+    )
+}
+
+pub fn list_containers(settings: &Settings, all: bool) -> Result<Vec<ContainerSummary>, AppError> {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    let spec = build_list_command(settings, all);
+    let output = super::run_captured_command(&spec.program, &spec.args)?;
+    engine.parse_container_list(&output)
+}
+
+/// Makes sure the container named after `session` was started by dungeon for
+/// that session, so attach, exec, stop and rm never touch a container that
+/// only happens to share the name.
+pub fn check_session_container(settings: &Settings, session: &str) -> Result<(), AppError> {
+    let engine = engine_backend(settings.engine.unwrap_or_default());
+    let spec = build_session_lookup_command(settings, session);
+    let output = super::run_captured_command(&spec.program, &spec.args)?;
+    find_session_container(&engine.parse_container_list(&output)?, session).map(|_| ())
+}
+
+pub fn find_session_container<'a>(
+    containers: &'a [ContainerSummary],
+    session: &str,
+) -> Result<&'a ContainerSummary, AppError> {
+    let name = session_container_name(session);
+    containers
+        .iter()
+        .find(|container| container.name == name && container.session.as_deref() == Some(session))
+        .ok_or_else(|| {
+            AppError::message(format!(
+                "ERROR: no dungeon session \"{}\"; `dungeon ps --all` lists them",
+                session
+            ))
+        })
+}
+
+pub fn render_json(containers: &[ContainerSummary]) -> Result<String, AppError> {
+    serde_json::to_string_pretty(containers)
+        .map_err(|err| AppError::message(format!("render containers: {}", err)))
+}
+
+pub fn render_table(containers: &[ContainerSummary]) -> String {
+    let header = ["NAME", "SESSION", "STATUS", "WORKSPACE", "GROUPS", "IMAGE"].map(String::from);
+    let mut rows = vec![header];
+    for container in containers {
+        rows.push([
+            container.name.clone(),
+            container.session.clone().unwrap_or_else(|| "-".to_string()),
+            container.status.clone(),
+            container.workspace.clone(),
+            if container.groups.is_empty() {
+                "-".to_string()
+            } else {
+                container.groups.join(",")
+            },
+            container.image.clone(),
+        ]);
+    }
+
+    render_columns(&rows)
+}
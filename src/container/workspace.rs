@@ -0,0 +1,436 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::ErrorKind,
+    os::unix::fs::{FileTypeExt, MetadataExt},
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{self, WorkspaceMode},
+    error::AppError,
+};
+
+const WORKSPACES_DIR: &str = "workspaces";
+const SOURCE_FILE: &str = "source";
+const UPPER_DIR: &str = "upper";
+const WORK_DIR: &str = "work";
+const TREE_DIR: &str = "tree";
+const MANIFEST_FILE: &str = "manifest.json";
+const DIR_DIGEST_SUFFIX: &str = "dir";
+const WHITEOUT_PREFIX: &str = ".wh.";
+const OPAQUE_MARKER: &str = ".wh..wh..opq";
+const OPAQUE_XATTRS: &[&str] = &["trusted.overlay.opaque", "user.overlay.opaque"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceChange {
+    pub kind: ChangeKind,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for WorkspaceChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.kind {
+            ChangeKind::Added => 'A',
+            ChangeKind::Modified => 'M',
+            ChangeKind::Deleted => 'D',
+        };
+        write!(f, "{} {}", marker, self.path.display())
+    }
+}
+
+enum State {
+    Overlay { upper: PathBuf },
+    Snapshot { tree: PathBuf, manifest: PathBuf },
+}
+
+pub fn overlay_mount(workspace: &Path, target: &str) -> Result<String, AppError> {
+    let dir = checked_state_dir(workspace, WorkspaceMode::Overlay)?;
+    Ok(format!(
+        "{}:{}:O,upperdir={},workdir={}",
+        workspace.display(),
+        target,
+        dir.join(UPPER_DIR).display(),
+        dir.join(WORK_DIR).display()
+    ))
+}
+
+/// The snapshot is a host directory rather than a named volume: volumes live in
+/// the engine's storage (inside the VM for podman machine and Docker Desktop,
+/// root-owned for rootful Docker), so `diff` and `apply` could not read them
+/// without starting a container.
+pub fn snapshot_tree(workspace: &Path) -> Result<PathBuf, AppError> {
+    Ok(checked_state_dir(workspace, WorkspaceMode::Snapshot)?.join(TREE_DIR))
+}
+
+/// Creates the upper directory or the snapshot copy that `overlay_mount` and
+/// `snapshot_tree` point at. An existing snapshot is kept as it is.
+pub fn prepare(workspace: &Path, mode: WorkspaceMode) -> Result<(), AppError> {
+    if !matches!(mode, WorkspaceMode::Overlay | WorkspaceMode::Snapshot) {
+        return Ok(());
+    }
+    let dir = checked_state_dir(workspace, mode)?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(SOURCE_FILE), workspace.display().to_string())?;
+    if mode == WorkspaceMode::Overlay {
+        fs::create_dir_all(dir.join(UPPER_DIR))?;
+        fs::create_dir_all(dir.join(WORK_DIR))?;
+        return Ok(());
+    }
+
+    let tree = dir.join(TREE_DIR);
+    let manifest = dir.join(MANIFEST_FILE);
+    if manifest.is_file() {
+        return Ok(());
+    }
+    if tree.exists() {
+        fs::remove_dir_all(&tree)?;
+    }
+    copy_tree(workspace, &tree)?;
+    let digests = collect_digests(&tree)?;
+    let data = serde_json::to_string_pretty(&digests)
+        .map_err(|err| AppError::message(format!("render snapshot manifest: {}", err)))?;
+    fs::write(&manifest, data)?;
+    Ok(())
+}
+
+pub fn changes(workspace: &Path) -> Result<Vec<WorkspaceChange>, AppError> {
+    let (_, state) = load_state(workspace)?;
+    state_changes(workspace, &state)
+}
+
+pub fn apply(workspace: &Path) -> Result<Vec<WorkspaceChange>, AppError> {
+    let (dir, state) = load_state(workspace)?;
+    let changes = state_changes(workspace, &state)?;
+    let source = match &state {
+        State::Overlay { upper } => upper,
+        State::Snapshot { tree, manifest } => {
+            check_snapshot_conflicts(workspace, tree, manifest, &changes)?;
+            tree
+        }
+    };
+    for change in &changes {
+        let host = workspace.join(&change.path);
+        match change.kind {
+            ChangeKind::Deleted => remove_entry(&host)?,
+            ChangeKind::Added | ChangeKind::Modified => {
+                apply_entry(&source.join(&change.path), &host)?
+            }
+        }
+    }
+    fs::remove_dir_all(&dir)?;
+    Ok(changes)
+}
+
+pub fn discard(workspace: &Path) -> Result<(), AppError> {
+    let (dir, _) = load_state(workspace)?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+fn state_dir(workspace: &Path) -> Result<PathBuf, AppError> {
+    Ok(config::data_dir()?
+        .join(WORKSPACES_DIR)
+        .join(config::path_key(workspace)))
+}
+
+fn checked_state_dir(workspace: &Path, mode: WorkspaceMode) -> Result<PathBuf, AppError> {
+    let dir = state_dir(workspace)?;
+    let other = match mode {
+        WorkspaceMode::Overlay => TREE_DIR,
+        _ => UPPER_DIR,
+    };
+    if dir.join(other).exists() {
+        return Err(AppError::message(format!(
+            "ERROR: {} has pending changes from another workspace_mode; run `dungeon diff` and `dungeon apply` (or `dungeon apply --discard`) before switching to {}",
+            workspace.display(),
+            mode.as_str()
+        )));
+    }
+    Ok(dir)
+}
+
+fn load_state(workspace: &Path) -> Result<(PathBuf, State), AppError> {
+    let dir = state_dir(workspace)?;
+    let upper = dir.join(UPPER_DIR);
+    let tree = dir.join(TREE_DIR);
+    let manifest = dir.join(MANIFEST_FILE);
+    let state = if upper.is_dir() {
+        State::Overlay { upper }
+    } else if tree.is_dir() && manifest.is_file() {
+        State::Snapshot { tree, manifest }
+    } else {
+        return Err(AppError::message(format!(
+            "ERROR: no overlay or snapshot workspace for {}; run with workspace_mode = \"overlay\" or \"snapshot\" first",
+            workspace.display()
+        )));
+    };
+    Ok((dir, state))
+}
+
+fn state_changes(workspace: &Path, state: &State) -> Result<Vec<WorkspaceChange>, AppError> {
+    let mut changes = Vec::new();
+    match state {
+        State::Overlay { upper } => overlay_changes(upper, workspace, Path::new(""), &mut changes)?,
+        State::Snapshot { tree, manifest } => {
+            let baseline = read_manifest(manifest)?;
+            let current = collect_digests(tree)?;
+            for (path, digest) in &current {
+                let kind = match baseline.get(path) {
+                    None => ChangeKind::Added,
+                    Some(base) if base != digest => ChangeKind::Modified,
+                    Some(_) => continue,
+                };
+                changes.push(WorkspaceChange {
+                    kind,
+                    path: path.clone(),
+                });
+            }
+            for path in baseline.keys() {
+                if !current.contains_key(path) {
+                    changes.push(WorkspaceChange {
+                        kind: ChangeKind::Deleted,
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+fn overlay_changes(
+    upper: &Path,
+    lower: &Path,
+    rel: &Path,
+    changes: &mut Vec<WorkspaceChange>,
+) -> Result<(), AppError> {
+    let dir = upper.join(rel);
+    let names = sorted_names(&dir)?;
+    if is_opaque(&dir, &names) {
+        for name in sorted_names(&lower.join(rel)).unwrap_or_default() {
+            if !names.contains(&name) {
+                changes.push(WorkspaceChange {
+                    kind: ChangeKind::Deleted,
+                    path: rel.join(name),
+                });
+            }
+        }
+    }
+
+    for name in names {
+        if name == OPAQUE_MARKER {
+            continue;
+        }
+        if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+            changes.push(WorkspaceChange {
+                kind: ChangeKind::Deleted,
+                path: rel.join(hidden),
+            });
+            continue;
+        }
+        let path = rel.join(&name);
+        let meta = fs::symlink_metadata(upper.join(&path))?;
+        if meta.file_type().is_char_device() && meta.rdev() == 0 {
+            changes.push(WorkspaceChange {
+                kind: ChangeKind::Deleted,
+                path,
+            });
+            continue;
+        }
+        let host = fs::symlink_metadata(lower.join(&path)).ok();
+        if meta.is_dir() {
+            let kind = match &host {
+                None => Some(ChangeKind::Added),
+                Some(host) if !host.is_dir() => Some(ChangeKind::Modified),
+                Some(_) => None,
+            };
+            if let Some(kind) = kind {
+                changes.push(WorkspaceChange {
+                    kind,
+                    path: path.clone(),
+                });
+            }
+            overlay_changes(upper, lower, &path, changes)?;
+            continue;
+        }
+        let kind = match host {
+            None => ChangeKind::Added,
+            Some(host) if host.is_dir() => ChangeKind::Modified,
+            Some(_) if entry_digest(&upper.join(&path))? == entry_digest(&lower.join(&path))? => {
+                continue;
+            }
+            Some(_) => ChangeKind::Modified,
+        };
+        changes.push(WorkspaceChange { kind, path });
+    }
+    Ok(())
+}
+
+/// Overlayfs marks a directory that hides the lower one with a marker file
+/// (fuse-overlayfs) or an xattr (kernel overlay, rootful or userxattr).
+fn is_opaque(dir: &Path, names: &[String]) -> bool {
+    names.iter().any(|name| name == OPAQUE_MARKER)
+        || OPAQUE_XATTRS.iter().any(|name| {
+            xattr::get(dir, name).is_ok_and(|value| value.as_deref() == Some(b"y".as_slice()))
+        })
+}
+
+fn check_snapshot_conflicts(
+    workspace: &Path,
+    tree: &Path,
+    manifest: &Path,
+    changes: &[WorkspaceChange],
+) -> Result<(), AppError> {
+    let baseline = read_manifest(manifest)?;
+    let conflicts = changes
+        .iter()
+        .filter(|change| {
+            let host = entry_digest(&workspace.join(&change.path)).ok().flatten();
+            let snapshot = entry_digest(&tree.join(&change.path)).ok().flatten();
+            (host.as_ref() != baseline.get(&change.path) && host != snapshot)
+                || (change.kind == ChangeKind::Deleted
+                    && has_new_entries(&workspace.join(&change.path), &change.path, &baseline))
+        })
+        .map(|change| change.path.display().to_string())
+        .collect::<Vec<_>>();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::message(format!(
+        "ERROR: {} changed on the host since the snapshot was taken: {}; resolve them or run `dungeon apply --discard`",
+        workspace.display(),
+        conflicts.join(", ")
+    )))
+}
+
+fn has_new_entries(dir: &Path, rel: &Path, baseline: &BTreeMap<PathBuf, String>) -> bool {
+    if !fs::symlink_metadata(dir).is_ok_and(|meta| meta.is_dir()) {
+        return false;
+    }
+    collect_digests(dir).map_or(true, |entries| {
+        entries
+            .keys()
+            .any(|path| !baseline.contains_key(&rel.join(path)))
+    })
+}
+
+fn read_manifest(path: &Path) -> Result<BTreeMap<PathBuf, String>, AppError> {
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(|err| {
+        AppError::message(format!(
+            "ERROR: malformed snapshot manifest {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+fn collect_digests(root: &Path) -> Result<BTreeMap<PathBuf, String>, AppError> {
+    let mut digests = BTreeMap::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(rel) = pending.pop() {
+        for name in sorted_names(&root.join(&rel))? {
+            let path = rel.join(name);
+            if let Some(digest) = entry_digest(&root.join(&path))? {
+                if digest.ends_with(DIR_DIGEST_SUFFIX) {
+                    pending.push(path.clone());
+                }
+                digests.insert(path, digest);
+            }
+        }
+    }
+    Ok(digests)
+}
+
+fn entry_digest(path: &Path) -> Result<Option<String>, AppError> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    if meta.is_symlink() {
+        return Ok(Some(format!("-> {}", fs::read_link(path)?.display())));
+    }
+    if meta.is_dir() {
+        return Ok(Some(format!(
+            "{:o} {}",
+            meta.mode() & 0o777,
+            DIR_DIGEST_SUFFIX
+        )));
+    }
+    let digest = config::hex(&Sha256::digest(fs::read(path)?));
+    Ok(Some(format!("{:o} {}", meta.mode() & 0o777, digest)))
+}
+
+fn sorted_names(dir: &Path) -> Result<Vec<String>, AppError> {
+    let mut names = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    names.sort();
+    Ok(names)
+}
+
+fn copy_tree(source: &Path, target: &Path) -> Result<(), AppError> {
+    fs::create_dir_all(target)?;
+    for name in sorted_names(source)? {
+        let from = source.join(&name);
+        if fs::symlink_metadata(&from)?.is_dir() {
+            copy_tree(&from, &target.join(&name))?;
+        } else {
+            copy_entry(&from, &target.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_entry(source: &Path, target: &Path) -> Result<(), AppError> {
+    remove_entry(target)?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let meta = fs::symlink_metadata(source)?;
+    if meta.is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(source)?, target)?;
+    } else if meta.is_dir() {
+        copy_tree(source, target)?;
+    } else {
+        fs::copy(source, target)?;
+    }
+    Ok(())
+}
+
+/// Directories are created or kept rather than copied, since the entries in
+/// them are listed as changes of their own.
+fn apply_entry(source: &Path, host: &Path) -> Result<(), AppError> {
+    let meta = fs::symlink_metadata(source)?;
+    if !meta.is_dir() {
+        return copy_entry(source, host);
+    }
+    if !fs::symlink_metadata(host).is_ok_and(|host| host.is_dir()) {
+        remove_entry(host)?;
+        fs::create_dir_all(host)?;
+    }
+    fs::set_permissions(host, meta.permissions())?;
+    Ok(())
+}
+
+fn remove_entry(path: &Path) -> Result<(), AppError> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path)?,
+        Ok(_) => fs::remove_file(path)?,
+        Err(err) if matches!(err.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => {}
+        Err(err) => return Err(err.into()),
+    }
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::{
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{config, error::AppError};
+
+use super::backend::ContainerSummary;
+
+pub(crate) const WORKTREES_DIR: &str = "worktrees";
+const BRANCH_PREFIX: &str = "dungeon/";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worktree {
+    pub repo: PathBuf,
+    pub path: PathBuf,
+    pub branch: String,
+}
+
+impl Worktree {
+    pub fn plan(cwd: &Path, branch: &str, session: Option<&str>) -> Result<Self, AppError> {
+        let repo = git(cwd, &["rev-parse", "--show-toplevel"]).map_err(|_| {
+            AppError::message(format!(
+                "ERROR: --worktree needs a git repository, but {} is not inside one",
+                cwd.display()
+            ))
+        })?;
+        let repo = PathBuf::from(repo);
+        let branch = match (branch.trim(), session) {
+            ("", Some(session)) => format!("{}{}", BRANCH_PREFIX, session),
+            ("", None) => {
+                let seconds = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs())
+                    .unwrap_or_default();
+                format!("{}{}-{}", BRANCH_PREFIX, seconds, std::process::id())
+            }
+            (branch, _) => branch.to_string(),
+        };
+        git(&repo, &["check-ref-format", "--branch", &branch]).map_err(|_| {
+            AppError::message(format!(
+                "ERROR: invalid worktree branch name \"{}\"",
+                branch
+            ))
+        })?;
+
+        let name = repo
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("project")
+            .to_string();
+        let path = config::data_dir()?
+            .join(WORKTREES_DIR)
+            .join(format!("{}-{}", name, &config::path_key(&repo)[..12]))
+            .join(branch_dir(&branch))
+            .join(name);
+        Ok(Self { repo, path, branch })
+    }
+
+    /// Checks out the worktree, or reuses it when an earlier session left it
+    /// behind. `running` lists the running dungeon containers and is only
+    /// called for an existing worktree, which is refused while one of them
+    /// still has it mounted.
+    pub fn create(
+        &self,
+        running: impl FnOnce() -> Result<Vec<ContainerSummary>, AppError>,
+    ) -> Result<(), AppError> {
+        if self.path.join(".git").is_file() {
+            let workspace = self.path.display().to_string();
+            if let Some(container) = running()?
+                .iter()
+                .find(|container| container.workspace == workspace)
+            {
+                return Err(AppError::message(format!(
+                    "ERROR: worktree {} on branch {} is in use by {}; stop it or pick another branch",
+                    workspace,
+                    self.branch,
+                    container
+                        .session
+                        .as_ref()
+                        .map(|session| format!("session \"{}\"", session))
+                        .unwrap_or_else(|| format!("container {}", container.name))
+                )));
+            }
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let path = self.path.display().to_string();
+        let branch_ref = format!("refs/heads/{}", self.branch);
+        if git(
+            &self.repo,
+            &["rev-parse", "--verify", "--quiet", &branch_ref],
+        )
+        .is_ok()
+        {
+            git(&self.repo, &["worktree", "add", &path, &self.branch])?;
+        } else {
+            git(&self.repo, &["worktree", "add", "-b", &self.branch, &path])?;
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, delete_branch: bool) -> Result<(), AppError> {
+        let path = self.path.display().to_string();
+        git(&self.repo, &["worktree", "remove", &path])?;
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
+        if delete_branch {
+            git(&self.repo, &["branch", "-d", &self.branch])?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&self, detached: bool) -> Result<(), AppError> {
+        if detached || !std::io::stdin().is_terminal() {
+            println!(
+                "kept worktree {} on branch {}; remove it with `git worktree remove {}`",
+                self.path.display(),
+                self.branch,
+                self.path.display()
+            );
+            return Ok(());
+        }
+
+        print!(
+            "worktree {} on branch {}: [k]eep it, [r]emove it and keep the branch, or remove it and [d]elete the branch? [k] ",
+            self.path.display(),
+            self.branch
+        );
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "r" | "remove" => {
+                self.remove(false)?;
+                println!("removed worktree; kept branch {}", self.branch);
+            }
+            "d" | "delete" => {
+                self.remove(true)?;
+                println!("removed worktree and branch {}", self.branch);
+            }
+            _ => println!("kept worktree {}", self.path.display()),
+        }
+        Ok(())
+    }
+}
+
+/// Names the directory of a branch's worktree. The hash keeps branches that
+/// only differ in `/` and `-`, such as `feat/x` and `feat-x`, apart.
+fn branch_dir(branch: &str) -> String {
+    let digest = config::hex(&Sha256::digest(branch.as_bytes()));
+    format!("{}-{}", branch.replace('/', "-"), &digest[..8])
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, AppError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|err| AppError::message(format!("ERROR: failed to start git: {}", err)))?;
+    if !output.status.success() {
+        return Err(AppError::message(format!(
+            "ERROR: git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -3,6 +3,7 @@ pub mod cli;
 pub mod config;
 pub mod container;
 pub mod error;
+mod table;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,157 @@
+use crate::{
+    config::GroupReason,
+    tests::support::{TestInput, explain_input, resolve_input, try_resolve_input},
+};
+
+const RUST_GROUP: &str = r#"
+[rust]
+when.file_exists = "Cargo.toml"
+caches = ["/home/dungeon/.cargo/registry"]
+"#;
+
+#[test]
+fn file_exists_condition_enables_group_from_repository_subdirectory() {
+    let input = TestInput {
+        toml: RUST_GROUP,
+        args: &["run"],
+        env: &[],
+        cwd_name: "crate/src",
+        cwd_entries: &[],
+        fs_entries: &[("crate/.git", None), ("crate/Cargo.toml", Some(""))],
+    };
+
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(resolved.groups, vec!["rust".to_string()]);
+    assert_eq!(
+        resolved.settings.cache,
+        Some(vec!["/home/dungeon/.cargo/registry".to_string()])
+    );
+}
+
+#[test]
+fn unmatched_condition_leaves_group_disabled() {
+    let input = TestInput {
+        toml: RUST_GROUP,
+        args: &["run"],
+        env: &[],
+        cwd_name: "python-project",
+        cwd_entries: &["pyproject.toml"],
+        fs_entries: &[],
+    };
+
+    assert!(resolve_input(input).resolved.groups.is_empty());
+}
+
+#[test]
+fn path_and_git_remote_conditions_must_all_match() {
+    let toml = r#"
+[acme]
+when.path = "~/work/**"
+when.git_remote = ["*github.com:acme/*", "*github.com/acme/*"]
+envs = ["ACME=1"]
+"#;
+    let git_config =
+        "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = git@github.com:acme/tool.git\n";
+
+    let matching = TestInput {
+        toml,
+        args: &["run"],
+        env: &[],
+        cwd_name: "home/work/tool",
+        cwd_entries: &[],
+        fs_entries: &[("home/work/tool/.git/config", Some(git_config))],
+    };
+    assert_eq!(resolve_input(matching).resolved.groups, vec!["acme"]);
+
+    let outside_path = TestInput {
+        toml,
+        args: &["run"],
+        env: &[],
+        cwd_name: "elsewhere/tool",
+        cwd_entries: &[],
+        fs_entries: &[("elsewhere/tool/.git/config", Some(git_config))],
+    };
+    assert!(resolve_input(outside_path).resolved.groups.is_empty());
+}
+
+#[test]
+fn conditions_are_evaluated_against_mounted_paths() {
+    let input = TestInput {
+        toml: RUST_GROUP,
+        args: &["run", "../crate"],
+        env: &[],
+        cwd_name: "workspace",
+        cwd_entries: &[],
+        fs_entries: &[("crate/Cargo.toml", Some(""))],
+    };
+
+    assert_eq!(resolve_input(input).resolved.groups, vec!["rust"]);
+}
+
+#[test]
+fn explain_groups_reports_why_each_group_is_enabled() {
+    let toml = r#"
+[general]
+include_groups = ["base"]
+
+[base]
+envs = ["BASE=1"]
+
+[tools]
+envs = ["TOOLS=1"]
+
+[rust]
+when.file_exists = "Cargo.toml"
+include_groups = ["tools"]
+
+[extra]
+envs = ["EXTRA=1"]
+"#;
+    let input = TestInput {
+        toml,
+        args: &["run", "--extra", "--explain-groups"],
+        env: &[],
+        cwd_name: "explained",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+
+    let explanations = explain_input(input);
+    let names = explanations
+        .iter()
+        .map(|explanation| explanation.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["base", "tools", "rust", "extra"]);
+
+    assert!(
+        matches!(&explanations[0].reason, GroupReason::Included(source) if source.ends_with("config.toml"))
+    );
+    assert_eq!(explanations[1].to_string(), "tools: included by group rust");
+    let rust = explanations[2].to_string();
+    assert!(
+        rust.starts_with("rust: when.file_exists = \"Cargo.toml\" matched ")
+            && rust.ends_with("/explained"),
+        "{rust}"
+    );
+    assert_eq!(explanations[3].to_string(), "extra: cli --extra");
+}
+
+#[test]
+fn errors_on_unknown_condition_keys() {
+    let input = TestInput {
+        toml: "[rust]\nwhen.branch = \"main\"\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "unknown-condition",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_resolve_input(input).expect_err("unknown condition key");
+    assert!(
+        err.to_string().contains(
+            "rust.when has unknown key \"branch\"; expected one of: path, file_exists, git_remote"
+        ),
+        "{err}"
+    );
+}
@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use crate::{
+    cli,
+    config::{self, ConfigEdit},
+};
+
+const CONFIG: &str = r#"# personal setup
+[general]
+image = "localhost/dungeon" # pinned
+mounts = [
+  "~/notes:/home/dungeon/notes:ro",
+  "~/docs:/home/dungeon/docs:ro",
+]
+
+[codex]
+mounts = ["~/.codex:/home/dungeon/.codex:rw"]
+"#;
+
+fn write_config(dir: &tempfile::TempDir) -> PathBuf {
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, CONFIG).expect("write config");
+    path
+}
+
+fn set(values: &[&str]) -> ConfigEdit {
+    ConfigEdit::Set(values.iter().map(|value| value.to_string()).collect())
+}
+
+fn append(values: &[&str]) -> ConfigEdit {
+    ConfigEdit::Append(values.iter().map(|value| value.to_string()).collect())
+}
+
+#[test]
+fn set_and_append_preserve_comments_and_layout() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir);
+
+    config::edit_config_file(&path, "general.image", set(&["localhost/other"])).expect("set");
+    config::edit_config_file(
+        &path,
+        "general.mounts",
+        append(&["~/src:/home/dungeon/src:rw"]),
+    )
+    .expect("append multi-line");
+    config::edit_config_file(&path, "codex.mounts", append(&["~/.cache:/cache:ro"]))
+        .expect("append inline");
+
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read config"),
+        r#"# personal setup
+[general]
+image = "localhost/other" # pinned
+mounts = [
+  "~/notes:/home/dungeon/notes:ro",
+  "~/docs:/home/dungeon/docs:ro",
+  "~/src:/home/dungeon/src:rw",
+]
+
+[codex]
+mounts = ["~/.codex:/home/dungeon/.codex:rw", "~/.cache:/cache:ro"]
+"#
+    );
+}
+
+#[test]
+fn set_creates_missing_tables_and_get_reads_values() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir);
+
+    config::edit_config_file(&path, "profile.work.podman_args", set(&["-c", "agent-vm"]))
+        .expect("set profile list");
+    config::edit_config_file(&path, "general.mount_git_metadata", set(&["yes"])).expect("set flag");
+
+    let data = std::fs::read_to_string(&path).expect("read config");
+    assert!(data.ends_with("\n[profile.work]\npodman_args = [\"-c\", \"agent-vm\"]\n"));
+    assert!(!data.contains("[profile]\n"));
+    assert_eq!(
+        config::get_config_value(&path, "profile.work.podman_args").expect("get list"),
+        "-c\nagent-vm"
+    );
+    assert_eq!(
+        config::get_config_value(&path, "general.mount_git_metadata").expect("get flag"),
+        "true"
+    );
+    assert_eq!(
+        config::get_config_value(&path, "general.image").expect("get scalar"),
+        "localhost/dungeon"
+    );
+}
+
+#[test]
+fn unset_removes_the_key_and_empty_tables() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir);
+
+    config::edit_config_file(&path, "codex.mounts", ConfigEdit::Unset).expect("unset");
+
+    let data = std::fs::read_to_string(&path).expect("read config");
+    assert!(!data.contains("[codex]"), "{data}");
+    let err = config::get_config_value(&path, "codex.mounts").expect_err("not set");
+    assert!(
+        err.to_string()
+            .starts_with("ERROR: codex.mounts is not set in "),
+        "{err}"
+    );
+    let err = config::edit_config_file(&path, "codex.mounts", ConfigEdit::Unset)
+        .expect_err("unset twice");
+    assert!(err.to_string().contains("is not set"), "{err}");
+}
+
+#[test]
+fn rejects_invalid_values_without_writing() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir);
+
+    let cases = [
+        (
+            "general.nope",
+            set(&["1"]),
+            "ERROR: general has unknown key \"nope\"",
+        ),
+        (
+            "general.mount_git_metadata",
+            set(&["maybe"]),
+            "general.mount_git_metadata must be a boolean",
+        ),
+        (
+            "general.dynamic_ports",
+            append(&["Docs"]),
+            "general.dynamic_ports entries must be lower-case ASCII identifiers ([a-z][a-z0-9_]*)",
+        ),
+        (
+            "general.expose_host_ports",
+            set(&["http"]),
+            "ERROR: invalid exposed host port specification \"http\"; expected PORT, PORT:HOST_PORT, RANGE, or RANGE:HOST_RANGE",
+        ),
+        (
+            "general.image",
+            append(&["x"]),
+            "ERROR: --append only applies to list settings; general.image takes a single value",
+        ),
+        (
+            "image",
+            set(&["x"]),
+            "ERROR: config key \"image\" must be <table>.<key>, for example general.image, codex.mounts or profile.work.image",
+        ),
+    ];
+    for (key, edit, expected) in cases {
+        let err = config::edit_config_file(&path, key, edit).expect_err(key);
+        assert_eq!(err.to_string(), expected);
+    }
+    assert_eq!(std::fs::read_to_string(&path).expect("read config"), CONFIG);
+}
+
+#[test]
+fn config_set_parses_append_and_unset() {
+    let defaults = config::Config::default();
+    let args = ["config", "set", "codex.mounts", "--append", "~/a:/a:ro"]
+        .map(String::from)
+        .to_vec();
+    let parsed =
+        cli::parse_args_with_sources(args, &defaults, &defaults, &defaults).expect("parse set");
+    let cli::Action::ConfigSet(action) = parsed.action else {
+        panic!("expected config set");
+    };
+    assert_eq!(action.key, "codex.mounts");
+    assert_eq!(action.edit, append(&["~/a:/a:ro"]));
+
+    let args = ["config", "unset", "general.image"]
+        .map(String::from)
+        .to_vec();
+    let parsed =
+        cli::parse_args_with_sources(args, &defaults, &defaults, &defaults).expect("parse unset");
+    let cli::Action::ConfigSet(action) = parsed.action else {
+        panic!("expected config unset");
+    };
+    assert_eq!(action.edit, ConfigEdit::Unset);
+}
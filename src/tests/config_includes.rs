@@ -0,0 +1,132 @@
+use crate::tests::support::{TestInput, groups_output, resolve_input, try_resolve_input};
+
+#[test]
+fn fragments_load_in_lexical_order_before_config_toml() {
+    let input = TestInput {
+        toml: "[general]\nimage = \"localhost/main\"\ninclude_groups = [\"ai\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "fragments",
+        cwd_entries: &[],
+        fs_entries: &[
+            (
+                "config/dungeon/config.d/20-ai.toml",
+                Some("[ai]\nenvs = [\"AI=second\"]\n"),
+            ),
+            (
+                "config/dungeon/config.d/10-ai.toml",
+                Some("[general]\nimage = \"localhost/fragment\"\n\n[ai]\nenvs = [\"AI=first\"]\n"),
+            ),
+            ("config/dungeon/config.d/notes.txt", Some("not toml")),
+        ],
+    };
+
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(resolved.groups, vec!["ai"]);
+    assert_eq!(resolved.settings.image.as_deref(), Some("localhost/main"));
+    assert_eq!(
+        resolved.settings.env_vars,
+        Some(vec!["AI=second".to_string()])
+    );
+}
+
+#[test]
+fn include_globs_resolve_relative_to_the_including_file() {
+    let input = TestInput {
+        toml: "include = [\"groups/*.toml\"]\n\n[general]\ninclude_groups = [\"rust\"]\n",
+        args: &["groups", "list"],
+        env: &[],
+        cwd_name: "includes",
+        cwd_entries: &[],
+        fs_entries: &[
+            (
+                "config/dungeon/groups/rust.toml",
+                Some(
+                    "include = [\"../shared/tools.toml\"]\n\n[rust]\ndescription = \"Rust toolchain caches\"\n",
+                ),
+            ),
+            (
+                "config/dungeon/shared/tools.toml",
+                Some("[tools]\nenvs = [\"TOOLS=1\"]\n"),
+            ),
+        ],
+    };
+
+    let output = groups_output(input);
+    let rust = output
+        .lines()
+        .find(|line| line.starts_with("rust "))
+        .expect("rust");
+    assert!(
+        rust.contains("Rust toolchain caches") && rust.ends_with("groups/rust.toml"),
+        "{rust}"
+    );
+    let tools = output
+        .lines()
+        .find(|line| line.starts_with("tools "))
+        .expect("tools");
+    assert!(tools.ends_with("shared/tools.toml"), "{tools}");
+}
+
+#[test]
+fn errors_name_the_fragment_that_caused_them() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "broken-fragment",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "config/dungeon/config.d/50-team.toml",
+            Some("[team]\nunknown = true\n"),
+        )],
+    };
+
+    let err = try_resolve_input(input).expect_err("broken fragment");
+    let message = err.to_string();
+    assert!(
+        message.starts_with("parse config ")
+            && message.contains("config.d/50-team.toml")
+            && message.ends_with("group \"team\" has unknown key \"unknown\""),
+        "{message}"
+    );
+}
+
+#[test]
+fn errors_on_missing_includes_and_include_cycles() {
+    let missing = TestInput {
+        toml: "include = [\"missing.toml\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "missing-include",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(missing).expect_err("missing include");
+    assert!(
+        err.to_string()
+            .ends_with("config.toml includes \"missing.toml\", which does not exist"),
+        "{err}"
+    );
+
+    let cycle = TestInput {
+        toml: "include = [\"a.toml\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "include-cycle",
+        cwd_entries: &[],
+        fs_entries: &[
+            ("config/dungeon/a.toml", Some("include = [\"b.toml\"]\n")),
+            ("config/dungeon/b.toml", Some("include = [\"a.toml\"]\n")),
+        ],
+    };
+    let err = try_resolve_input(cycle).expect_err("include cycle");
+    let message = err.to_string();
+    assert!(
+        message.starts_with("ERROR: config include cycle: ")
+            && message.contains("a.toml -> ")
+            && message.contains("b.toml -> ")
+            && message.ends_with("a.toml"),
+        "{message}"
+    );
+}
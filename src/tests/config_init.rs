@@ -0,0 +1,140 @@
+use std::path::Path;
+
+use crate::{cli, config};
+
+fn workspace(entries: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().expect("tempdir");
+    for (name, contents) in entries {
+        std::fs::write(dir.path().join(name), contents).expect("write entry");
+    }
+    dir
+}
+
+fn assert_valid(path: &Path) {
+    let diagnostics = config::validate_files(&[path.to_path_buf()]).expect("validate");
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn user_config_suggests_conditional_groups_and_git_metadata() {
+    let dir = workspace(&[
+        ("Cargo.toml", "[package]\n"),
+        ("package.json", "{}\n"),
+        (".git", "gitdir: /src/repo/.git/worktrees/feature\n"),
+    ]);
+    let path = dir.path().join("config/dungeon/config.toml");
+
+    config::init_config(&path, dir.path(), false, false).expect("init");
+
+    let data = std::fs::read_to_string(&path).expect("read config");
+    assert!(
+        data.contains("# Suggestions based on: Cargo.toml, package.json, .git file (worktree).\n"),
+        "{data}"
+    );
+    assert!(
+        data.contains("[general]\nmount_git_metadata = true\n"),
+        "{data}"
+    );
+    assert!(
+        data.contains(
+            "[rust]\ndescription = \"Rust registry and git caches\"\nwhen.file_exists = \"Cargo.toml\"\ncaches = [\"/home/dungeon/.cargo/registry\", \"/home/dungeon/.cargo/git\"]\n"
+        ),
+        "{data}"
+    );
+    assert!(
+        data.contains("when.file_exists = \"package.json\"\ncaches = [\"/home/dungeon/.npm\"]\ndynamic_ports = [\"dev\"]\n"),
+        "{data}"
+    );
+    assert!(!data.contains("[python]"), "{data}");
+    assert_valid(&path);
+}
+
+#[test]
+fn project_config_enables_detected_groups() {
+    let dir = workspace(&[("pyproject.toml", "[project]\n")]);
+    std::fs::create_dir(dir.path().join(".git")).expect("git dir");
+    let path = config::project_config_path(dir.path());
+
+    config::init_config(&path, dir.path(), true, false).expect("init");
+
+    let data = std::fs::read_to_string(&path).expect("read config");
+    assert!(
+        data.starts_with(
+            "# Project config for dungeon, generated by `dungeon config init --project`.\n"
+        ),
+        "{data}"
+    );
+    assert!(
+        data.contains("[general]\ninclude_groups = [\"python\"]\n\n[python]\ndescription = \"pip and uv caches\"\ncaches = "),
+        "{data}"
+    );
+    assert!(!data.contains("mount_git_metadata = true"), "{data}");
+    assert!(!data.contains("when."), "{data}");
+    assert_valid(&path);
+}
+
+#[test]
+fn markers_are_found_up_to_the_repository_root() {
+    let dir = workspace(&[("Cargo.toml", "[workspace]\n")]);
+    std::fs::create_dir(dir.path().join(".git")).expect("git dir");
+    let nested = dir.path().join("crates/app");
+    std::fs::create_dir_all(&nested).expect("nested dir");
+
+    let data = config::render_init_config(&nested, true);
+    assert!(data.contains("include_groups = [\"rust\"]"), "{data}");
+}
+
+#[test]
+fn empty_directory_gets_only_the_commented_defaults() {
+    let dir = workspace(&[]);
+
+    let data = config::render_init_config(dir.path(), false);
+    assert!(!data.contains("Suggestions"), "{data}");
+    let active = data
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>();
+    assert_eq!(active, ["version = 2"]);
+    assert!(
+        data.contains("# [codex]\n# description = \"Mount Codex config and auth\"\n"),
+        "{data}"
+    );
+}
+
+#[test]
+fn refuses_to_overwrite_without_force() {
+    let dir = workspace(&[("Cargo.toml", "[package]\n")]);
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[general]\nimage = \"mine\"\n").expect("write config");
+
+    let err = config::init_config(&path, dir.path(), false, false).expect_err("exists");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "ERROR: {} already exists; use --force to overwrite it",
+            path.display()
+        )
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read config"),
+        "[general]\nimage = \"mine\"\n"
+    );
+
+    config::init_config(&path, dir.path(), false, true).expect("force");
+    let data = std::fs::read_to_string(&path).expect("read config");
+    assert!(data.contains("[rust]"), "{data}");
+}
+
+#[test]
+fn config_init_parses_project_and_force() {
+    let defaults = config::Config::default();
+    let args = ["config", "init", "--project", "--force"]
+        .map(String::from)
+        .to_vec();
+    let parsed =
+        cli::parse_args_with_sources(args, &defaults, &defaults, &defaults).expect("parse init");
+    let cli::Action::ConfigInit(action) = parsed.action else {
+        panic!("expected config init");
+    };
+    assert!(action.project && action.force);
+}
@@ -0,0 +1,211 @@
+use crate::{
+    cli, config,
+    tests::support::{TestInput, config_warnings, resolve_input, try_resolve_input},
+};
+
+const LEGACY: &str = r#"[general]
+envs = ["PROMPT=${USER}", "!BANG"]
+
+[codex]
+mounts = ["~/notes/$${drafts}:/home/dungeon/notes:ro"]
+"#;
+
+#[test]
+fn unversioned_files_keep_literal_values_with_warnings() {
+    let input = || TestInput {
+        toml: LEGACY,
+        args: &["run", "--codex"],
+        env: &[],
+        cwd_name: "legacy",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "legacy/.dungeon.toml",
+            Some("[general]\nenvs = [\"!PROMPT\"]\n"),
+        )],
+    };
+
+    let settings = resolve_input(input()).resolved.settings;
+    assert_eq!(
+        settings.env_vars,
+        Some(vec![
+            "PROMPT=${USER}".to_string(),
+            "!BANG".to_string(),
+            "!PROMPT".to_string(),
+        ])
+    );
+    assert_eq!(
+        settings.mounts,
+        Some(vec!["~/notes/$${drafts}:/home/dungeon/notes:ro".into()])
+    );
+
+    let warnings = config_warnings(input());
+    let messages = warnings
+        .iter()
+        .map(|warning| warning.rsplit(".toml: ").next().unwrap_or_default())
+        .collect::<Vec<_>>();
+    let literal = "is read literally because the file has no version; run `dungeon config migrate`";
+    let project_literal =
+        "is read literally because the file has no version; run `dungeon config migrate --project`";
+    assert_eq!(
+        messages,
+        [
+            format!("codex.mounts value \"~/notes/$${{drafts}}:/home/dungeon/notes:ro\" {literal}"),
+            format!("general.envs value \"PROMPT=${{USER}}\" {literal}"),
+            format!("general.envs value \"!BANG\" {literal}"),
+            format!("general.envs value \"!PROMPT\" {project_literal}"),
+        ]
+    );
+    assert!(
+        warnings[0].contains("config/dungeon/config.toml: "),
+        "{warnings:?}"
+    );
+    assert!(
+        warnings[3].contains("legacy/.dungeon.toml: "),
+        "{warnings:?}"
+    );
+}
+
+#[test]
+fn current_version_rejects_old_shapes_and_newer_versions_fail() {
+    for toml in [
+        "[general]\nmount = [\"~/a:/a:ro\"]\n",
+        "version = 2\n\n[general]\nmount = [\"~/a:/a:ro\"]\n",
+    ] {
+        let input = TestInput {
+            toml,
+            args: &["run"],
+            env: &[],
+            cwd_name: "current",
+            cwd_entries: &[],
+            fs_entries: &[],
+        };
+        let err = try_resolve_input(input).expect_err("unknown key");
+        assert!(
+            err.to_string()
+                .ends_with("[general] has unknown key \"mount\""),
+            "{err}"
+        );
+    }
+
+    let newer = TestInput {
+        toml: "version = 3\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "newer",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(newer).expect_err("newer version");
+    assert!(
+        err.to_string()
+            .ends_with("config version 3 is newer than this dungeon supports (2); upgrade dungeon"),
+        "{err}"
+    );
+}
+
+#[test]
+fn migrate_rewrites_the_file_and_keeps_formatting() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        r#"# personal setup
+
+[general]
+image = "localhost/dungeon" # pinned
+envs = ["PROMPT=${USER}", "TERM=xterm"] # colours
+
+[profile.work.corp]
+# proxy settings
+mounts = [{ source = "~/corp", target = "/home/dungeon/${corp}" }, "!odd:/odd"]
+"#,
+    )
+    .expect("write config");
+
+    let changes = config::migrate_config_file(&path).expect("migrate");
+    assert_eq!(
+        changes,
+        [
+            "general.envs: \"PROMPT=${USER}\" -> \"PROMPT=$${USER}\"",
+            "profile.work.corp.mounts: \"/home/dungeon/${corp}\" -> \"/home/dungeon/$${corp}\"",
+            "profile.work.corp.mounts: \"!odd:/odd\" -> \"!!odd:/odd\"",
+            "version = 2",
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path).expect("read config"),
+        r#"# personal setup
+
+version = 2
+
+[general]
+image = "localhost/dungeon" # pinned
+envs = ["PROMPT=$${USER}", "TERM=xterm"] # colours
+
+[profile.work.corp]
+# proxy settings
+mounts = [{ source = "~/corp", target = "/home/dungeon/$${corp}" }, "!!odd:/odd"]
+"#
+    );
+    assert!(
+        config::migrate_config_file(&path)
+            .expect("migrate again")
+            .is_empty()
+    );
+}
+
+#[test]
+fn migrated_files_resolve_to_the_same_literal_values() {
+    let input = TestInput {
+        toml: "version = 2\n\n[general]\nenvs = [\"PROMPT=$${USER}\", \"!!BANG\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "migrated",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let settings = resolve_input(input).resolved.settings;
+    assert_eq!(
+        settings.env_vars,
+        Some(vec!["PROMPT=${USER}".to_string(), "!BANG".to_string(),])
+    );
+}
+
+#[test]
+fn validate_checks_the_version() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let legacy = dir.path().join("legacy.toml");
+    std::fs::write(&legacy, "[general]\ndynamic_ports = [\"Docs\"]\n").expect("write legacy");
+    let versioned = dir.path().join("versioned.toml");
+    std::fs::write(&versioned, "version = \"2\"\n").expect("write versioned");
+
+    let diagnostics = config::validate_files(&[legacy, versioned]).expect("validate");
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.line, diagnostic.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            (
+                2,
+                "general.dynamic_ports entries must be lower-case ASCII identifiers ([a-z][a-z0-9_]*)"
+            ),
+            (1, "version must be an integer between 1 and 2"),
+        ]
+    );
+}
+
+#[test]
+fn config_migrate_parses_project_flag() {
+    let defaults = config::Config::default();
+    let args = ["config", "migrate", "--project"]
+        .map(String::from)
+        .to_vec();
+    let parsed =
+        cli::parse_args_with_sources(args, &defaults, &defaults, &defaults).expect("parse migrate");
+    let cli::Action::ConfigMigrate(action) = parsed.action else {
+        panic!("expected config migrate");
+    };
+    assert!(action.project);
+}
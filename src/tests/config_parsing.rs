@@ -130,7 +130,7 @@ fn errors_on_invalid_engine_from_env() {
         fs_entries: &[],
     };
 
-    assert_input_error_contains(input, "engine must be one of: podman");
+    assert_input_error_contains(input, "DUNGEON_ENGINE must be one of: podman");
 }
 
 fn assert_input_error_contains(input: TestInput<'_>, expected_substring: &str) {
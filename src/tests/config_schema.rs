@@ -0,0 +1,82 @@
+use crate::{cli, config};
+
+#[test]
+fn schema_lists_every_setting_in_every_table() {
+    let schema = config::config_schema();
+    let tables = [
+        &schema["properties"]["general"]["properties"],
+        &schema["definitions"]["group"]["properties"],
+        &schema["properties"]["profile"]["additionalProperties"]["properties"],
+    ];
+    for properties in tables {
+        for field in config::SETTING_FIELDS {
+            let property = &properties[field.key];
+            let expected = match field.kind {
+                config::SettingKind::Scalar => "string",
+                config::SettingKind::List => "array",
+                config::SettingKind::Flag => "boolean",
+            };
+            assert_eq!(property["type"], expected, "{}", field.key);
+            assert_eq!(property["description"], field.help, "{}", field.key);
+        }
+        assert_eq!(properties["include_groups"]["type"], "array");
+    }
+
+    let group = &schema["definitions"]["group"];
+    assert_eq!(group["additionalProperties"], false);
+    assert_eq!(group["properties"]["description"]["type"], "string");
+    assert_eq!(
+        group["properties"]["when"]["properties"]
+            .as_object()
+            .expect("when keys")
+            .keys()
+            .collect::<Vec<_>>(),
+        ["file_exists", "git_remote", "path"]
+    );
+    assert_eq!(
+        schema["additionalProperties"]["$ref"],
+        "#/definitions/group"
+    );
+    assert_eq!(schema["properties"]["include"]["items"]["type"], "string");
+}
+
+#[test]
+fn schema_describes_value_formats() {
+    let schema = config::config_schema();
+    let general = &schema["properties"]["general"]["properties"];
+
+    assert_eq!(
+        general["engine"]["enum"],
+        serde_json::json!(["podman", "docker"])
+    );
+    assert_eq!(
+        general["dynamic_ports"]["items"]["anyOf"][0]["pattern"],
+        "^[a-z][a-z0-9_]*$"
+    );
+    assert_eq!(
+        general["dynamic_ports"]["items"]["anyOf"][1]["pattern"], "^!",
+        "removal entries are accepted in formatted lists"
+    );
+    assert_eq!(
+        general["ports"]["items"],
+        serde_json::json!({ "type": "string" }),
+        "port specs are checked by dungeon, not the schema"
+    );
+    assert_eq!(
+        general["mounts"]["items"]["anyOf"][0],
+        serde_json::json!({ "type": "string" })
+    );
+    assert_eq!(
+        general["mounts"]["items"]["anyOf"][1]["required"],
+        serde_json::json!(["source", "target"])
+    );
+}
+
+#[test]
+fn config_schema_parses_as_an_action() {
+    let defaults = config::Config::default();
+    let args = ["config", "schema"].map(String::from).to_vec();
+    let parsed =
+        cli::parse_args_with_sources(args, &defaults, &defaults, &defaults).expect("parse schema");
+    assert!(matches!(parsed.action, cli::Action::ConfigSchema));
+}
@@ -0,0 +1,207 @@
+use crate::{
+    config::{Origin, TracedSetting, TracedValue, render_trace},
+    tests::support::{TestInput, trace_input, try_trace_input},
+};
+
+fn traced(value: &str, origin: Origin) -> TracedValue {
+    TracedValue {
+        value: toml::Value::String(value.to_string()),
+        origin,
+    }
+}
+
+#[test]
+fn config_show_records_origin_of_each_layer() {
+    let input = TestInput {
+        toml: r#"
+[general]
+image = "localhost/dungeon-user"
+ports = ["127.0.0.1:3000:3000"]
+
+[rust]
+caches = ["/home/dungeon/.cargo/registry"]
+ports = ["127.0.0.1:4000:4000"]
+"#,
+        args: &["config", "show", "--groups", "rust"],
+        env: &[("DUNGEON_PORTS", "127.0.0.1:5000:5000")],
+        cwd_name: "config-show",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "config-show/.dungeon.toml",
+            Some("[general]\ncommand = \"echo project\"\n"),
+        )],
+    };
+
+    let trace = trace_input(input);
+    assert_eq!(trace.groups, vec!["rust".to_string()]);
+
+    let TracedSetting::List(ports) = &trace.settings["ports"] else {
+        panic!("ports should be a list");
+    };
+    let origins = ports
+        .iter()
+        .map(|entry| entry.origin.to_string())
+        .collect::<Vec<_>>();
+    assert!(origins[0].starts_with("file ") && origins[0].ends_with("config.toml"));
+    assert_eq!(origins[1..], ["group rust", "env DUNGEON_PORTS"]);
+
+    let TracedSetting::Scalar(command) = &trace.settings["command"] else {
+        panic!("command should be a scalar");
+    };
+    assert!(command.origin.to_string().starts_with("project "));
+    assert!(command.origin.to_string().ends_with(".dungeon.toml"));
+
+    assert_eq!(
+        trace.settings["engine"],
+        TracedSetting::Scalar(TracedValue {
+            value: toml::Value::String("podman".to_string()),
+            origin: Origin::Default,
+        })
+    );
+}
+
+#[test]
+fn config_show_names_the_fragment_or_include_that_set_a_value() {
+    let input = TestInput {
+        toml: "include = [\"shared.toml\"]\n\n[general]\nenvs = [\"MAIN=1\"]\n",
+        args: &["config", "show"],
+        env: &[],
+        cwd_name: "config-show-files",
+        cwd_entries: &[],
+        fs_entries: &[
+            (
+                "config/dungeon/config.d/10-image.toml",
+                Some("[general]\nimage = \"localhost/fragment\"\nenvs = [\"FRAGMENT=1\"]\n"),
+            ),
+            (
+                "config/dungeon/shared.toml",
+                Some("[general]\nenvs = [\"SHARED=1\"]\n"),
+            ),
+        ],
+    };
+
+    let trace = trace_input(input);
+    let TracedSetting::Scalar(image) = &trace.settings["image"] else {
+        panic!("image should be a scalar");
+    };
+    assert!(
+        image.origin.to_string().ends_with("config.d/10-image.toml"),
+        "{}",
+        image.origin
+    );
+
+    let TracedSetting::List(envs) = &trace.settings["envs"] else {
+        panic!("envs should be a list");
+    };
+    let origins = envs
+        .iter()
+        .map(|entry| {
+            let origin = entry.origin.to_string();
+            let file = origin.rsplit("config/dungeon/").next().unwrap_or_default();
+            format!("{} {}", entry.value, file)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        origins,
+        [
+            "\"FRAGMENT=1\" config.d/10-image.toml",
+            "\"SHARED=1\" shared.toml",
+            "\"MAIN=1\" config.toml",
+        ]
+    );
+}
+
+#[test]
+fn config_show_attributes_group_dependencies() {
+    let input = TestInput {
+        toml: r#"
+[base]
+mounts = ["/srv/base:/base"]
+
+[tools]
+include_groups = ["base"]
+mounts = ["/srv/tools:/tools"]
+"#,
+        args: &["config", "show", "--groups", "tools"],
+        env: &[],
+        cwd_name: "config-show-deps",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let trace = trace_input(input);
+    assert_eq!(trace.groups, vec!["base".to_string(), "tools".to_string()]);
+    assert_eq!(
+        trace.settings["mounts"],
+        TracedSetting::List(vec![
+            traced("/srv/base:/base", Origin::Group("base".to_string())),
+            traced("/srv/tools:/tools", Origin::Group("tools".to_string())),
+        ])
+    );
+}
+
+#[test]
+fn renders_trace_with_origin_comments() {
+    let input = TestInput {
+        toml: r#"
+[general]
+run_args = ["--init"]
+"#,
+        args: &["config", "show"],
+        env: &[("DUNGEON_IMAGE", "localhost/dungeon-env")],
+        cwd_name: "config-show-render",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let rendered = render_trace(&trace_input(input));
+    assert!(rendered.contains("image = \"localhost/dungeon-env\"  # env DUNGEON_IMAGE"));
+    assert!(rendered.contains("run_args = [\n  \"--init\",  # file "));
+    assert!(rendered.starts_with("groups = []"));
+}
+
+#[test]
+fn config_show_prints_the_values_a_run_uses() {
+    let input = TestInput {
+        toml: r#"
+[general]
+run_args = ["!--init", "--label=${USER}"]
+image = "${USER}/dungeon"
+"#,
+        args: &["config", "show"],
+        env: &[("USER", "me")],
+        cwd_name: "config-show-v1",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "config-show-v1/.dungeon.toml",
+            Some("version = 2\n[general]\nenvs = [\"!!BANG=1\", \"WHO=${USER}\"]\n"),
+        )],
+    };
+
+    let trace = trace_input(input);
+    let values = |key: &str| match &trace.settings[key] {
+        TracedSetting::List(entries) => entries
+            .iter()
+            .map(|entry| entry.value.to_string())
+            .collect::<Vec<_>>(),
+        TracedSetting::Scalar(entry) => vec![entry.value.to_string()],
+    };
+    assert_eq!(values("run_args"), ["\"!--init\"", "\"--label=${USER}\""]);
+    assert_eq!(values("image"), ["\"${USER}/dungeon\""]);
+    assert_eq!(values("envs"), ["\"!BANG=1\"", "\"WHO=me\""]);
+}
+
+#[test]
+fn config_show_rejects_unknown_groups() {
+    let input = TestInput {
+        toml: "",
+        args: &["config", "show", "--groups", "missing"],
+        env: &[],
+        cwd_name: "config-show-unknown",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_trace_input(input).expect_err("unknown group");
+    assert_eq!(err.to_string(), "ERROR: unknown group \"missing\"");
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+
+use crate::config::{self, Diagnostic};
+
+fn write_config(dir: &tempfile::TempDir, name: &str, contents: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).expect("write config");
+    path
+}
+
+fn rendered(diagnostics: &[Diagnostic], dir: &tempfile::TempDir) -> Vec<String> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            diagnostic
+                .to_string()
+                .replace(dir.path().to_string_lossy().as_ref(), "<TMP>")
+        })
+        .collect()
+}
+
+#[test]
+fn reports_every_problem_with_locations() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(
+        &dir,
+        "config.toml",
+        r#"[general]
+ports = "127.0.0.1:3000:3000"
+unknown = true
+expose_host_ports = ["8080", "70000"]
+
+[docs]
+dynamic_ports = ["Docs"]
+caches = [1]
+
+[disabled]
+image = "localhost/never-enabled"
+typo = "x"
+"#,
+    );
+
+    let diagnostics = config::validate_files(&[path]).expect("validate");
+    assert_eq!(
+        rendered(&diagnostics, &dir),
+        vec![
+            "<TMP>/config.toml:2:9: general.ports must be a list of strings",
+            "<TMP>/config.toml:3:1: [general] has unknown key \"unknown\"",
+            "<TMP>/config.toml:4:30: invalid exposed host port specification \"70000\"; expected PORT, PORT:HOST_PORT, RANGE, or RANGE:HOST_RANGE",
+            "<TMP>/config.toml:7:17: docs.dynamic_ports entries must be lower-case ASCII identifiers ([a-z][a-z0-9_]*)",
+            "<TMP>/config.toml:8:10: docs.caches must be a list of strings",
+            "<TMP>/config.toml:12:1: group \"disabled\" has unknown key \"typo\"",
+        ]
+    );
+}
+
+#[test]
+fn reports_group_reference_problems_across_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let user = write_config(
+        &dir,
+        "config.toml",
+        r#"[general]
+include_groups = ["missing"]
+
+[a]
+include_groups = ["b"]
+
+[run]
+image = "localhost/reserved"
+"#,
+    );
+    let project = write_config(
+        &dir,
+        ".dungeon.toml",
+        r#"[b]
+include_groups = ["a", "gone"]
+"#,
+    );
+
+    let diagnostics = config::validate_files(&[user, project]).expect("validate");
+    assert_eq!(
+        rendered(&diagnostics, &dir),
+        vec![
+            "<TMP>/config.toml:2:19: include_groups includes unknown group \"missing\"",
+            "<TMP>/config.toml:7:2: group name 'run' conflicts with a reserved CLI flag",
+            "<TMP>/.dungeon.toml:2:19: group inclusion cycle: a -> b -> a",
+            "<TMP>/.dungeon.toml:2:24: group \"b\" includes unknown group \"gone\"",
+        ]
+    );
+}
+
+#[test]
+fn applies_the_project_file_rules_to_dungeon_toml() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let project = write_config(
+        &dir,
+        ".dungeon.toml",
+        r#"include = ["shared.toml"]
+
+[profile.work]
+image = "localhost/work"
+"#,
+    );
+
+    let diagnostics = config::validate_files(&[project]).expect("validate");
+    assert_eq!(
+        rendered(&diagnostics, &dir),
+        vec![
+            "<TMP>/.dungeon.toml:1:1: include can only be used in the user config",
+            "<TMP>/.dungeon.toml:3:2: profiles can only be defined in the user config",
+        ]
+    );
+}
+
+#[test]
+fn reports_syntax_errors_with_locations() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir, "config.toml", "[general]\nimage = \n");
+
+    let diagnostics = config::validate_files(&[path]).expect("validate");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 9));
+}
+
+#[test]
+fn accepts_valid_config() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(
+        &dir,
+        "config.toml",
+        r#"[general]
+include_groups = ["codex"]
+expose_host_ports = ["8080", "9000-9002:19000-19002"]
+
+[codex]
+mounts = ["~/.codex:/home/dungeon/.codex:rw"]
+"#,
+    );
+
+    assert!(
+        config::validate_files(&[path])
+            .expect("validate")
+            .is_empty()
+    );
+}
@@ -0,0 +1,143 @@
+use crate::tests::support::{TestInput, resolve_input, try_resolve_input};
+
+const CONFIG: &str = r#"
+[general]
+include_groups = ["ai", "dev"]
+
+[ai]
+include_groups = ["agent", "tools"]
+envs = ["AI=1"]
+
+[agent]
+envs = ["AGENT=1"]
+
+[dev]
+include_groups = ["tools"]
+envs = ["DEV=1"]
+
+[tools]
+envs = ["TOOLS=1"]
+
+[rust]
+when.file_exists = "Cargo.toml"
+envs = ["RUST=1"]
+"#;
+
+#[test]
+fn negated_group_flag_drops_root_group_and_its_own_dependencies() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--no-ai"],
+        env: &[],
+        cwd_name: "disabled-groups",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(resolved.groups, vec!["tools", "dev", "rust"]);
+    assert_eq!(
+        resolved.settings.env_vars,
+        Some(vec![
+            "TOOLS=1".to_string(),
+            "DEV=1".to_string(),
+            "RUST=1".to_string()
+        ])
+    );
+}
+
+#[test]
+fn without_flag_accepts_comma_separated_groups() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--without", "dev,rust"],
+        env: &[],
+        cwd_name: "disabled-groups",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(resolved.groups, vec!["agent", "tools", "ai"]);
+}
+
+#[test]
+fn dependency_of_a_disabled_group_can_still_be_enabled_explicitly() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--no-ai", "--agent"],
+        env: &[],
+        cwd_name: "disabled-groups",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(resolved.groups, vec!["tools", "dev", "rust", "agent"]);
+}
+
+#[test]
+fn errors_when_disabling_a_dependency_of_an_enabled_group() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--no-tools"],
+        env: &[],
+        cwd_name: "disabled-groups",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(input).expect_err("dependency");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: cannot disable group \"tools\": it is included by enabled group \"ai\""
+    );
+}
+
+#[test]
+fn errors_on_enabling_and_disabling_the_same_group() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--ai", "--without", "ai"],
+        env: &[],
+        cwd_name: "disabled-groups",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(input).expect_err("conflict");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: --ai and --no-ai are mutually exclusive"
+    );
+}
+
+#[test]
+fn errors_on_unknown_without_group() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--without", "missing"],
+        env: &[],
+        cwd_name: "disabled-groups",
+        cwd_entries: &["Cargo.toml"],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(input).expect_err("unknown");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: --without names unknown group \"missing\""
+    );
+}
+
+#[test]
+fn errors_on_group_names_using_the_negation_prefix() {
+    let input = TestInput {
+        toml: "[no-ai]\nenvs = [\"AI=0\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "negated-group-name",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_resolve_input(input).expect_err("reserved name");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: group name 'no-ai' conflicts with a reserved CLI flag"
+    );
+}
@@ -135,12 +135,17 @@ dynamic_ports = ["Difit"]
 
 #[test]
 fn rejects_invalid_dynamic_port_names_from_environment_and_cli() {
-    for (args, env) in [
+    for (args, env, label) in [
         (
             vec!["run", "--dynamic-port", "Difit"],
             Vec::<(&str, &str)>::new(),
+            "--dynamic-port entries",
+        ),
+        (
+            vec!["run"],
+            vec![("DUNGEON_DYNAMIC_PORTS", "Difit")],
+            "DUNGEON_DYNAMIC_PORTS entries",
         ),
-        (vec!["run"], vec![("DUNGEON_DYNAMIC_PORTS", "Difit")]),
     ] {
         let input = TestInput {
             toml: "",
@@ -152,6 +157,10 @@ fn rejects_invalid_dynamic_port_names_from_environment_and_cli() {
         };
 
         let err = try_run_input(input).expect_err("invalid dynamic port name");
-        assert!(err.to_string().contains("lower-case ASCII identifiers"));
+        assert!(
+            err.to_string().starts_with(label)
+                && err.to_string().contains("lower-case ASCII identifiers"),
+            "{err}"
+        );
     }
 }
@@ -99,6 +99,58 @@ engine = "podman"
     assert_command(input, expected);
 }
 
+#[test]
+fn engine_from_env_accepts_docker() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[("DUNGEON_ENGINE", "docker")],
+        cwd_name: "docker-env-project",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let expected = "docker run -it -w /workspace/docker-env-project --rm -v <CWD>:/workspace/docker-env-project localhost/dungeon zsh";
+
+    assert_command(input, expected);
+}
+
+#[test]
+fn docker_engine_runs_as_root_without_keep_id() {
+    let input = TestInput {
+        toml: r#"
+[general]
+engine = "docker"
+podman_args = ["--context", "remote"]
+run_args = ["--runtime=runsc"]
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "docker-config-project",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let output = run_input(input);
+
+    assert!(
+        output
+            .command
+            .starts_with("docker --context remote run -it --user root ")
+    );
+    assert!(!output.command.contains("--userns=keep-id"));
+    assert!(output.command.contains("--runtime=runsc"));
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    assert!(
+        output.command.contains(&format!(
+            "--user root --env DUNGEON_UID={} --env DUNGEON_GID={} -w ",
+            uid, gid
+        )),
+        "{}",
+        output.command
+    );
+}
+
 #[test]
 fn passes_run_args_from_config_and_cli() {
     let input = TestInput {
@@ -0,0 +1,112 @@
+use crate::{
+    config,
+    container::{
+        backend::{ContainerEngine, ContainerSummary, ImageBuild, Mount, RunSession},
+        engine,
+    },
+    error::AppError,
+    tests::support::acquire_test_lock,
+};
+
+struct MockEngine;
+
+impl ContainerEngine for MockEngine {
+    fn program(&self) -> &'static str {
+        "mock"
+    }
+
+    fn run_session_args(&self, session: &RunSession) -> Vec<String> {
+        let mut args = vec!["start".to_string(), session.workdir.clone()];
+        for mount in &session.mounts {
+            args.push(match mount {
+                Mount::Bind(spec) => format!("bind={}", spec),
+                Mount::Volume { name, spec } => format!("volume={}:{}", name, spec),
+            });
+        }
+        args.push(session.image.clone());
+        args
+    }
+
+    fn build_image_args(&self, build: &ImageBuild) -> Vec<String> {
+        vec!["bake".to_string(), build.tag.clone()]
+    }
+
+    fn remove_volume_args(&self, name: &str) -> Vec<String> {
+        vec!["drop".to_string(), name.to_string()]
+    }
+
+    fn list_containers_args(&self, label: &str, _all: bool) -> Vec<String> {
+        vec!["list".to_string(), label.to_string()]
+    }
+
+    fn parse_container_list(&self, output: &str) -> Result<Vec<ContainerSummary>, AppError> {
+        Ok(output
+            .lines()
+            .map(|name| ContainerSummary {
+                name: name.to_string(),
+                ..ContainerSummary::default()
+            })
+            .collect())
+    }
+
+    fn attach_args(&self, container: &str) -> Vec<String> {
+        vec!["join".to_string(), container.to_string()]
+    }
+
+    fn exec_args(&self, container: &str, command: &[String]) -> Vec<String> {
+        let mut args = vec!["enter".to_string(), container.to_string()];
+        args.extend(command.iter().cloned());
+        args
+    }
+
+    fn stop_args(&self, container: &str) -> Vec<String> {
+        vec!["halt".to_string(), container.to_string()]
+    }
+
+    fn remove_container_args(&self, container: &str, _force: bool) -> Vec<String> {
+        vec!["delete".to_string(), container.to_string()]
+    }
+}
+
+#[test]
+fn mock_engine_receives_typed_run_session() {
+    let _guard = acquire_test_lock();
+    let settings = config::Settings {
+        podman_args: Some(vec!["--global".to_string()]),
+        mounts: Some(vec!["/data:/data:ro".into()]),
+        cache: Some(vec!["/var/cache/pacman/pkg".to_string()]),
+        image: Some("localhost/mock".to_string()),
+        ..config::Settings::default()
+    };
+
+    let session =
+        engine::resolve_run_session(&settings, &[], true, false).expect("resolve session");
+    let spec = engine::build_engine_command(
+        &MockEngine,
+        &settings,
+        MockEngine.run_session_args(&session),
+    );
+
+    assert_eq!(
+        format!("{} {}", spec.program, spec.args.join(" ")),
+        "mock --global start /workspace bind=/data:/data:ro volume=dungeon-cache:/var/cache/pacman/pkg localhost/mock"
+    );
+}
+
+#[test]
+fn run_session_holds_shell_command_and_default_image() {
+    let _guard = acquire_test_lock();
+    let settings = config::Settings {
+        command: Some("echo ok".to_string()),
+        env_vars: Some(vec!["FOO=bar".to_string()]),
+        ..config::Settings::default()
+    };
+
+    let session =
+        engine::resolve_run_session(&settings, &[], true, false).expect("resolve session");
+
+    assert_eq!(session.image, "localhost/dungeon");
+    assert_eq!(session.command, vec!["zsh", "-ic", "echo ok"]);
+    assert_eq!(session.env_vars, vec!["FOO=bar"]);
+    assert!(session.mounts.is_empty());
+}
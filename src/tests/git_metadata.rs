@@ -136,7 +136,7 @@ mount_git_metadata = true
 }
 
 #[test]
-fn rejects_relative_gitdir_paths() {
+fn relative_gitdir_paths_are_mounted_where_the_container_resolves_them() {
     let input = TestInput {
         toml: r#"
 [general]
@@ -146,17 +146,63 @@ mount_git_metadata = true
         env: &[],
         cwd_name: "relative-worktree",
         cwd_entries: &[],
-        fs_entries: &[(
-            "relative-worktree/.git",
-            Some("gitdir: ../repo/.git/worktrees/relative-worktree\n"),
-        )],
+        fs_entries: &[
+            (
+                "relative-worktree/.git",
+                Some("gitdir: ../repo/.git/worktrees/relative-worktree\n"),
+            ),
+            ("repo/.git/worktrees/relative-worktree", None),
+            (
+                "repo/.git/worktrees/relative-worktree/commondir",
+                Some("../..\n"),
+            ),
+        ],
     };
 
-    let err = try_run_input(input).expect_err("expected relative gitdir error");
-    assert!(
-        err.to_string()
-            .contains("ERROR: relative gitdir paths are unsupported")
-    );
+    let expected = "podman run -it --userns=keep-id -w /workspace/relative-worktree --rm -v <CWD>:/workspace/relative-worktree -v <TMP>/repo/.git:/workspace/repo/.git localhost/dungeon zsh";
+
+    assert_command(input, expected);
+}
+
+#[test]
+fn submodule_git_files_are_scanned_recursively() {
+    let input = TestInput {
+        toml: r#"
+[general]
+mount_git_metadata = true
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "super-project",
+        cwd_entries: &[".git/modules/lib/", "docs/"],
+        fs_entries: &[
+            (
+                "super-project/.gitmodules",
+                Some(
+                    "[submodule \"lib\"]\n\tpath = lib\n\turl = ../lib.git\n[submodule \"missing\"]\n\tpath = missing\n[submodule \"escape\"]\n\tpath = ../outside\n",
+                ),
+            ),
+            (
+                "super-project/lib/.git",
+                Some("gitdir: ../.git/modules/lib\n"),
+            ),
+            (
+                "super-project/lib/.gitmodules",
+                Some("[submodule \"vendor/dep\"]\n\tpath = vendor/dep\n"),
+            ),
+            (
+                "super-project/lib/vendor/dep/.git",
+                Some("gitdir: <TMP>/modules/dep\n"),
+            ),
+            ("modules/dep", None),
+            ("outside/.git", Some("gitdir: <TMP>/modules/outside\n")),
+            ("modules/outside", None),
+        ],
+    };
+
+    let expected = "podman run -it --userns=keep-id -w /workspace/super-project --rm -v <CWD>:/workspace/super-project -v <TMP>/modules/dep:<TMP>/modules/dep localhost/dungeon zsh";
+
+    assert_command(input, expected);
 }
 
 #[test]
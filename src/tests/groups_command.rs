@@ -0,0 +1,164 @@
+use crate::tests::support::{TestInput, groups_output, try_groups_output, try_resolve_input};
+
+const CONFIG: &str = r#"
+[ai]
+description = "Coding agents"
+include_groups = ["agent", "tools"]
+
+[agent]
+envs = ["AGENT=1"]
+include_groups = ["tools"]
+
+[tools]
+description = "Shared tooling"
+mounts = ["~/.tools:/home/dungeon/.tools:ro"]
+"#;
+
+#[test]
+fn groups_list_shows_description_and_source() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["groups", "list"],
+        env: &[],
+        cwd_name: "groups-command",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "groups-command/.dungeon.toml",
+            Some("[local]\ndescription = \"Project only\"\n"),
+        )],
+    };
+    let output = groups_output(input);
+    let rows = output
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    assert_eq!(rows[0], ["NAME", "DESCRIPTION", "SOURCE"]);
+    let ai = output
+        .lines()
+        .find(|line| line.starts_with("ai "))
+        .expect("ai");
+    assert!(ai.contains("Coding agents") && ai.contains("file ") && ai.ends_with("config.toml"));
+    let agent = output
+        .lines()
+        .find(|line| line.starts_with("agent "))
+        .expect("agent");
+    assert!(agent.contains(" - "), "{agent}");
+    let codex = output
+        .lines()
+        .find(|line| line.starts_with("codex "))
+        .expect("codex");
+    assert!(codex.ends_with("default"), "{codex}");
+    let local = output
+        .lines()
+        .find(|line| line.starts_with("local "))
+        .expect("local");
+    assert!(
+        local.contains("project ") && local.ends_with(".dungeon.toml"),
+        "{local}"
+    );
+}
+
+#[test]
+fn groups_show_prints_settings_and_expanded_order() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["groups", "show", "ai"],
+        env: &[],
+        cwd_name: "groups-command",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "groups-command/.dungeon.toml",
+            Some("[local]\ndescription = \"Project only\"\n"),
+        )],
+    };
+    let output = groups_output(input);
+    let lines = output.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines[0], "[ai]");
+    assert!(lines[1].starts_with("# file "));
+    assert_eq!(
+        lines[2..],
+        [
+            "description = \"Coding agents\"",
+            "include_groups = [\"agent\", \"tools\"]",
+            "",
+            "# enables: tools -> agent -> ai",
+        ]
+    );
+}
+
+#[test]
+fn groups_graph_renders_tree_and_dot() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["groups", "graph"],
+        env: &[],
+        cwd_name: "groups-command",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "groups-command/.dungeon.toml",
+            Some("[local]\ndescription = \"Project only\"\n"),
+        )],
+    };
+    let tree = groups_output(input);
+    assert_eq!(
+        tree,
+        "ai\n├── agent\n│   └── tools\n└── tools\ncodex\nlocal\nopencode\npi"
+    );
+
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["groups", "graph", "--format", "dot"],
+        env: &[],
+        cwd_name: "groups-command",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "groups-command/.dungeon.toml",
+            Some("[local]\ndescription = \"Project only\"\n"),
+        )],
+    };
+    let dot = groups_output(input);
+    assert!(dot.starts_with("digraph groups {\n"));
+    assert!(dot.contains("  \"ai\" -> \"agent\";\n  \"ai\" -> \"tools\";\n"));
+    assert!(dot.contains("  \"agent\" -> \"tools\";\n"));
+    assert!(dot.ends_with("\n}"));
+}
+
+#[test]
+fn groups_requires_a_subcommand() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["groups"],
+        env: &[],
+        cwd_name: "groups-command",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "groups-command/.dungeon.toml",
+            Some("[local]\ndescription = \"Project only\"\n"),
+        )],
+    };
+    let err = try_groups_output(input).expect_err("subcommand");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: groups requires a subcommand (use: groups list, groups show, groups graph)"
+    );
+}
+
+#[test]
+fn errors_on_non_string_group_description() {
+    let input = TestInput {
+        toml: "[ai]\ndescription = 1\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "bad-description",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_resolve_input(input).expect_err("description type");
+    assert!(
+        err.to_string().contains("ai.description must be a string"),
+        "{err}"
+    );
+}
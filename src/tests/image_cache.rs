@@ -113,6 +113,7 @@ fn image_build_accepts_podman_args_from_config_and_cli() {
             file: file_cfg.settings.clone(),
             env: env_cfg.settings.clone(),
             cli: parsed.settings.clone(),
+            ..config::Sources::default()
         },
         &defaults.groups,
         &[],
@@ -158,6 +159,7 @@ fn cache_reset_accepts_podman_args_from_config_and_cli() {
             file: file_cfg.settings.clone(),
             env: env_cfg.settings.clone(),
             cli: parsed.settings.clone(),
+            ..config::Sources::default()
         },
         &defaults.groups,
         &[],
@@ -200,6 +202,7 @@ fn image_build_uses_podman_args_from_included_groups() {
         defaults,
         file: file_cfg,
         env: config::Config::default(),
+        ..config::LoadedConfigSources::default()
     };
     let args = vec!["image".to_string(), "build".to_string()];
 
@@ -247,6 +250,7 @@ fn cache_reset_uses_podman_args_from_included_groups() {
         defaults,
         file: file_cfg,
         env: config::Config::default(),
+        ..config::LoadedConfigSources::default()
     };
     let args = vec!["cache".to_string(), "reset".to_string()];
 
@@ -267,3 +271,23 @@ fn cache_reset_uses_podman_args_from_included_groups() {
         "podman -c agent-vm volume rm -f dungeon-cache"
     );
 }
+
+#[test]
+fn image_build_and_cache_reset_use_docker_engine() {
+    let settings = config::Settings {
+        engine: Some(config::Engine::Docker),
+        ..config::Settings::default()
+    };
+
+    let build = container::engine::build_image_command(&settings, "localhost/dungeon", true, ".");
+    let reset = container::engine::build_cache_reset_command(&settings);
+
+    assert_eq!(
+        format!("{} {}", build.program, build.args.join(" ")),
+        "docker build -f images/Containerfile -t localhost/dungeon --no-cache ."
+    );
+    assert_eq!(
+        format!("{} {}", reset.program, reset.args.join(" ")),
+        "docker volume rm -f dungeon-cache"
+    );
+}
@@ -0,0 +1,131 @@
+use crate::{
+    container::engine::reserve_dynamic_ports,
+    tests::support::{TestInput, assert_command, resolve_input, try_resolve_input},
+};
+
+#[test]
+fn expands_variables_defaults_and_builtins_in_string_settings() {
+    let input = TestInput {
+        toml: r#"
+version = 2
+
+[general]
+command = "${SHELL_NAME:-bash}"
+ports = ["127.0.0.1:${APP_PORT}:${APP_PORT}"]
+mounts = ["${DATA_DIR}/${project_name}:/data"]
+envs = ["PROJECT=${project_name}", "ROOT=${project_dir}", "MODE=${MODE:-dev}"]
+run_args = ["--hostname=${project_name}"]
+"#,
+        args: &["run"],
+        env: &[("APP_PORT", "4000"), ("DATA_DIR", "/srv"), ("MODE", "")],
+        cwd_name: "interpolated",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let expected = "podman run -it --userns=keep-id -w /workspace/interpolated --rm --env PROJECT=interpolated --env ROOT=<CWD> --env MODE=dev -p 127.0.0.1:4000:4000 --hostname=interpolated -v /srv/interpolated:/data -v <CWD>:/workspace/interpolated localhost/dungeon zsh -ic bash";
+
+    assert_command(input, expected);
+}
+
+#[test]
+fn project_builtins_point_at_the_project_config_directory() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "app/src",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "app/.dungeon.toml",
+            Some(
+                "version = 2\n\n[general]\nenvs = [\"NAME=${project_name}\", \"DIR=${project_dir}\"]\n",
+            ),
+        )],
+    };
+
+    let output = resolve_input(input);
+    let envs = output.resolved.settings.env_vars.expect("envs");
+    assert_eq!(envs[0], "NAME=app");
+    assert!(envs[1].starts_with("DIR=") && envs[1].ends_with("/app"));
+}
+
+#[test]
+fn escaped_references_are_kept_literally() {
+    let input = TestInput {
+        toml: "version = 2\n\n[general]\nenvs = [\"TEMPLATE=$${NAME}\", \"PRICE=$5\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "escaped",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let output = resolve_input(input);
+    assert_eq!(
+        output.resolved.settings.env_vars,
+        Some(vec!["TEMPLATE=${NAME}".to_string(), "PRICE=$5".to_string()])
+    );
+}
+
+#[test]
+fn errors_on_undefined_variables() {
+    let input = TestInput {
+        toml: "version = 2\n\n[general]\nmounts = [\"${MISSING_DIR}:/data\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "undefined-variable",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_resolve_input(input).expect_err("undefined variable");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: undefined variable \"MISSING_DIR\" in mounts value \"${MISSING_DIR}:/data\""
+    );
+}
+
+#[test]
+fn port_references_expand_to_reserved_dynamic_ports() {
+    let input = TestInput {
+        toml: r#"
+version = 2
+
+[general]
+dynamic_ports = ["difit"]
+envs = ["DIFIT_URL=http://127.0.0.1:${port:difit}"]
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "port-reference",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let mut settings = resolve_input(input).resolved.settings;
+    let _reservations = reserve_dynamic_ports(&mut settings).expect("reserve dynamic ports");
+    let envs = settings.env_vars.expect("envs");
+    let port = envs[1]
+        .strip_prefix("DUNGEON_PORT_FOR_DIFIT=")
+        .expect("generated env");
+    assert_eq!(envs[0], format!("DIFIT_URL=http://127.0.0.1:{}", port));
+}
+
+#[test]
+fn errors_on_port_references_without_dynamic_port() {
+    let input = TestInput {
+        toml: "version = 2\n\n[general]\nenvs = [\"URL=${port:api}\"]\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "unknown-port-reference",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_resolve_input(input).expect_err("unknown dynamic port");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: \"${port:api}\" refers to \"api\", which is not in dynamic_ports in envs value \"URL=${port:api}\""
+    );
+}
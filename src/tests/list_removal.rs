@@ -0,0 +1,128 @@
+use crate::{
+    config::{Origin, TracedSetting},
+    tests::support::{TestInput, resolve_input, trace_input},
+};
+
+#[test]
+fn removal_entries_drop_inherited_values_from_toml_env_and_cli() {
+    let input = TestInput {
+        toml: r#"
+version = 2
+
+[general]
+include_groups = ["agent"]
+mounts = ["~/data:/data:ro", "~/notes:/notes:rw"]
+envs = ["SECRET=value", "EDITOR=vim"]
+ports = ["127.0.0.1:3000:3000"]
+
+[agent]
+mounts = ["~/.agent:/home/dungeon/.agent:rw"]
+"#,
+        args: &["run", "--mount", "!~/.agent", "--port", "!127.0.0.1:3000"],
+        env: &[("DUNGEON_ENVS", "!SECRET")],
+        cwd_name: "list-removal",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "list-removal/.dungeon.toml",
+            Some("version = 2\n\n[general]\nmounts = [\"!~/notes:/notes:rw\"]\n"),
+        )],
+    };
+
+    let settings = resolve_input(input).resolved.settings;
+    assert_eq!(settings.mounts, Some(vec!["~/data:/data:ro".into()]));
+    assert_eq!(settings.env_vars, Some(vec!["EDITOR=vim".to_string()]));
+    assert_eq!(settings.ports, Some(Vec::new()));
+}
+
+#[test]
+fn project_removal_entries_drop_group_values() {
+    let input = TestInput {
+        toml: r#"
+version = 2
+
+[general]
+include_groups = ["agent"]
+
+[agent]
+mounts = ["~/.agent:/home/dungeon/.agent:rw", "~/.cache/agent:/home/dungeon/.cache/agent:rw"]
+envs = ["AGENT_TOKEN=secret"]
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "project-removal",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "project-removal/.dungeon.toml",
+            Some(
+                "version = 2\n\n[general]\nmounts = [\"!~/.agent\", \"~/src:/src:ro\"]\nenvs = [\"!*\", \"!AGENT_TOKEN\"]\n",
+            ),
+        )],
+    };
+
+    let settings = resolve_input(input).resolved.settings;
+    assert_eq!(
+        settings.mounts,
+        Some(vec![
+            "~/src:/src:ro".into(),
+            "~/.cache/agent:/home/dungeon/.cache/agent:rw".into(),
+        ])
+    );
+    assert_eq!(settings.env_vars, Some(Vec::new()));
+}
+
+#[test]
+fn clear_entry_replaces_inherited_list() {
+    let input = TestInput {
+        toml: r#"
+[general]
+run_args = ["--cap-drop=ALL", "--pids-limit=100"]
+dynamic_ports = ["difit"]
+"#,
+        args: &["run", "--dynamic-port", "!difit", "--dynamic-port", "api"],
+        env: &[("DUNGEON_RUN_ARGS", "!*,--pids-limit=50")],
+        cwd_name: "list-replace",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let settings = resolve_input(input).resolved.settings;
+    assert_eq!(settings.run_args, Some(vec!["--pids-limit=50".to_string()]));
+    assert_eq!(settings.dynamic_ports, Some(vec!["api".to_string()]));
+}
+
+#[test]
+fn config_show_omits_removed_entries() {
+    let input = TestInput {
+        toml: r#"
+version = 2
+
+[general]
+caches = ["/var/cache/pacman/pkg", "/home/dungeon/.npm"]
+
+[rust]
+caches = ["!/home/dungeon/.npm", "/home/dungeon/.cargo/registry"]
+"#,
+        args: &["config", "show", "--groups", "rust"],
+        env: &[],
+        cwd_name: "list-removal-show",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let trace = trace_input(input);
+    let TracedSetting::List(caches) = &trace.settings["caches"] else {
+        panic!("caches should be a list");
+    };
+    let entries = caches
+        .iter()
+        .map(|entry| (entry.value.as_str().unwrap_or_default(), &entry.origin))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries[1..],
+        [(
+            "/home/dungeon/.cargo/registry",
+            &Origin::Group("rust".to_string())
+        )]
+    );
+    assert_eq!(entries[0].0, "/var/cache/pacman/pkg");
+}
@@ -1,14 +1,37 @@
 mod support;
 
 mod basic_run;
+mod conditional_groups;
+mod config_edit;
+mod config_includes;
+mod config_init;
+mod config_migrate;
 mod config_parsing;
+mod config_schema;
+mod config_show;
+mod config_validate;
+mod disabled_groups;
 mod dynamic_ports;
 mod engine;
+mod engine_backend;
 mod env_files;
 mod git_metadata;
 mod group_overrides;
+mod groups_command;
 mod image_cache;
+mod interpolation;
+mod list_removal;
+mod mount_tables;
 mod mounts_ports;
 mod network;
 mod paths;
+mod profiles;
+mod project_config;
+mod ps;
+mod sensitive_mounts;
+mod sessions;
+mod settings_model;
+mod trust;
 mod validation;
+mod workspace_modes;
+mod worktrees;
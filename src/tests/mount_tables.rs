@@ -0,0 +1,196 @@
+use crate::{
+    config::{MountEntry, MountTable},
+    tests::support::{
+        TestInput, groups_output, resolve_input, run_input, try_resolve_input, try_run_input,
+    },
+};
+
+#[test]
+fn table_mounts_check_sources_and_apply_options() {
+    let input = TestInput {
+        toml: r#"
+[general]
+mounts = [
+  "~/data:/data:ro",
+  { source = "~/notes", target = "/home/dungeon/notes", mode = "ro" },
+  { source = "~/missing", target = "/missing", optional = true },
+  { source = "~/.cache/tool", target = "/home/dungeon/.cache/tool", create = true },
+]
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "mount-tables",
+        cwd_entries: &[],
+        fs_entries: &[("home/notes", None)],
+    };
+
+    let output = run_input(input);
+    let home = output.home.display().to_string();
+    assert!(
+        output.command.contains(&format!(
+            "-v {home}/data:/data:ro -v {home}/notes:/home/dungeon/notes:ro -v {home}/.cache/tool:/home/dungeon/.cache/tool -v "
+        )),
+        "{}",
+        output.command
+    );
+    assert!(!output.command.contains("/missing"), "{}", output.command);
+    assert!(output.home.join(".cache/tool").is_dir());
+}
+
+#[test]
+fn relative_table_sources_resolve_against_the_defining_file() {
+    let input = TestInput {
+        toml: "include = [\"../../team/docs.toml\"]\n",
+        args: &["run", "--docs"],
+        env: &[],
+        cwd_name: "mount-tables",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "team/docs.toml",
+            Some(
+                "[docs]\nmounts = [{ source = \"shared\", target = \"/shared\", create = true }]\n",
+            ),
+        )],
+    };
+
+    let output = run_input(input);
+    let shared = output.root.join("team/shared");
+    assert!(
+        output
+            .command
+            .contains(&format!("-v {}:/shared ", shared.display())),
+        "{}",
+        output.command
+    );
+    assert!(shared.is_dir() && !output.cwd.join("shared").exists());
+}
+
+#[test]
+fn table_mount_failures_report_where_the_mount_was_defined() {
+    let missing = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "mount-tables",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "mount-tables/.dungeon.toml",
+            Some("[general]\nmounts = [{ source = \"notez\", target = \"/notes\" }]\n"),
+        )],
+    };
+    let message = try_run_input(missing)
+        .expect_err("missing source")
+        .to_string();
+    assert!(
+        message.starts_with("ERROR: mount source ")
+            && message
+                .contains("/mount-tables/notez does not exist (defined in general.mounts in ")
+            && message.contains("/mount-tables/.dungeon.toml); set create = true"),
+        "{message}"
+    );
+
+    let relative = TestInput {
+        toml: "[docs]\nmounts = [{ source = \"~\", target = \"docs\", optional = true }]\n",
+        args: &["run", "--docs"],
+        env: &[],
+        cwd_name: "mount-tables",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let message = try_run_input(relative)
+        .expect_err("relative target")
+        .to_string();
+    assert!(
+        message.starts_with(
+            "ERROR: mount target \"docs\" must be an absolute path (defined in docs.mounts in "
+        ) && message.ends_with("/config/dungeon/config.toml)"),
+        "{message}"
+    );
+}
+
+#[test]
+fn table_mounts_are_validated_when_parsed() {
+    for (entry, expected) in [
+        (
+            "{ source = \"~/a\", target = \"/a\", mode = \"rx\" }",
+            "general.mounts.mode must be one of: ro, rw",
+        ),
+        (
+            "{ source = \"~/a\", target = \"/a\", readonly = true }",
+            "general.mounts entry has unknown key \"readonly\"; expected one of: source, target, mode, create, optional",
+        ),
+        (
+            "{ source = \"~/a\" }",
+            "general.mounts entry needs a non-empty target",
+        ),
+        (
+            "[1]",
+            "general.mounts must be a list of strings or mount tables",
+        ),
+    ] {
+        let toml = format!("[general]\nmounts = [{}]\n", entry);
+        let input = TestInput {
+            toml: &toml,
+            args: &["run"],
+            env: &[],
+            cwd_name: "mount-tables",
+            cwd_entries: &[],
+            fs_entries: &[],
+        };
+        let message = try_resolve_input(input).expect_err(entry).to_string();
+        assert!(message.ends_with(expected), "{message}");
+    }
+}
+
+#[test]
+fn removal_entries_match_table_mounts_by_source() {
+    let toml = r#"
+[general]
+include_groups = ["notes"]
+mounts = [{ source = "~/src", target = "/src", create = true }]
+
+[notes]
+mounts = ["~/notes:/notes", { source = "~/notes", target = "/home/dungeon/notes", mode = "ro" }]
+"#;
+    let input = TestInput {
+        toml,
+        args: &["run", "--mount", "!~/notes"],
+        env: &[],
+        cwd_name: "mount-tables",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let mounts = resolve_input(input).resolved.settings.mounts;
+    let Some([MountEntry::Table(table)]) = mounts.as_deref() else {
+        panic!("expected one table mount, got {mounts:?}");
+    };
+    assert_eq!(
+        (table.source.as_str(), table.target.as_str(), table.create),
+        ("~/src", "/src", true)
+    );
+    assert!(table.defined_at().starts_with("general.mounts in "));
+    assert_eq!(
+        MountTable {
+            label: "codex.mounts".to_string(),
+            ..MountTable::default()
+        }
+        .defined_at(),
+        "codex.mounts"
+    );
+
+    let output = groups_output(TestInput {
+        toml,
+        args: &["groups", "show", "notes"],
+        env: &[],
+        cwd_name: "mount-tables",
+        cwd_entries: &[],
+        fs_entries: &[],
+    });
+    assert!(
+        output.contains(
+            "\nmounts = [\"~/notes:/notes\", { mode = \"ro\", source = \"~/notes\", target = \"/home/dungeon/notes\" }]\n"
+        ),
+        "{output}"
+    );
+}
@@ -115,3 +115,25 @@ expose_host_ports = ["8080"]
         ));
     }
 }
+
+#[test]
+fn rejects_exposed_host_ports_with_docker_engine() {
+    let input = TestInput {
+        toml: r#"
+[general]
+engine = "docker"
+expose_host_ports = ["8080"]
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "docker-exposed-host-port",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_resolve_input(input).expect_err("expected docker network error");
+    assert!(
+        err.to_string()
+            .contains("ERROR: expose_host_ports requires the podman engine")
+    );
+}
@@ -0,0 +1,219 @@
+use crate::{
+    config,
+    tests::support::{TestInput, global_settings_input, resolve_input, try_resolve_input},
+};
+
+const CONFIG: &str = r#"
+version = 2
+
+[general]
+image = "localhost/dungeon"
+mounts = ["~/notes:/home/dungeon/notes:ro"]
+include_groups = ["codex"]
+
+[tools]
+envs = ["TOOLS=personal"]
+
+[profile.work]
+image = "registry.corp/dungeon"
+podman_args = ["-c", "agent-vm"]
+mounts = ["!~/notes", "~/corp:/home/dungeon/corp:rw"]
+include_groups = ["tools", "vpn"]
+
+[profile.work.tools]
+envs = ["TOOLS=work"]
+
+[profile.work.vpn]
+envs = ["VPN=1"]
+
+[profile.personal]
+command = "pi"
+"#;
+
+#[test]
+fn profile_flag_overrides_general_and_group_set() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--profile", "work"],
+        env: &[],
+        cwd_name: "profiles",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+
+    assert_eq!(resolved.groups, vec!["tools", "vpn"]);
+    assert_eq!(
+        resolved.settings.image.as_deref(),
+        Some("registry.corp/dungeon")
+    );
+    assert_eq!(
+        resolved.settings.mounts,
+        Some(vec!["~/corp:/home/dungeon/corp:rw".into()])
+    );
+    assert_eq!(
+        resolved.settings.env_vars,
+        Some(vec!["TOOLS=work".to_string(), "VPN=1".to_string()])
+    );
+}
+
+#[test]
+fn without_a_profile_only_general_applies() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run"],
+        env: &[],
+        cwd_name: "profiles",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+
+    assert_eq!(resolved.groups, vec!["codex"]);
+    assert_eq!(
+        resolved.settings.image.as_deref(),
+        Some("localhost/dungeon")
+    );
+}
+
+#[test]
+fn profile_can_be_selected_from_environment() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run"],
+        env: &[("DUNGEON_PROFILE", "personal")],
+        cwd_name: "profiles",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(resolved.settings.command.as_deref(), Some("pi"));
+
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--profile=work"],
+        env: &[("DUNGEON_PROFILE", "personal")],
+        cwd_name: "profiles",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let overridden = resolve_input(input).resolved;
+    assert_eq!(overridden.settings.command, None);
+    assert_eq!(
+        overridden.settings.image.as_deref(),
+        Some("registry.corp/dungeon")
+    );
+}
+
+#[test]
+fn global_subcommands_use_the_selected_profile() {
+    for args in [
+        &["image", "build", "--profile", "work"][..],
+        &["--profile", "work", "cache", "reset"],
+    ] {
+        let input = TestInput {
+            toml: CONFIG,
+            args,
+            env: &[],
+            cwd_name: "profiles",
+            cwd_entries: &[],
+            fs_entries: &[],
+        };
+        let settings = global_settings_input(input).expect("global settings");
+        assert_eq!(
+            settings.podman_args,
+            Some(vec!["-c".to_string(), "agent-vm".to_string()])
+        );
+    }
+}
+
+#[test]
+fn option_values_that_look_like_the_profile_flag_are_not_a_profile() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--run-arg", "--profile", "--run-arg", "work"],
+        env: &[],
+        cwd_name: "profiles",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let resolved = resolve_input(input).resolved;
+    assert_eq!(
+        resolved.settings.image.as_deref(),
+        Some("localhost/dungeon")
+    );
+    assert_eq!(
+        resolved.settings.run_args,
+        Some(vec!["--profile".to_string(), "work".to_string()])
+    );
+}
+
+#[test]
+fn errors_on_unknown_profile() {
+    let input = TestInput {
+        toml: CONFIG,
+        args: &["run", "--profile", "home"],
+        env: &[],
+        cwd_name: "profiles",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(input).expect_err("unknown");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: unknown profile \"home\"; expected one of: personal, work"
+    );
+}
+
+#[test]
+fn validate_checks_profile_settings_and_groups() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        "[profile.work]\nimage = 1\ninclude_groups = [\"vpn\", \"missing\"]\ntypo = true\n\n[profile.work.vpn]\nenvs = [\"VPN=1\"]\nbad = 1\n",
+    )
+    .expect("write config");
+
+    let messages = config::validate_files(&[path])
+        .expect("validate")
+        .into_iter()
+        .map(|diagnostic| {
+            format!(
+                "{}:{} {}",
+                diagnostic.line, diagnostic.column, diagnostic.message
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "2:9 profile.work.image must be a string",
+            "3:26 include_groups includes unknown group \"missing\"",
+            "4:1 [profile.work] has unknown key \"typo\"",
+            "8:1 group \"vpn\" has unknown key \"bad\"",
+        ]
+    );
+}
+
+#[test]
+fn errors_on_profiles_in_project_config() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "project-profiles",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "project-profiles/.dungeon.toml",
+            Some("[profile.work]\nimage = \"x\"\n"),
+        )],
+    };
+
+    let err = try_resolve_input(input).expect_err("project profiles");
+    assert!(
+        err.to_string()
+            .ends_with("profiles can only be defined in the user config"),
+        "{err}"
+    );
+}
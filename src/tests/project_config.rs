@@ -0,0 +1,148 @@
+use crate::tests::support::{TestInput, assert_command, resolve_input, try_resolve_input};
+
+#[test]
+fn project_config_layers_between_user_file_and_env() {
+    let input = TestInput {
+        toml: r#"
+[general]
+image = "localhost/dungeon-user"
+command = "echo user"
+ports = ["127.0.0.1:3000:3000"]
+"#,
+        args: &["run", "--port", "127.0.0.1:6000:6000"],
+        env: &[
+            ("DUNGEON_PORTS", "127.0.0.1:5000:5000"),
+            ("DUNGEON_COMMAND", "echo env"),
+        ],
+        cwd_name: "project-layer",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "project-layer/.dungeon.toml",
+            Some(
+                r#"
+[general]
+image = "localhost/dungeon-project"
+command = "echo project"
+ports = ["127.0.0.1:4000:4000"]
+"#,
+            ),
+        )],
+    };
+
+    let output = resolve_input(input);
+    let settings = output.resolved.settings;
+    assert_eq!(settings.image.as_deref(), Some("localhost/dungeon-project"));
+    assert_eq!(settings.command.as_deref(), Some("echo env"));
+    assert_eq!(
+        settings.ports,
+        Some(vec![
+            "127.0.0.1:3000:3000".to_string(),
+            "127.0.0.1:4000:4000".to_string(),
+            "127.0.0.1:5000:5000".to_string(),
+            "127.0.0.1:6000:6000".to_string(),
+        ])
+    );
+}
+
+#[test]
+fn discovers_project_config_in_parent_directories() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "repo/crates/core",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "repo/.dungeon.toml",
+            Some(
+                r#"
+[general]
+caches = ["/home/dungeon/.cargo/registry"]
+"#,
+            ),
+        )],
+    };
+
+    let expected = "podman run -it --userns=keep-id -w /workspace/core --rm -v dungeon-cache:/home/dungeon/.cargo/registry -v <CWD>:/workspace/core localhost/dungeon zsh";
+
+    assert_command(input, expected);
+}
+
+#[test]
+fn project_groups_merge_with_user_and_default_groups() {
+    let input = TestInput {
+        toml: r#"
+[rust]
+caches = ["/home/dungeon/.cargo/registry"]
+"#,
+        args: &["run", "--docs"],
+        env: &[],
+        cwd_name: "project-groups",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "project-groups/.dungeon.toml",
+            Some(
+                r#"
+[general]
+include_groups = ["rust"]
+
+[rust]
+caches = ["/home/dungeon/.rustup"]
+
+[docs]
+ports = ["127.0.0.1:8000:8000"]
+
+[pi]
+"#,
+            ),
+        )],
+    };
+
+    let output = resolve_input(input);
+    let settings = output.resolved.settings;
+    assert_eq!(output.resolved.groups, vec!["rust", "docs"]);
+    assert_eq!(
+        settings.cache,
+        Some(vec!["/home/dungeon/.rustup".to_string()])
+    );
+    assert_eq!(
+        settings.ports,
+        Some(vec!["127.0.0.1:8000:8000".to_string()])
+    );
+}
+
+#[test]
+fn project_config_can_remove_default_groups() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--pi"],
+        env: &[],
+        cwd_name: "project-removes-pi",
+        cwd_entries: &[],
+        fs_entries: &[("project-removes-pi/.dungeon.toml", Some("[pi]\n"))],
+    };
+
+    let err = try_resolve_input(input).expect_err("expected removed group flag");
+    assert!(err.to_string().contains("--pi"));
+}
+
+#[test]
+fn project_config_errors_name_the_file() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "project-invalid",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "project-invalid/.dungeon.toml",
+            Some("[general]\nunknown = 1\n"),
+        )],
+    };
+
+    let err = try_resolve_input(input).expect_err("expected project config error");
+    let message = err.to_string();
+    assert!(message.contains("parse project config"));
+    assert!(message.contains("project-invalid/.dungeon.toml"));
+    assert!(message.contains("[general] has unknown key \"unknown\""));
+}
@@ -0,0 +1,167 @@
+use crate::tests::support::{TestInput, run_input};
+use crate::{
+    config,
+    container::{
+        self,
+        backend::{self, ContainerEngine},
+    },
+};
+
+#[test]
+fn labels_every_container_with_workspace_groups_image_and_version() {
+    let input = TestInput {
+        toml: r#"
+[general]
+include_groups = ["ai"]
+
+[ai]
+include_groups = ["codex"]
+"#,
+        args: &["run", "--image", "localhost/dungeon-dev"],
+        env: &[],
+        cwd_name: "labelled-project",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let output = run_input(input);
+
+    for label in [
+        "--label dungeon.managed=true".to_string(),
+        format!("--label dungeon.workspace={}", output.cwd.display()),
+        "--label dungeon.groups=codex,ai".to_string(),
+        "--label dungeon.image=localhost/dungeon-dev".to_string(),
+        format!("--label dungeon.version={}", crate::cli::build_version()),
+    ] {
+        assert!(
+            output.command.contains(&label),
+            "expected {label} in {}",
+            output.command
+        );
+    }
+    assert!(!output.command.contains("dungeon.session="));
+}
+
+#[test]
+fn list_command_filters_managed_containers_through_podman_args() {
+    let settings = config::Settings {
+        podman_args: Some(vec!["-c".to_string(), "agent-vm".to_string()]),
+        ..config::Settings::default()
+    };
+
+    let spec = container::ps::build_list_command(&settings, true);
+
+    assert_eq!(
+        format!("{} {}", spec.program, spec.args.join(" ")),
+        "podman -c agent-vm ps --all --filter label=dungeon.managed=true --format json"
+    );
+}
+
+#[test]
+fn parses_podman_container_list() {
+    let output = r#"[
+  {
+    "Id": "abc123",
+    "Names": ["dungeon-review"],
+    "Status": "Up 2 minutes",
+    "Labels": {
+      "dungeon.managed": "true",
+      "dungeon.session": "review",
+      "dungeon.workspace": "/home/me/repo",
+      "dungeon.groups": "codex,ai",
+      "dungeon.image": "localhost/dungeon",
+      "dungeon.version": "0.1.0"
+    }
+  },
+  {
+    "Id": "def456",
+    "Names": ["eager_turing"],
+    "Status": "Up 5 seconds",
+    "Labels": {
+      "dungeon.managed": "true",
+      "dungeon.workspace": "/home/me/other",
+      "dungeon.groups": "",
+      "dungeon.image": "localhost/dungeon",
+      "dungeon.version": "0.1.0"
+    }
+  }
+]"#;
+
+    let containers = backend::Podman
+        .parse_container_list(output)
+        .expect("parse podman output");
+
+    assert_eq!(containers.len(), 2);
+    assert_eq!(containers[0].session.as_deref(), Some("review"));
+    assert_eq!(containers[0].groups, vec!["codex", "ai"]);
+    assert_eq!(containers[1].session, None);
+    assert!(containers[1].groups.is_empty());
+
+    assert_eq!(
+        container::ps::render_table(&containers),
+        "NAME            SESSION  STATUS        WORKSPACE       GROUPS    IMAGE\n\
+         dungeon-review  review   Up 2 minutes  /home/me/repo   codex,ai  localhost/dungeon\n\
+         eager_turing    -        Up 5 seconds  /home/me/other  -         localhost/dungeon"
+    );
+}
+
+#[test]
+fn parses_docker_container_list() {
+    let output = concat!(
+        r#"["abc123","dungeon-review","Up 2 minutes","review","/home/me/repo","codex","localhost/dungeon","0.1.0"]"#,
+        "\n",
+    );
+
+    let containers = backend::Docker
+        .parse_container_list(output)
+        .expect("parse docker output");
+
+    assert_eq!(
+        containers,
+        vec![backend::ContainerSummary {
+            id: "abc123".to_string(),
+            name: "dungeon-review".to_string(),
+            status: "Up 2 minutes".to_string(),
+            session: Some("review".to_string()),
+            workspace: "/home/me/repo".to_string(),
+            groups: vec!["codex".to_string()],
+            image: "localhost/dungeon".to_string(),
+            version: "0.1.0".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn session_commands_only_target_containers_labelled_with_the_session() {
+    let settings = config::Settings {
+        engine: Some(config::Engine::Docker),
+        ..config::Settings::default()
+    };
+    let spec = container::ps::build_session_lookup_command(&settings, "review");
+    assert!(
+        format!("{} {}", spec.program, spec.args.join(" "))
+            .starts_with("docker ps --all --filter label=dungeon.session=review --format "),
+        "{:?}",
+        spec.args
+    );
+
+    let labelled = backend::ContainerSummary {
+        name: "dungeon-review".to_string(),
+        session: Some("review".to_string()),
+        ..backend::ContainerSummary::default()
+    };
+    let found = container::ps::find_session_container(std::slice::from_ref(&labelled), "review")
+        .expect("labelled session");
+    assert_eq!(found, &labelled);
+
+    let unlabelled = backend::ContainerSummary {
+        name: "dungeon-review".to_string(),
+        ..backend::ContainerSummary::default()
+    };
+    let err = container::ps::find_session_container(&[unlabelled], "review")
+        .expect_err("unlabelled container");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: no dungeon session \"review\"; `dungeon ps --all` lists them"
+    );
+}
@@ -0,0 +1,140 @@
+use crate::{
+    config::{MountEntry, MountTable, Settings},
+    container::engine,
+    tests::support::{TestInput, acquire_test_lock, run_input, try_run_input},
+};
+
+fn run_error(toml: &str, args: &[&str]) -> String {
+    try_run_input(TestInput {
+        toml,
+        args,
+        env: &[],
+        cwd_name: "sensitive",
+        cwd_entries: &[],
+        fs_entries: &[],
+    })
+    .expect_err("sensitive mount")
+    .to_string()
+}
+
+#[test]
+fn refuses_sensitive_mounts_and_their_parents() {
+    let message = run_error(
+        "[general]\nmounts = [\"~/.ssh:/home/dungeon/.ssh:ro\"]\n",
+        &["run"],
+    );
+    assert!(
+        message.starts_with("ERROR: refusing to mount ")
+            && message.contains("/home/.ssh:/home/dungeon/.ssh:ro: ")
+            && message.contains("/home/.ssh is the sensitive path ")
+            && message
+                .ends_with("; add it to allow_sensitive_paths or pass --allow-sensitive-mount"),
+        "{message}"
+    );
+
+    let message = run_error("[general]\nmounts = [\"~:/home/dungeon/host\"]\n", &["run"]);
+    assert!(
+        message.contains("/home contains the sensitive path ") && message.contains("/home/.ssh;"),
+        "{message}"
+    );
+
+    let message = run_error(
+        "[general]\nmounts = [\"/var/run/docker.sock:/var/run/docker.sock\"]\n",
+        &["run"],
+    );
+    assert!(
+        message.starts_with("ERROR: refusing to mount /var/run/docker.sock:/var/run/docker.sock: "),
+        "{message}"
+    );
+
+    let message = run_error("", &["run", "../home"]);
+    assert!(
+        message.starts_with("ERROR: refusing to mount ../home: ")
+            && message.contains("/home contains the sensitive path "),
+        "{message}"
+    );
+
+    let message = run_error(
+        "[general]\nmounts = [{ source = \"~/.gnupg/keys\", target = \"/keys\", create = true }]\n",
+        &["run"],
+    );
+    assert!(
+        message
+            .starts_with("ERROR: refusing to mount ~/.gnupg/keys (defined in general.mounts in ")
+            && message.contains("/home/.gnupg/keys is inside the sensitive path "),
+        "{message}"
+    );
+}
+
+#[test]
+fn allowlist_and_flag_override_the_denylist() {
+    let input = TestInput {
+        toml: r#"
+[general]
+mounts = ["~/.ssh/known_hosts:/home/dungeon/.ssh/known_hosts:ro", "named-volume:/data"]
+allow_sensitive_paths = ["~/.ssh/known_hosts"]
+"#,
+        args: &["run"],
+        env: &[],
+        cwd_name: "sensitive",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let output = run_input(input);
+    assert!(
+        output.command.contains(
+            "/home/.ssh/known_hosts:/home/dungeon/.ssh/known_hosts:ro -v named-volume:/data"
+        ),
+        "{}",
+        output.command
+    );
+
+    let input = TestInput {
+        toml: "[general]\nmounts = [\"~:/home/dungeon/host\"]\n",
+        args: &["run", "--allow-sensitive-mount"],
+        env: &[],
+        cwd_name: "sensitive",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+    let output = run_input(input);
+    assert!(
+        output
+            .command
+            .contains(&format!("-v {}:/home/dungeon/host", output.home.display())),
+        "{}",
+        output.command
+    );
+}
+
+#[test]
+fn configured_sensitive_paths_catch_symlinks_into_them() {
+    let _guard = acquire_test_lock();
+    let dir = tempfile::tempdir().expect("tempdir");
+    let secret = dir.path().join("secret");
+    let link = dir.path().join("link");
+    std::fs::create_dir_all(&secret).expect("create secret");
+    std::os::unix::fs::symlink(&secret, &link).expect("symlink");
+
+    let settings = Settings {
+        mounts: Some(vec![MountEntry::Table(MountTable {
+            source: link.display().to_string(),
+            target: "/data".to_string(),
+            label: "general.mounts".to_string(),
+            ..MountTable::default()
+        })]),
+        sensitive_paths: Some(vec![secret.display().to_string()]),
+        ..Settings::default()
+    };
+
+    let err = engine::resolve_run_session(&settings, &[], true, false).expect_err("symlink");
+    assert!(
+        err.to_string().contains(&format!(
+            "(defined in general.mounts): {} is the sensitive path {};",
+            secret.canonicalize().expect("canonical").display(),
+            secret.canonicalize().expect("canonical").display()
+        )),
+        "{err}"
+    );
+    assert!(engine::resolve_run_session(&settings, &[], true, true).is_ok());
+}
@@ -0,0 +1,131 @@
+use crate::tests::support::{TestInput, assert_command, run_input, try_run_input};
+use crate::{cli, config, container};
+
+#[test]
+fn named_detached_session_keeps_container() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--name", "agent", "--detach"],
+        env: &[],
+        cwd_name: "named-session-project",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let expected = "podman run -d -it --userns=keep-id -w /workspace/named-session-project --name dungeon-agent -v <CWD>:/workspace/named-session-project localhost/dungeon zsh";
+
+    assert_command(input, expected);
+}
+
+#[test]
+fn named_session_runs_attached_without_detach() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--name", "agent"],
+        env: &[],
+        cwd_name: "named-attached-project",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let output = run_input(input);
+
+    assert!(
+        output
+            .command
+            .contains("--name dungeon-agent --label dungeon.managed=true")
+    );
+    assert!(output.command.contains("--label dungeon.session=agent"));
+    assert!(!output.command.contains("--rm"));
+    assert!(!output.command.contains(" -d "));
+}
+
+#[test]
+fn detach_requires_a_session_name() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--detach"],
+        env: &[],
+        cwd_name: "detach-without-name",
+        cwd_entries: &[],
+        fs_entries: &[],
+    };
+
+    let err = try_run_input(input).expect_err("expected detach error");
+    assert!(err.to_string().contains("ERROR: --detach requires --name"));
+}
+
+#[test]
+fn rejects_invalid_session_names() {
+    for name in ["", ".agent", "my agent", "a/b"] {
+        let result = cli::parse_args_with_sources(
+            vec!["attach".to_string(), name.to_string()],
+            &config::Config::default(),
+            &config::Config::default(),
+            &config::Config::default(),
+        );
+        let err = result.expect_err("expected invalid session name");
+        assert!(
+            err.to_string().contains("ERROR: invalid session name"),
+            "unexpected error for {name:?}: {err}"
+        );
+    }
+}
+
+#[test]
+fn session_subcommands_target_managed_container() {
+    let cases: &[(&[&str], &str)] = &[
+        (&["attach", "agent"], "podman attach dungeon-agent"),
+        (
+            &["exec", "agent"],
+            "podman exec -it --user dungeon dungeon-agent zsh",
+        ),
+        (
+            &["exec", "agent", "--command", "git status"],
+            "podman exec -it --user dungeon dungeon-agent zsh -ic git status",
+        ),
+        (&["stop", "agent"], "podman stop dungeon-agent"),
+        (&["rm", "agent"], "podman rm dungeon-agent"),
+        (
+            &[
+                "rm",
+                "agent",
+                "--force",
+                "--podman-arg=-c",
+                "--podman-arg=vm",
+            ],
+            "podman -c vm rm -f dungeon-agent",
+        ),
+    ];
+
+    for (args, expected) in cases {
+        let sources = config::LoadedConfigSources::default();
+        let argv = args.iter().map(|arg| arg.to_string()).collect();
+        let parsed = cli::parse_args_with_loaded_sources(argv, &sources).expect("parse args");
+        let settings =
+            config::resolve_global_settings(&parsed.settings, &sources).expect("resolve settings");
+
+        let spec = match &parsed.action {
+            cli::Action::Attach(action) => {
+                container::engine::build_attach_command(&settings, &action.session)
+            }
+            cli::Action::Exec(action) => container::engine::build_exec_command(
+                &settings,
+                &action.session,
+                action.command.as_deref(),
+            ),
+            cli::Action::Stop(action) => {
+                container::engine::build_stop_command(&settings, &action.session)
+            }
+            cli::Action::Remove(action) => {
+                container::engine::build_remove_command(&settings, &action.session, action.force)
+            }
+            _ => panic!("expected session action for {args:?}"),
+        };
+
+        assert_eq!(
+            format!("{} {}", spec.program, spec.args.join(" ")),
+            *expected
+        );
+    }
+}
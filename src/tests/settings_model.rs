@@ -0,0 +1,101 @@
+use crate::{
+    config::{SETTING_FIELDS, SettingField, SettingKind},
+    tests::support::{TestInput, resolve_input, try_run_input},
+};
+
+fn sample_value(field: &SettingField) -> &'static str {
+    match field.key {
+        "engine" => "docker",
+        "workspace_mode" => "ro",
+        "expose_host_ports" => "8080",
+        _ => "sample",
+    }
+}
+
+fn expected_value(field: &SettingField) -> toml::Value {
+    let value = toml::Value::String(sample_value(field).to_string());
+    match field.kind {
+        SettingKind::Scalar => value,
+        SettingKind::List => toml::Value::Array(vec![value]),
+        SettingKind::Flag => toml::Value::Boolean(true),
+    }
+}
+
+#[test]
+fn every_setting_is_supported_in_toml_env_and_cli() {
+    for field in SETTING_FIELDS {
+        let expected = expected_value(field);
+        let toml = format!("[general]\n{} = {}\n", field.key, expected);
+        let env_var = field.env_var();
+        let env_value = match field.kind {
+            SettingKind::Flag => "true",
+            _ => sample_value(field),
+        };
+        let flag = format!("--{}", field.flag);
+        let cli_args = match field.kind {
+            SettingKind::Flag => vec!["run", flag.as_str()],
+            _ => vec!["run", flag.as_str(), sample_value(field)],
+        };
+
+        let env = [(env_var.as_str(), env_value)];
+        let inputs = [
+            (toml.as_str(), &["run"][..], &[][..]),
+            ("", &["run"][..], &env[..]),
+            ("", &cli_args[..], &[][..]),
+        ];
+        for (toml, args, env) in inputs {
+            let output = resolve_input(TestInput {
+                toml,
+                args,
+                env,
+                cwd_name: "settings-model",
+                cwd_entries: &[],
+                fs_entries: &[],
+            });
+            assert_eq!(
+                output.resolved.settings.get(field.key),
+                Some(expected.clone()),
+                "{} via toml={:?} args={:?} env={:?}",
+                field.key,
+                toml,
+                args,
+                env
+            );
+        }
+    }
+}
+
+#[test]
+fn selects_engine_from_cli_flag() {
+    let output = resolve_input(TestInput {
+        toml: "",
+        args: &["run", "--engine", "docker", "--image", "localhost/dungeon"],
+        env: &[("DUNGEON_ENGINE", "podman")],
+        cwd_name: "engine-flag",
+        cwd_entries: &[],
+        fs_entries: &[],
+    });
+
+    assert_eq!(
+        output.resolved.settings.engine,
+        Some(crate::config::Engine::Docker)
+    );
+}
+
+#[test]
+fn rejects_groups_named_after_setting_flags() {
+    let err = try_run_input(TestInput {
+        toml: "[engine]\nimage = \"localhost/dungeon\"\n",
+        args: &["run"],
+        env: &[],
+        cwd_name: "setting-flag-group",
+        cwd_entries: &[],
+        fs_entries: &[],
+    })
+    .expect_err("reserved group name");
+
+    assert_eq!(
+        err.to_string(),
+        "ERROR: group name 'engine' conflicts with a reserved CLI flag"
+    );
+}
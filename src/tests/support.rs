@@ -49,6 +49,7 @@ pub fn try_run_input(input: TestInput<'_>) -> Result<TestOutput, AppError> {
     let cwd = temp_dir.path().join(input.cwd_name);
     let home = temp_dir.path().join("home");
     let config_home = temp_dir.path().join("config");
+    let data_home = temp_dir.path().join("data");
     let config_path = config_home.join("dungeon").join("config.toml");
 
     std::fs::create_dir_all(&cwd).expect("create cwd");
@@ -58,12 +59,13 @@ pub fn try_run_input(input: TestInput<'_>) -> Result<TestOutput, AppError> {
     create_cwd_entries(&cwd, input.cwd_entries).expect("create entries");
     create_fs_entries(temp_dir.path(), input.fs_entries).expect("create fs entries");
 
-    let _env_guard = EnvGuard::new(&home, &config_home, input.env);
+    let _env_guard = EnvGuard::new(&home, &config_home, &data_home, input.env);
 
     if !input.toml.trim().is_empty() {
         std::fs::write(&config_path, input.toml).expect("write config");
     }
 
+    trust_project_entries(temp_dir.path(), input.fs_entries)?;
     let command = with_cwd(&cwd, || build_command_string(input))?;
 
     Ok(TestOutput {
@@ -75,11 +77,95 @@ pub fn try_run_input(input: TestInput<'_>) -> Result<TestOutput, AppError> {
 }
 
 pub fn try_resolve_input(input: TestInput<'_>) -> Result<ResolvedTestOutput, AppError> {
+    let entries = input.fs_entries;
+    try_resolve_input_with(input, |root| trust_project_entries(root, entries))
+}
+
+pub fn try_resolve_input_with(
+    input: TestInput<'_>,
+    prepare: impl FnOnce(&Path) -> Result<(), AppError>,
+) -> Result<ResolvedTestOutput, AppError> {
+    let resolved = try_in_test_env(input, prepare, resolve_settings)?;
+    Ok(ResolvedTestOutput { resolved })
+}
+
+pub fn trace_input(input: TestInput<'_>) -> config::SettingsTrace {
+    try_trace_input(input).expect("trace input")
+}
+
+pub fn try_trace_input(input: TestInput<'_>) -> Result<config::SettingsTrace, AppError> {
+    let entries = input.fs_entries;
+    try_in_test_env(
+        input,
+        |root| trust_project_entries(root, entries),
+        trace_settings,
+    )
+}
+
+pub fn explain_input(input: TestInput<'_>) -> Vec<config::GroupExplanation> {
+    let entries = input.fs_entries;
+    try_in_test_env(
+        input,
+        |root| trust_project_entries(root, entries),
+        explain_groups,
+    )
+    .expect("explain input")
+}
+
+pub fn global_settings_input(input: TestInput<'_>) -> Result<config::Settings, AppError> {
+    let entries = input.fs_entries;
+    try_in_test_env(
+        input,
+        |root| trust_project_entries(root, entries),
+        resolve_global,
+    )
+}
+
+pub fn config_warnings(input: TestInput<'_>) -> Vec<String> {
+    let entries = input.fs_entries;
+    try_in_test_env(
+        input,
+        |root| trust_project_entries(root, entries),
+        |_| Ok(config::load_sources()?.warnings()),
+    )
+    .expect("config warnings")
+}
+
+pub fn groups_output(input: TestInput<'_>) -> String {
+    try_groups_output(input).expect("groups output")
+}
+
+pub fn try_groups_output(input: TestInput<'_>) -> Result<String, AppError> {
+    let entries = input.fs_entries;
+    try_in_test_env(
+        input,
+        |root| trust_project_entries(root, entries),
+        render_groups,
+    )
+}
+
+pub fn in_test_env<T>(
+    input: TestInput<'_>,
+    run: impl FnOnce(&Path) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    try_in_test_env(
+        input,
+        |_| Ok(()),
+        |_| run(&env::current_dir().map_err(AppError::Io)?),
+    )
+}
+
+fn try_in_test_env<'a, T>(
+    input: TestInput<'a>,
+    prepare: impl FnOnce(&Path) -> Result<(), AppError>,
+    run: impl FnOnce(TestInput<'a>) -> Result<T, AppError>,
+) -> Result<T, AppError> {
     let _guard = acquire_test_lock();
     let temp_dir = tempfile::tempdir().expect("tempdir");
     let cwd = temp_dir.path().join(input.cwd_name);
     let home = temp_dir.path().join("home");
     let config_home = temp_dir.path().join("config");
+    let data_home = temp_dir.path().join("data");
     let config_path = config_home.join("dungeon").join("config.toml");
 
     std::fs::create_dir_all(&cwd).expect("create cwd");
@@ -89,38 +175,79 @@ pub fn try_resolve_input(input: TestInput<'_>) -> Result<ResolvedTestOutput, App
     create_cwd_entries(&cwd, input.cwd_entries).expect("create entries");
     create_fs_entries(temp_dir.path(), input.fs_entries).expect("create fs entries");
 
-    let _env_guard = EnvGuard::new(&home, &config_home, input.env);
+    let _env_guard = EnvGuard::new(&home, &config_home, &data_home, input.env);
 
     if !input.toml.trim().is_empty() {
         std::fs::write(&config_path, input.toml).expect("write config");
     }
 
-    let resolved = with_cwd(&cwd, || resolve_settings(input))?;
-
-    Ok(ResolvedTestOutput { resolved })
+    prepare(temp_dir.path())?;
+    with_cwd(&cwd, || run(input))
 }
 
 fn build_command_string(input: TestInput<'_>) -> Result<String, AppError> {
     let resolved = resolve_settings(input)?;
 
-    let spec = container::engine::build_container_command(
-        &resolved.settings,
-        &resolved.paths,
-        resolved.skip_cwd,
-    )?;
+    let spec =
+        container::engine::build_resolved_container_command(&resolved.settings, &resolved, None)?;
 
     Ok(format!("{} {}", spec.program, spec.args.join(" ")))
 }
 
-fn resolve_settings(input: TestInput<'_>) -> Result<config::ResolvedConfig, AppError> {
-    let sources = config::load_sources()?;
+/// Loads the config sources the way `app::run` does and parses `args` against
+/// them.
+pub fn parse_args(
+    args: &[&str],
+) -> Result<(cli::ParsedCLI, config::LoadedConfigSources), AppError> {
+    let argv = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let sources = config::load_sources_for_profile(cli::profile_from_args(&argv).as_deref())?;
+    let parsed = cli::parse_args_with_loaded_sources(argv, &sources)?;
+    Ok((parsed, sources))
+}
 
-    let argv = input.args.iter().map(|arg| arg.to_string()).collect();
-    let parsed =
-        cli::parse_args_with_sources(argv, &sources.defaults, &sources.file, &sources.env)?;
+fn resolve_settings(input: TestInput<'_>) -> Result<config::ResolvedConfig, AppError> {
+    let (parsed, sources) = parse_args(input.args)?;
     config::resolve(&parsed, &sources)
 }
 
+fn explain_groups(input: TestInput<'_>) -> Result<Vec<config::GroupExplanation>, AppError> {
+    let (parsed, sources) = parse_args(input.args)?;
+    config::explain_groups(&parsed, &sources)
+}
+
+fn resolve_global(input: TestInput<'_>) -> Result<config::Settings, AppError> {
+    let (parsed, sources) = parse_args(input.args)?;
+    config::resolve_global_settings(&parsed.settings, &sources)
+}
+
+fn render_groups(input: TestInput<'_>) -> Result<String, AppError> {
+    let (parsed, sources) = parse_args(input.args)?;
+    match &parsed.action {
+        cli::Action::GroupsList => Ok(config::render_group_list(&sources.group_infos()?)),
+        cli::Action::GroupsShow(action) => {
+            let info = sources
+                .group_infos()?
+                .into_iter()
+                .find(|info| info.name == action.name)
+                .ok_or_else(|| AppError::message("unknown group"))?;
+            config::render_group(&info, &sources.group_definitions()?)
+        }
+        cli::Action::GroupsGraph(action) => Ok(config::render_group_graph(
+            &sources.group_definitions()?,
+            action.format,
+        )),
+        _ => Err(AppError::message("expected groups subcommand")),
+    }
+}
+
+fn trace_settings(input: TestInput<'_>) -> Result<config::SettingsTrace, AppError> {
+    let (parsed, sources) = parse_args(input.args)?;
+    let cli::Action::ConfigShow(action) = &parsed.action else {
+        return Err(AppError::message("expected config show"));
+    };
+    config::trace(&action.groups, &parsed.settings, &sources)
+}
+
 pub fn acquire_test_lock() -> std::sync::MutexGuard<'static, ()> {
     static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
     LOCK.get_or_init(|| Mutex::new(()))
@@ -129,13 +256,34 @@ pub fn acquire_test_lock() -> std::sync::MutexGuard<'static, ()> {
 }
 
 fn normalize_command(command: &str, cwd: &Path, home: &Path, root: &Path) -> String {
-    command
+    strip_dungeon_args(command)
         .replace(cwd.to_string_lossy().as_ref(), "<CWD>")
         .replace(home.to_string_lossy().as_ref(), "<HOME>")
         .replace(root.to_string_lossy().as_ref(), "<TMP>")
         .replace("--user root ", "")
 }
 
+fn strip_dungeon_args(command: &str) -> String {
+    let mut kept = Vec::new();
+    let mut parts = command.split(' ').peekable();
+    while let Some(part) = parts.next() {
+        if (part == "--label"
+            && parts
+                .peek()
+                .is_some_and(|next| next.starts_with("dungeon.")))
+            || (part == "--env"
+                && parts.peek().is_some_and(|next| {
+                    next.starts_with("DUNGEON_UID=") || next.starts_with("DUNGEON_GID=")
+                }))
+        {
+            parts.next();
+            continue;
+        }
+        kept.push(part);
+    }
+    kept.join(" ")
+}
+
 fn create_cwd_entries(cwd: &Path, entries: &[&str]) -> Result<(), std::io::Error> {
     for entry in entries {
         let trimmed = entry.trim();
@@ -183,6 +331,15 @@ fn create_fs_entries(
     Ok(())
 }
 
+fn trust_project_entries(root: &Path, entries: &[(&str, Option<&str>)]) -> Result<(), AppError> {
+    for (path, contents) in entries {
+        if contents.is_some() && path.ends_with(".dungeon.toml") {
+            config::trust_project_config(&root.join(path))?;
+        }
+    }
+    Ok(())
+}
+
 fn with_cwd<T>(cwd: &Path, f: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
     let original = env::current_dir().map_err(AppError::Io)?;
     env::set_current_dir(cwd).map_err(AppError::Io)?;
@@ -196,7 +353,7 @@ struct EnvGuard {
 }
 
 impl EnvGuard {
-    fn new(home: &Path, config_home: &Path, vars: &[(&str, &str)]) -> Self {
+    fn new(home: &Path, config_home: &Path, data_home: &Path, vars: &[(&str, &str)]) -> Self {
         let mut previous = BTreeMap::new();
         let mut set_var = |key: &str, value: Option<&str>| {
             previous.insert(key.to_string(), env::var(key).ok());
@@ -214,7 +371,11 @@ impl EnvGuard {
             "XDG_CONFIG_HOME",
             Some(config_home.to_string_lossy().as_ref()),
         );
+        set_var("XDG_DATA_HOME", Some(data_home.to_string_lossy().as_ref()));
 
+        for field in config::SETTING_FIELDS {
+            set_var(&field.env_var(), None);
+        }
         for key in DUNGEON_ENV_KEYS {
             set_var(key, None);
         }
@@ -241,21 +402,11 @@ impl Drop for EnvGuard {
     }
 }
 
+// Settings variables come from `SETTING_FIELDS`; these are the others that
+// change how dungeon loads its config or reaches the engine.
 const DUNGEON_ENV_KEYS: &[&str] = &[
     "CONTAINER_CONNECTION",
     "CONTAINER_HOST",
-    "DUNGEON_ENGINE",
-    "DUNGEON_COMMAND",
-    "DUNGEON_IMAGE",
-    "DUNGEON_PORTS",
-    "DUNGEON_DYNAMIC_PORTS",
-    "DUNGEON_EXPOSE_HOST_PORTS",
-    "DUNGEON_CACHES",
-    "DUNGEON_MOUNTS",
-    "DUNGEON_ENVS",
-    "DUNGEON_ENV_FILES",
-    "DUNGEON_PODMAN_ARGS",
-    "DUNGEON_RUN_ARGS",
-    "DUNGEON_MOUNT_GIT_METADATA",
     "DUNGEON_INCLUDE_GROUPS",
+    "DUNGEON_PROFILE",
 ];
@@ -0,0 +1,141 @@
+use crate::{
+    config,
+    tests::support::{TestInput, try_resolve_input_with},
+};
+
+const PROJECT_TOML: &str = r#"
+[general]
+mounts = ["~/.ssh:/home/dungeon/.ssh"]
+run_args = ["--privileged"]
+"#;
+
+#[test]
+fn refuses_untrusted_project_config() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "untrusted",
+        cwd_entries: &[],
+        fs_entries: &[("untrusted/.dungeon.toml", Some(PROJECT_TOML))],
+    };
+    let err = try_resolve_input_with(input, |_| Ok(())).expect_err("untrusted project config");
+    let message = err.to_string();
+    assert!(message.contains("untrusted/.dungeon.toml is not trusted"));
+    assert!(message.contains(r#"general.mounts = ["~/.ssh:/home/dungeon/.ssh"]"#));
+    assert!(message.contains(r#"general.run_args = ["--privileged"]"#));
+    assert!(message.contains("dungeon trust"));
+}
+
+#[test]
+fn untrusted_summary_lists_auto_enabling_groups() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "untrusted",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "untrusted/.dungeon.toml",
+            Some(
+                r#"
+[secrets]
+when = { file_exists = ["Cargo.toml"], git_remote = ["*github.com*"] }
+mounts = ["~/.aws:/home/dungeon/.aws:ro"]
+"#,
+            ),
+        )],
+    };
+    let err = try_resolve_input_with(input, |_| Ok(())).expect_err("untrusted project config");
+    let message = err.to_string();
+    assert!(
+        message.contains(
+            r#"  secrets.when.file_exists = ["Cargo.toml"]  (enables secrets automatically)
+  secrets.when.git_remote = ["*github.com*"]  (enables secrets automatically)
+  secrets.mounts = ["~/.aws:/home/dungeon/.aws:ro"]"#
+        ),
+        "{message}"
+    );
+}
+
+#[test]
+fn refuses_untrusted_project_config_before_unknown_group_flags() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--rust"],
+        env: &[],
+        cwd_name: "untrusted",
+        cwd_entries: &[],
+        fs_entries: &[(
+            "untrusted/.dungeon.toml",
+            Some("[rust]\ncaches = [\"/home/dungeon/.cargo/registry\"]\n"),
+        )],
+    };
+
+    let err = try_resolve_input_with(input, |_| Ok(())).expect_err("untrusted project config");
+    assert!(err.to_string().contains("is not trusted"));
+    assert!(err.to_string().contains("rust.caches"));
+}
+
+#[test]
+fn refuses_project_config_changed_since_trusted() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "untrusted",
+        cwd_entries: &[],
+        fs_entries: &[("untrusted/.dungeon.toml", Some(PROJECT_TOML))],
+    };
+    let err = try_resolve_input_with(input, |root| {
+        let path = root.join("untrusted/.dungeon.toml");
+        std::fs::write(&path, "[general]\nimage = \"localhost/other\"\n").expect("write");
+        config::trust_project_config(&path)?;
+        std::fs::write(&path, PROJECT_TOML).expect("rewrite");
+        Ok(())
+    })
+    .expect_err("changed project config");
+    let message = err.to_string();
+    assert!(message.contains("has changed since it was trusted"));
+    assert!(message.contains("--privileged"));
+}
+
+#[test]
+fn applies_project_config_once_trusted() {
+    let input = TestInput {
+        toml: "",
+        args: &["run"],
+        env: &[],
+        cwd_name: "untrusted",
+        cwd_entries: &[],
+        fs_entries: &[("untrusted/.dungeon.toml", Some(PROJECT_TOML))],
+    };
+    let output = try_resolve_input_with(input, |root| {
+        config::trust_project_config(&root.join("untrusted/.dungeon.toml"))
+    })
+    .expect("trusted project config");
+    assert_eq!(
+        output.resolved.settings.run_args,
+        Some(vec!["--privileged".to_string()])
+    );
+}
+
+#[test]
+fn trust_subcommand_skips_trust_check() {
+    let sources = config::LoadedConfigSources {
+        untrusted_project: Some(config::UntrustedProject {
+            path: "/repo/.dungeon.toml".into(),
+            config: config::Config::default(),
+            state: config::TrustState::Unknown,
+        }),
+        ..config::LoadedConfigSources::default()
+    };
+
+    let parsed = crate::cli::parse_args_with_loaded_sources(vec!["trust".to_string()], &sources)
+        .expect("parse trust");
+    assert!(matches!(parsed.action, crate::cli::Action::Trust));
+
+    let err = crate::cli::parse_args_with_loaded_sources(vec!["ps".to_string()], &sources)
+        .expect_err("untrusted ps");
+    assert!(err.to_string().contains("It does not add any settings."));
+}
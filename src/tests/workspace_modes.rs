@@ -0,0 +1,259 @@
+use std::fs;
+
+use crate::{
+    cli::{self, Action},
+    config::{self, WorkspaceMode},
+    container::workspace,
+    tests::support::{TestInput, assert_command, in_test_env, run_input, try_run_input},
+};
+
+#[test]
+fn workspace_modes_change_the_workspace_mount() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--workspace-mode", "ro"],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    assert_command(
+        input,
+        "podman run -it --userns=keep-id -w /workspace/workspace-mode --rm -v <CWD>:/workspace/workspace-mode:ro localhost/dungeon zsh",
+    );
+
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--workspace-mode", "overlay"],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    let output = run_input(input);
+    let upper = output.root.join("data/dungeon/workspaces");
+    assert!(
+        output.command.contains(&format!(
+            "-v {}:/workspace/workspace-mode:O,upperdir={}/",
+            output.cwd.display(),
+            upper.display()
+        )) && output.command.contains("/upper,workdir="),
+        "{}",
+        output.command
+    );
+
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--workspace-mode", "snapshot"],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    let output = run_input(input);
+    assert!(
+        output.command.contains(&format!(
+            "-v {}/",
+            output.root.join("data/dungeon/workspaces").display()
+        )) && output.command.contains("/tree:/workspace/workspace-mode "),
+        "{}",
+        output.command
+    );
+    assert!(
+        !upper.exists(),
+        "building the command must not create state"
+    );
+
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--workspace-mode", "overlay", "--engine", "docker"],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    let err = try_run_input(input).expect_err("overlay with docker");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: workspace_mode = \"overlay\" needs podman; use \"snapshot\" with docker"
+    );
+
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--workspace-mode", "snapshot", "notes.txt"],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    let err = try_run_input(input).expect_err("snapshot of a file");
+    assert!(
+        err.to_string()
+            .starts_with("ERROR: workspace_mode = \"snapshot\" needs a directory, but "),
+        "{err}"
+    );
+}
+
+#[test]
+fn snapshot_changes_are_listed_and_applied() {
+    let input = TestInput {
+        toml: "",
+        args: &[],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    in_test_env(input, |cwd| {
+        fs::create_dir(cwd.join("logs"))?;
+        workspace::prepare(cwd, WorkspaceMode::Snapshot)?;
+        let tree = workspace::snapshot_tree(cwd)?;
+        assert_eq!(fs::read_to_string(tree.join("notes.txt"))?, "test");
+
+        fs::write(tree.join("notes.txt"), "edited")?;
+        fs::write(tree.join("new.txt"), "new")?;
+        fs::remove_file(tree.join("src/main.rs"))?;
+        fs::remove_dir(tree.join("logs"))?;
+        fs::create_dir(tree.join("build"))?;
+        workspace::prepare(cwd, WorkspaceMode::Snapshot)?;
+        assert_eq!(fs::read_to_string(tree.join("notes.txt"))?, "edited");
+
+        let changes = workspace::changes(cwd)?
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "A build",
+                "D logs",
+                "A new.txt",
+                "M notes.txt",
+                "D src/main.rs"
+            ]
+        );
+
+        fs::write(cwd.join("notes.txt"), "host edit")?;
+        let err = workspace::apply(cwd).expect_err("conflict");
+        assert!(
+            err.to_string().ends_with(
+                "changed on the host since the snapshot was taken: notes.txt; resolve them or run `dungeon apply --discard`"
+            ),
+            "{err}"
+        );
+
+        fs::write(cwd.join("notes.txt"), "test")?;
+        assert_eq!(workspace::apply(cwd)?.len(), 5);
+        assert!(cwd.join("build").is_dir() && !cwd.join("logs").exists());
+        assert_eq!(fs::read_to_string(cwd.join("notes.txt"))?, "edited");
+        assert_eq!(fs::read_to_string(cwd.join("new.txt"))?, "new");
+        assert!(!cwd.join("src/main.rs").exists());
+        assert!(!tree.exists());
+
+        let err = workspace::changes(cwd).expect_err("no state");
+        assert!(
+            err.to_string().starts_with("ERROR: no overlay or snapshot workspace for "),
+            "{err}"
+        );
+        Ok(())
+    })
+    .expect("snapshot workflow");
+}
+
+#[test]
+fn overlay_upper_dir_entries_become_changes() {
+    let input = TestInput {
+        toml: "",
+        args: &[],
+        env: &[],
+        cwd_name: "workspace-mode",
+        cwd_entries: &["notes.txt", "src/main.rs"],
+        fs_entries: &[],
+    };
+    in_test_env(input, |cwd| {
+        fs::create_dir_all(cwd.join("old"))?;
+        fs::write(cwd.join("old/a.txt"), "a")?;
+        fs::write(cwd.join("old/b.txt"), "b")?;
+        fs::create_dir_all(cwd.join("gen"))?;
+        fs::write(cwd.join("gen/x.txt"), "x")?;
+
+        workspace::prepare(cwd, WorkspaceMode::Overlay)?;
+        let spec = workspace::overlay_mount(cwd, "/workspace/workspace-mode")?;
+        let upper = spec
+            .split_once("upperdir=")
+            .and_then(|(_, rest)| rest.split_once(",workdir="))
+            .map(|(upper, _)| std::path::PathBuf::from(upper))
+            .expect("upperdir");
+        fs::write(upper.join("notes.txt"), "test")?;
+        fs::create_dir_all(upper.join("src"))?;
+        fs::write(upper.join("src/main.rs"), "fn main() {}")?;
+        fs::write(upper.join("src/lib.rs"), "")?;
+        fs::write(upper.join(".wh.notes-old.txt"), "")?;
+        fs::create_dir_all(upper.join("old"))?;
+        fs::write(upper.join("old/.wh..wh..opq"), "")?;
+        fs::write(upper.join("old/b.txt"), "b2")?;
+        fs::create_dir_all(upper.join("gen"))?;
+        xattr::set(upper.join("gen"), "user.overlay.opaque", b"y")?;
+        fs::write(upper.join("gen/y.txt"), "y")?;
+        fs::create_dir_all(upper.join("cache"))?;
+
+        let changes = workspace::changes(cwd)?
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "A cache",
+                "D gen/x.txt",
+                "A gen/y.txt",
+                "D notes-old.txt",
+                "D old/a.txt",
+                "M old/b.txt",
+                "A src/lib.rs",
+                "M src/main.rs",
+            ]
+        );
+
+        let err = workspace::prepare(cwd, WorkspaceMode::Snapshot).expect_err("mode switch");
+        assert!(
+            err.to_string()
+                .contains("has pending changes from another workspace_mode"),
+            "{err}"
+        );
+
+        workspace::apply(cwd)?;
+        assert!(!cwd.join("old/a.txt").exists());
+        assert!(!cwd.join("gen/x.txt").exists() && cwd.join("gen/y.txt").is_file());
+        assert!(cwd.join("cache").is_dir());
+        assert_eq!(fs::read_to_string(cwd.join("old/b.txt"))?, "b2");
+        assert_eq!(fs::read_to_string(cwd.join("src/main.rs"))?, "fn main() {}");
+        assert!(!upper.exists());
+        Ok(())
+    })
+    .expect("overlay workflow");
+}
+
+#[test]
+fn diff_and_apply_parse_workspaces_without_trust() {
+    let sources = config::LoadedConfigSources {
+        untrusted_project: Some(config::UntrustedProject {
+            path: "/repo/.dungeon.toml".into(),
+            config: config::Config::default(),
+            state: config::TrustState::Unknown,
+        }),
+        ..config::LoadedConfigSources::default()
+    };
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    let parsed = cli::parse_args_with_loaded_sources(args(&["diff"]), &sources).expect("diff");
+    assert!(matches!(&parsed.action, Action::Diff(action) if action.paths.is_empty()));
+
+    let parsed =
+        cli::parse_args_with_loaded_sources(args(&["apply", "app", "--discard"]), &sources)
+            .expect("apply");
+    assert!(matches!(
+        &parsed.action,
+        Action::Apply(action) if action.paths == ["app"] && action.discard
+    ));
+}
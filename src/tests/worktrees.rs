@@ -0,0 +1,197 @@
+use std::{path::Path, process::Command};
+
+use crate::{
+    config,
+    container::{backend::ContainerSummary, engine, worktree::Worktree},
+    tests::support::{TestInput, in_test_env, parse_args, try_resolve_input},
+};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "-c",
+            "user.name=dungeon",
+            "-c",
+            "user.email=dungeon@example.com",
+        ])
+        .args(args)
+        .output()
+        .expect("run git")
+        .status;
+    assert!(status.success(), "git {}", args.join(" "));
+}
+
+#[test]
+fn worktree_runs_mount_a_fresh_checkout_with_git_metadata() {
+    let input = TestInput {
+        toml: "",
+        args: &[],
+        env: &[],
+        cwd_name: "worktree-repo",
+        cwd_entries: &["README.md"],
+        fs_entries: &[],
+    };
+    in_test_env(input, |cwd| {
+        git(cwd, &["init", "-q", "-b", "main"]);
+        git(cwd, &["add", "README.md"]);
+        git(cwd, &["commit", "-q", "-m", "init"]);
+
+        let (parsed, sources) = parse_args(&["run", "--worktree", "feature/login"])?;
+        let resolved = config::resolve(&parsed, &sources)?;
+        let branch = resolved.worktree.as_deref().expect("worktree flag");
+        let worktree = Worktree::plan(cwd, branch, None)?;
+        assert_eq!(worktree.branch, "feature/login");
+        assert!(worktree.path.ends_with("worktree-repo"));
+        let branch_dir = worktree.path.parent().expect("branch dir");
+        assert!(
+            branch_dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("feature-login-")),
+            "{}",
+            worktree.path.display()
+        );
+        assert_ne!(
+            Worktree::plan(cwd, "feature-login", None)?.path,
+            worktree.path
+        );
+        assert!(
+            worktree
+                .path
+                .starts_with(config::data_dir()?.join("worktrees"))
+        );
+
+        worktree.create(|| panic!("a new worktree needs no container list"))?;
+        assert!(worktree.path.join("README.md").is_file());
+        worktree.create(|| Ok(Vec::new()))?;
+        let err = worktree
+            .create(|| {
+                Ok(vec![ContainerSummary {
+                    name: "dungeon-login".to_string(),
+                    session: Some("login".to_string()),
+                    workspace: worktree.path.display().to_string(),
+                    ..ContainerSummary::default()
+                }])
+            })
+            .expect_err("worktree in use");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "ERROR: worktree {} on branch feature/login is in use by session \"login\"; stop it or pick another branch",
+                worktree.path.display()
+            )
+        );
+
+        let spec = engine::build_resolved_container_command(
+            &resolved.settings,
+            &resolved,
+            Some(&worktree.path),
+        )?;
+        let command = spec.args.join(" ");
+        let git_dir = cwd.canonicalize()?.join(".git");
+        assert!(
+            command.contains("-w /workspace/worktree-repo ")
+                && command.contains(&format!(
+                    "-v {}:/workspace/worktree-repo -v {}:{} ",
+                    worktree.path.display(),
+                    git_dir.display(),
+                    git_dir.display()
+                ))
+                && command.contains(&format!("dungeon.workspace={} ", worktree.path.display())),
+            "{command}"
+        );
+
+        worktree.remove(true)?;
+        assert!(!worktree.path.exists());
+        let branches = Command::new("git")
+            .arg("-C")
+            .arg(cwd)
+            .args(["branch", "--list", "feature/login"])
+            .output()?;
+        assert!(branches.stdout.is_empty());
+        Ok(())
+    })
+    .expect("worktree session");
+}
+
+#[test]
+fn worktree_branch_defaults_to_the_session_name() {
+    let input = TestInput {
+        toml: "",
+        args: &[],
+        env: &[],
+        cwd_name: "worktree-repo",
+        cwd_entries: &["README.md"],
+        fs_entries: &[],
+    };
+    in_test_env(input, |cwd| {
+        git(cwd, &["init", "-q"]);
+        let worktree = Worktree::plan(cwd, "", Some("agent"))?;
+        assert_eq!(worktree.branch, "dungeon/agent");
+        assert!(
+            worktree
+                .path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("dungeon-agent-")),
+            "{}",
+            worktree.path.display()
+        );
+
+        let worktree = Worktree::plan(cwd, "", None)?;
+        assert!(
+            worktree
+                .branch
+                .ends_with(&format!("-{}", std::process::id())),
+            "{}",
+            worktree.branch
+        );
+
+        let err = Worktree::plan(cwd, "bad..name", None).expect_err("bad branch");
+        assert_eq!(
+            err.to_string(),
+            "ERROR: invalid worktree branch name \"bad..name\""
+        );
+        Ok(())
+    })
+    .expect("worktree plan");
+}
+
+#[test]
+fn worktree_rejects_explicit_paths_and_non_repositories() {
+    let input = TestInput {
+        toml: "",
+        args: &["run", "--skip-cwd", "--worktree"],
+        env: &[],
+        cwd_name: "worktree-repo",
+        cwd_entries: &["README.md"],
+        fs_entries: &[],
+    };
+    let err = try_resolve_input(input).expect_err("skip-cwd");
+    assert_eq!(
+        err.to_string(),
+        "ERROR: --worktree cannot be used with --skip-cwd or explicit paths"
+    );
+
+    let input = TestInput {
+        toml: "",
+        args: &[],
+        env: &[],
+        cwd_name: "worktree-repo",
+        cwd_entries: &["README.md"],
+        fs_entries: &[],
+    };
+    in_test_env(input, |cwd| {
+        let err = Worktree::plan(cwd, "", None).expect_err("not a repository");
+        assert!(
+            err.to_string()
+                .starts_with("ERROR: --worktree needs a git repository, but "),
+            "{err}"
+        );
+        Ok(())
+    })
+    .expect("non-repository");
+}